@@ -0,0 +1,37 @@
+//! Bounds-checked reads and writes of the integers in file headers
+
+use crate::swizzle::{SwizzleError, TextureSlice};
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32, SwizzleError> {
+    Ok(u32::from_le_bytes(read(data, offset)?))
+}
+
+pub(crate) fn read_u32_be(data: &[u8], offset: usize) -> Result<u32, SwizzleError> {
+    Ok(u32::from_be_bytes(read(data, offset)?))
+}
+
+pub(crate) fn read_u16_be(data: &[u8], offset: usize) -> Result<u16, SwizzleError> {
+    Ok(u16::from_be_bytes(read(data, offset)?))
+}
+
+pub(crate) fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn write_u32_be(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+pub(crate) fn write_u16_be(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Returns `len` bytes of `data` from `offset`
+pub(crate) fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8], SwizzleError> {
+    data.get(offset..offset + len)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Source))
+}
+
+fn read<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], SwizzleError> {
+    Ok(slice(data, offset, N)?.try_into().unwrap())
+}
@@ -0,0 +1,547 @@
+//! Reading and writing DirectDraw Surface files.
+//!
+//! The data of a DDS file is laid out like the linear side of the swizzlers: layer after layer,
+//! each with its whole mip chain. Surfaces read from a file can go straight into
+//! [`Swizzler::swizzle_surface`](crate::swizzle::Swizzler::swizzle_surface), and deswizzled
+//! surfaces can be written out as-is.
+//!
+//! ```
+//! # use swizzleinator::{dds::{self, Dds, HeaderVersion}, format::DxgiFormat, swizzle::SurfaceDesc};
+//! let desc = SurfaceDesc::builder(DxgiFormat::BC1_UNORM)
+//!     .width(64)
+//!     .height(64)
+//!     .mip_count(7)
+//!     .build()
+//!     .unwrap();
+//! let data = vec![0u8; dds::data_size(&desc).unwrap()];
+//!
+//! let mut file = vec![0u8; dds::header_size(HeaderVersion::Legacy) + data.len()];
+//! dds::write(&mut file, &desc, &data, HeaderVersion::Legacy).unwrap();
+//!
+//! let dds = Dds::read(&file).unwrap();
+//! assert_eq!(dds.desc(), &desc);
+//! ```
+
+use crate::{
+    bytes::{read_u32, slice, write_u32},
+    format::DxgiFormat,
+    swizzle::{Format, SurfaceDesc, SwizzleError, TextureKind, TextureSlice, gcn::TileMode},
+};
+
+const MAGIC: u32 = u32::from_le_bytes(*b"DDS ");
+const HEADER_SIZE: usize = 124;
+const PIXEL_FORMAT_SIZE: usize = 32;
+const DX10_HEADER_SIZE: usize = 20;
+const XBOX_HEADER_SIZE: usize = 36;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSD_DEPTH: u32 = 0x800000;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+const DIMENSION_TEXTURE2D: u32 = 3;
+const DIMENSION_TEXTURE3D: u32 = 4;
+const RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+const FOURCC_DX10: u32 = u32::from_le_bytes(*b"DX10");
+const FOURCC_XBOX: u32 = u32::from_le_bytes(*b"XBOX");
+
+/// Which header a DDS file is written with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HeaderVersion {
+    /// The original header, which only describes a subset of formats and no arrays
+    Legacy,
+    /// The legacy header followed by a `DDS_HEADER_DXT10`, which can describe any DXGI format
+    #[default]
+    Dx10,
+}
+
+/// The `DDS_HEADER_XBOX` fields of tiled Xbox One textures, which follow the ones shared with
+/// the DX10 header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XboxHeader {
+    pub tile_mode: TileMode,
+    /// Alignment of the swizzled data in memory
+    pub base_alignment: u32,
+    /// Size of the swizzled data in bytes
+    pub data_size: u32,
+    pub xdk_version: u32,
+}
+
+/// A DDS file, borrowing its data from the file
+#[derive(Debug, Clone, Copy)]
+pub struct Dds<'a> {
+    desc: SurfaceDesc<DxgiFormat>,
+    xbox: Option<XboxHeader>,
+    data: &'a [u8],
+}
+
+impl<'a> Dds<'a> {
+    /// Parses the headers of a DDS file.
+    /// For Xbox One files the tile mode of the extension header is set on the surface, ready to
+    /// be deswizzled with [`XboxOne`](crate::swizzle::xbox::XboxOne).
+    pub fn read(file: &'a [u8]) -> Result<Self, SwizzleError> {
+        let magic = read_u32(file, 0)?;
+        if magic != MAGIC {
+            return Err(SwizzleError::InvalidMagic(magic));
+        }
+
+        let header = slice(file, 4, HEADER_SIZE)?;
+        let height = read_u32(header, 8)? as usize;
+        let width = read_u32(header, 12)? as usize;
+        let depth = read_u32(header, 20)? as usize;
+        let mip_count = (read_u32(header, 24)? as usize).max(1);
+        let caps2 = read_u32(header, 108)?;
+
+        let pixel_format = PixelFormat::read(&header[72..72 + PIXEL_FORMAT_SIZE])?;
+        let mut offset = 4 + HEADER_SIZE;
+
+        let mut xbox = None;
+        let (format, kind) = if pixel_format.flags & DDPF_FOURCC != 0
+            && matches!(pixel_format.four_cc, FOURCC_DX10 | FOURCC_XBOX)
+        {
+            // The Xbox header starts with the same fields as the DX10 one
+            let dx10_size = if pixel_format.four_cc == FOURCC_XBOX {
+                XBOX_HEADER_SIZE
+            } else {
+                DX10_HEADER_SIZE
+            };
+            let dx10 = slice(file, offset, dx10_size)?;
+            offset += dx10_size;
+
+            let format = DxgiFormat::try_from(read_u32(dx10, 0)?)?;
+            let dimension = read_u32(dx10, 4)?;
+            let misc_flag = read_u32(dx10, 8)?;
+            let array_size = read_u32(dx10, 12)? as usize;
+
+            let kind = if dimension == DIMENSION_TEXTURE3D {
+                TextureKind::Volume
+            } else if misc_flag & RESOURCE_MISC_TEXTURECUBE != 0 {
+                if array_size != 1 {
+                    return Err(SwizzleError::InvalidArraySize(array_size * 6));
+                }
+                TextureKind::Cube
+            } else if array_size > 1 {
+                TextureKind::Array(array_size)
+            } else {
+                TextureKind::Texture2D
+            };
+
+            if pixel_format.four_cc == FOURCC_XBOX {
+                xbox = Some(XboxHeader {
+                    tile_mode: TileMode::try_from(read_u32(dx10, 20)?)?,
+                    base_alignment: read_u32(dx10, 24)?,
+                    data_size: read_u32(dx10, 28)?,
+                    xdk_version: read_u32(dx10, 32)?,
+                });
+            }
+
+            (format, kind)
+        } else {
+            let kind = if caps2 & DDSCAPS2_VOLUME != 0 {
+                TextureKind::Volume
+            } else if caps2 & DDSCAPS2_CUBEMAP != 0 {
+                if caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+                    return Err(SwizzleError::InvalidArraySize(
+                        (caps2 & DDSCAPS2_CUBEMAP_ALLFACES).count_ones() as usize,
+                    ));
+                }
+                TextureKind::Cube
+            } else {
+                TextureKind::Texture2D
+            };
+            (pixel_format.dxgi_format()?, kind)
+        };
+
+        let depth = if kind == TextureKind::Volume {
+            depth.max(1)
+        } else {
+            1
+        };
+        let mut builder = SurfaceDesc::builder(format)
+            .width(width)
+            .height(height)
+            .kind(kind)
+            .depth(depth)
+            .mip_count(mip_count);
+        if let Some(xbox) = xbox {
+            builder = builder.tile_mode(xbox.tile_mode);
+        }
+        let desc = builder.build()?;
+
+        let data = match xbox {
+            Some(xbox) => slice(file, offset, xbox.data_size as usize)?,
+            None => slice(file, offset, data_size(&desc)?)?,
+        };
+
+        Ok(Dds { desc, xbox, data })
+    }
+
+    pub fn desc(&self) -> &SurfaceDesc<DxgiFormat> {
+        &self.desc
+    }
+
+    /// The Xbox One extension header, if the file has one
+    pub fn xbox(&self) -> Option<&XboxHeader> {
+        self.xbox.as_ref()
+    }
+
+    /// The texel data of every layer and level. Swizzled if the file has an Xbox header.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Size in bytes of the headers of a file, including the magic
+pub fn header_size(version: HeaderVersion) -> usize {
+    match version {
+        HeaderVersion::Legacy => 4 + HEADER_SIZE,
+        HeaderVersion::Dx10 => 4 + HEADER_SIZE + DX10_HEADER_SIZE,
+    }
+}
+
+/// Size in bytes of the texel data of every layer and level of a surface
+pub fn data_size(desc: &SurfaceDesc<DxgiFormat>) -> Result<usize, SwizzleError> {
+    let (mut width, mut height, mut depth) = desc.dimensions();
+    let mut layer_size = 0;
+    for _ in 0..desc.mip_count() {
+        layer_size += desc.format().calculate_pitch(width, height)?.1 * depth;
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+        depth = (depth / 2).max(1);
+    }
+    Ok(layer_size * desc.array_size())
+}
+
+/// Writes a DDS file holding `data`, and returns the number of bytes written.
+///
+/// Legacy headers can't describe texture arrays, nor formats without a `D3DFORMAT` equivalent.
+pub fn write(
+    dest: &mut [u8],
+    desc: &SurfaceDesc<DxgiFormat>,
+    data: &[u8],
+    version: HeaderVersion,
+) -> Result<usize, SwizzleError> {
+    let data = slice(data, 0, data_size(desc)?)?;
+    let header_size = header_size(version);
+    let file = dest
+        .get_mut(..header_size + data.len())
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+    file.fill(0);
+
+    let pixel_format = match version {
+        HeaderVersion::Legacy => {
+            if let TextureKind::Array(layers) = desc.kind() {
+                return Err(SwizzleError::InvalidArraySize(layers));
+            }
+            PixelFormat::from_dxgi(*desc.format())?
+        }
+        HeaderVersion::Dx10 => PixelFormat {
+            flags: DDPF_FOURCC,
+            four_cc: FOURCC_DX10,
+            ..Default::default()
+        },
+    };
+
+    let format = desc.format();
+    let (pitch, slice_size) = format.calculate_pitch(desc.width(), desc.height())?;
+
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+    let mut caps = DDSCAPS_TEXTURE;
+    let mut caps2 = 0;
+    flags |= if format.is_compressed() {
+        DDSD_LINEARSIZE
+    } else {
+        DDSD_PITCH
+    };
+    if desc.mip_count() > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+    match desc.kind() {
+        TextureKind::Volume => {
+            flags |= DDSD_DEPTH;
+            caps |= DDSCAPS_COMPLEX;
+            caps2 |= DDSCAPS2_VOLUME;
+        }
+        TextureKind::Cube => {
+            caps |= DDSCAPS_COMPLEX;
+            caps2 |= DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALLFACES;
+        }
+        TextureKind::Array(_) => caps |= DDSCAPS_COMPLEX,
+        TextureKind::Texture2D => {}
+    }
+
+    let header = &mut file[4..4 + HEADER_SIZE];
+    write_u32(header, 0, HEADER_SIZE as u32);
+    write_u32(header, 4, flags);
+    write_u32(header, 8, desc.height() as u32);
+    write_u32(header, 12, desc.width() as u32);
+    write_u32(
+        header,
+        16,
+        if format.is_compressed() {
+            slice_size
+        } else {
+            pitch
+        } as u32,
+    );
+    write_u32(header, 20, desc.depth() as u32);
+    write_u32(header, 24, desc.mip_count() as u32);
+    pixel_format.write(&mut header[72..72 + PIXEL_FORMAT_SIZE]);
+    write_u32(header, 104, caps);
+    write_u32(header, 108, caps2);
+    write_u32(file, 0, MAGIC);
+
+    if version == HeaderVersion::Dx10 {
+        let (dimension, misc_flag, array_size) = match desc.kind() {
+            TextureKind::Volume => (DIMENSION_TEXTURE3D, 0, 1),
+            TextureKind::Cube => (DIMENSION_TEXTURE2D, RESOURCE_MISC_TEXTURECUBE, 1),
+            kind => (DIMENSION_TEXTURE2D, 0, kind.layers() as u32),
+        };
+        let dx10 = &mut file[4 + HEADER_SIZE..header_size];
+        write_u32(dx10, 0, (*format).into());
+        write_u32(dx10, 4, dimension);
+        write_u32(dx10, 8, misc_flag);
+        write_u32(dx10, 12, array_size);
+    }
+
+    file[header_size..].copy_from_slice(data);
+    Ok(file.len())
+}
+
+/// `DDS_PIXELFORMAT`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PixelFormat {
+    flags: u32,
+    four_cc: u32,
+    bit_count: u32,
+    /// Red, green, blue and alpha masks
+    masks: [u32; 4],
+}
+
+impl PixelFormat {
+    const fn four_cc(four_cc: u32) -> Self {
+        PixelFormat {
+            flags: DDPF_FOURCC,
+            four_cc,
+            bit_count: 0,
+            masks: [0; 4],
+        }
+    }
+
+    const fn masks(flags: u32, bit_count: u32, masks: [u32; 4]) -> Self {
+        PixelFormat {
+            flags,
+            four_cc: 0,
+            bit_count,
+            masks,
+        }
+    }
+
+    fn read(data: &[u8]) -> Result<Self, SwizzleError> {
+        Ok(PixelFormat {
+            flags: read_u32(data, 4)?,
+            four_cc: read_u32(data, 8)?,
+            bit_count: read_u32(data, 12)?,
+            masks: [
+                read_u32(data, 16)?,
+                read_u32(data, 20)?,
+                read_u32(data, 24)?,
+                read_u32(data, 28)?,
+            ],
+        })
+    }
+
+    fn write(&self, data: &mut [u8]) {
+        write_u32(data, 0, PIXEL_FORMAT_SIZE as u32);
+        write_u32(data, 4, self.flags);
+        write_u32(data, 8, self.four_cc);
+        write_u32(data, 12, self.bit_count);
+        for (i, mask) in self.masks.iter().enumerate() {
+            write_u32(data, 16 + i * 4, *mask);
+        }
+    }
+
+    fn from_dxgi(format: DxgiFormat) -> Result<Self, SwizzleError> {
+        LEGACY_FORMATS
+            .iter()
+            .find(|(f, _)| *f == format)
+            .map(|(_, pf)| *pf)
+            .ok_or(SwizzleError::FormatOutOfRange(format.into()))
+    }
+
+    fn dxgi_format(&self) -> Result<DxgiFormat, SwizzleError> {
+        let legacy = if self.flags & DDPF_FOURCC != 0 {
+            LEGACY_FORMATS
+                .iter()
+                .chain(LEGACY_FOURCC_ALIASES)
+                .find(|(_, pf)| pf.flags & DDPF_FOURCC != 0 && pf.four_cc == self.four_cc)
+        } else {
+            LEGACY_FORMATS.iter().find(|(_, pf)| {
+                pf.flags & DDPF_FOURCC == 0
+                    && pf.bit_count == self.bit_count
+                    && pf.masks == self.masks
+            })
+        };
+        legacy
+            .map(|(format, _)| *format)
+            .ok_or(SwizzleError::FormatOutOfRange(self.four_cc))
+    }
+}
+
+const fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
+/// Formats a legacy header can describe, and how they are written
+const LEGACY_FORMATS: &[(DxgiFormat, PixelFormat)] = &[
+    (
+        DxgiFormat::BC1_UNORM,
+        PixelFormat::four_cc(four_cc(b"DXT1")),
+    ),
+    (
+        DxgiFormat::BC2_UNORM,
+        PixelFormat::four_cc(four_cc(b"DXT3")),
+    ),
+    (
+        DxgiFormat::BC3_UNORM,
+        PixelFormat::four_cc(four_cc(b"DXT5")),
+    ),
+    (
+        DxgiFormat::BC4_UNORM,
+        PixelFormat::four_cc(four_cc(b"ATI1")),
+    ),
+    (
+        DxgiFormat::BC4_SNORM,
+        PixelFormat::four_cc(four_cc(b"BC4S")),
+    ),
+    (
+        DxgiFormat::BC5_UNORM,
+        PixelFormat::four_cc(four_cc(b"ATI2")),
+    ),
+    (
+        DxgiFormat::BC5_SNORM,
+        PixelFormat::four_cc(four_cc(b"BC5S")),
+    ),
+    (
+        DxgiFormat::R8G8_B8G8_UNORM,
+        PixelFormat::four_cc(four_cc(b"RGBG")),
+    ),
+    (
+        DxgiFormat::G8R8_G8B8_UNORM,
+        PixelFormat::four_cc(four_cc(b"GRGB")),
+    ),
+    (DxgiFormat::YUY2, PixelFormat::four_cc(four_cc(b"YUY2"))),
+    // D3DFORMAT values
+    (DxgiFormat::R16G16B16A16_UNORM, PixelFormat::four_cc(36)),
+    (DxgiFormat::R16G16B16A16_SNORM, PixelFormat::four_cc(110)),
+    (DxgiFormat::R16_FLOAT, PixelFormat::four_cc(111)),
+    (DxgiFormat::R16G16_FLOAT, PixelFormat::four_cc(112)),
+    (DxgiFormat::R16G16B16A16_FLOAT, PixelFormat::four_cc(113)),
+    (DxgiFormat::R32_FLOAT, PixelFormat::four_cc(114)),
+    (DxgiFormat::R32G32_FLOAT, PixelFormat::four_cc(115)),
+    (DxgiFormat::R32G32B32A32_FLOAT, PixelFormat::four_cc(116)),
+    (
+        DxgiFormat::R8G8B8A8_UNORM,
+        PixelFormat::masks(
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            32,
+            [0xFF, 0xFF00, 0xFF0000, 0xFF000000],
+        ),
+    ),
+    (
+        DxgiFormat::B8G8R8A8_UNORM,
+        PixelFormat::masks(
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            32,
+            [0xFF0000, 0xFF00, 0xFF, 0xFF000000],
+        ),
+    ),
+    (
+        DxgiFormat::B8G8R8X8_UNORM,
+        PixelFormat::masks(DDPF_RGB, 32, [0xFF0000, 0xFF00, 0xFF, 0]),
+    ),
+    (
+        DxgiFormat::R10G10B10A2_UNORM,
+        PixelFormat::masks(
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            32,
+            [0x3FF, 0xFFC00, 0x3FF00000, 0xC0000000],
+        ),
+    ),
+    (
+        DxgiFormat::R16G16_UNORM,
+        PixelFormat::masks(DDPF_RGB, 32, [0xFFFF, 0xFFFF0000, 0, 0]),
+    ),
+    (
+        DxgiFormat::B5G6R5_UNORM,
+        PixelFormat::masks(DDPF_RGB, 16, [0xF800, 0x7E0, 0x1F, 0]),
+    ),
+    (
+        DxgiFormat::B5G5R5A1_UNORM,
+        PixelFormat::masks(
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            16,
+            [0x7C00, 0x3E0, 0x1F, 0x8000],
+        ),
+    ),
+    (
+        DxgiFormat::B4G4R4A4_UNORM,
+        PixelFormat::masks(DDPF_RGB | DDPF_ALPHAPIXELS, 16, [0xF00, 0xF0, 0xF, 0xF000]),
+    ),
+    (
+        DxgiFormat::R8G8_UNORM,
+        PixelFormat::masks(DDPF_LUMINANCE | DDPF_ALPHAPIXELS, 16, [0xFF, 0, 0, 0xFF00]),
+    ),
+    (
+        DxgiFormat::R16_UNORM,
+        PixelFormat::masks(DDPF_LUMINANCE, 16, [0xFFFF, 0, 0, 0]),
+    ),
+    (
+        DxgiFormat::R8_UNORM,
+        PixelFormat::masks(DDPF_LUMINANCE, 8, [0xFF, 0, 0, 0]),
+    ),
+    (
+        DxgiFormat::A8_UNORM,
+        PixelFormat::masks(DDPF_ALPHA, 8, [0, 0, 0, 0xFF]),
+    ),
+];
+
+/// Other FourCCs written by common tools, only used when reading
+const LEGACY_FOURCC_ALIASES: &[(DxgiFormat, PixelFormat)] = &[
+    (
+        DxgiFormat::BC2_UNORM,
+        PixelFormat::four_cc(four_cc(b"DXT2")),
+    ),
+    (
+        DxgiFormat::BC3_UNORM,
+        PixelFormat::four_cc(four_cc(b"DXT4")),
+    ),
+    (
+        DxgiFormat::BC4_UNORM,
+        PixelFormat::four_cc(four_cc(b"BC4U")),
+    ),
+    (
+        DxgiFormat::BC5_UNORM,
+        PixelFormat::four_cc(four_cc(b"BC5U")),
+    ),
+];
@@ -0,0 +1,1445 @@
+//! Decoding textures to RGBA, to preview deswizzled data.
+//!
+//! Requires the `decode` feature. Like the rest of the crate, nothing is allocated: texels are
+//! decoded into caller provided buffers, laid out like the linear output of the deswizzlers.
+//!
+//! BC1 to BC5 and BC7 decode to 8-bit RGBA, BC6H to half or single precision floats. Every
+//! format can be decoded to every output, so thumbnails can always be made with
+//! [`decode_rgba8`]. The Xbox 360 only CTX1 and DXT3A formats are decoded the same way, and
+//! CTX1 can also be converted to BC5 with [`ctx1_to_bc5`]. Uncompressed texels of any
+//! [`TexelFormat`] are decoded with [`decode_texels_rgba8`] and [`decode_texels_rgba32f`].
+//!
+//! ```
+//! # use swizzleinator::decode::{self, BcFormat};
+//! // A 4x4 BC1 block of solid red
+//! let block = [0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0];
+//! let mut rgba = [0u8; 4 * 4 * 4];
+//! decode::decode_rgba8(&block, &mut rgba, (4, 4, 1), BcFormat::Bc1).unwrap();
+//! assert_eq!(rgba[..4], [0xFF, 0, 0, 0xFF]);
+//! ```
+
+use crate::{
+    bytes::slice,
+    format::{DxgiFormat, GcmSurfaceFormat, GcnSurfaceFormat, XenosSurfaceFormat},
+    gnf::TextureDescriptor,
+    swizzle::{Format, SwizzleError, TextureSlice},
+};
+
+/// The block compression formats that can be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BcFormat {
+    /// DXT1
+    Bc1,
+    /// DXT2 and DXT3
+    Bc2,
+    /// DXT4 and DXT5
+    Bc3,
+    Bc4,
+    Bc4Signed,
+    Bc5,
+    Bc5Signed,
+    Bc6h,
+    Bc6hSigned,
+    Bc7,
+    /// Xbox 360 two channel normal maps, with 8-bit endpoints and 4 interpolated values
+    Ctx1,
+    /// Xbox 360 single channel 4-bit values, laid out like the alpha of BC2
+    Dxt3a,
+    /// [`BcFormat::Dxt3a`] with each bit of a value read as a separate 1-bit channel, from
+    /// R in the least significant bit to A in the most significant one
+    Dxt3aAs1111,
+}
+
+impl BcFormat {
+    /// Size in bytes of a 4x4 block
+    pub fn block_size(self) -> usize {
+        match self {
+            BcFormat::Bc1
+            | BcFormat::Bc4
+            | BcFormat::Bc4Signed
+            | BcFormat::Ctx1
+            | BcFormat::Dxt3a
+            | BcFormat::Dxt3aAs1111 => 8,
+            _ => 16,
+        }
+    }
+}
+
+impl TryFrom<DxgiFormat> for BcFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: DxgiFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            DxgiFormat::BC1_TYPELESS | DxgiFormat::BC1_UNORM | DxgiFormat::BC1_UNORM_SRGB => {
+                BcFormat::Bc1
+            }
+            DxgiFormat::BC2_TYPELESS | DxgiFormat::BC2_UNORM | DxgiFormat::BC2_UNORM_SRGB => {
+                BcFormat::Bc2
+            }
+            DxgiFormat::BC3_TYPELESS | DxgiFormat::BC3_UNORM | DxgiFormat::BC3_UNORM_SRGB => {
+                BcFormat::Bc3
+            }
+            DxgiFormat::BC4_TYPELESS | DxgiFormat::BC4_UNORM => BcFormat::Bc4,
+            DxgiFormat::BC4_SNORM => BcFormat::Bc4Signed,
+            DxgiFormat::BC5_TYPELESS | DxgiFormat::BC5_UNORM => BcFormat::Bc5,
+            DxgiFormat::BC5_SNORM => BcFormat::Bc5Signed,
+            DxgiFormat::BC6H_TYPELESS | DxgiFormat::BC6H_UF16 => BcFormat::Bc6h,
+            DxgiFormat::BC6H_SF16 => BcFormat::Bc6hSigned,
+            DxgiFormat::BC7_TYPELESS | DxgiFormat::BC7_UNORM | DxgiFormat::BC7_UNORM_SRGB => {
+                BcFormat::Bc7
+            }
+            e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+        })
+    }
+}
+
+impl TryFrom<GcnSurfaceFormat> for BcFormat {
+    type Error = SwizzleError;
+
+    /// The signedness of GCN textures is stored separately, so BC4, BC5 and BC6H are unsigned.
+    fn try_from(value: GcnSurfaceFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            GcnSurfaceFormat::BC1 => BcFormat::Bc1,
+            GcnSurfaceFormat::BC2 => BcFormat::Bc2,
+            GcnSurfaceFormat::BC3 => BcFormat::Bc3,
+            GcnSurfaceFormat::BC4 => BcFormat::Bc4,
+            GcnSurfaceFormat::BC5 => BcFormat::Bc5,
+            GcnSurfaceFormat::BC6 => BcFormat::Bc6h,
+            GcnSurfaceFormat::BC7 => BcFormat::Bc7,
+            e => return Err(SwizzleError::FormatOutOfRange(e as u32)),
+        })
+    }
+}
+
+impl TryFrom<XenosSurfaceFormat> for BcFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: XenosSurfaceFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            XenosSurfaceFormat::k_DXT1 | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16 => BcFormat::Bc1,
+            XenosSurfaceFormat::k_DXT2_3 | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16 => {
+                BcFormat::Bc2
+            }
+            XenosSurfaceFormat::k_DXT4_5 | XenosSurfaceFormat::k_DXT4_5_AS_16_16_16_16 => {
+                BcFormat::Bc3
+            }
+            XenosSurfaceFormat::k_DXT5A => BcFormat::Bc4,
+            XenosSurfaceFormat::k_DXN => BcFormat::Bc5,
+            XenosSurfaceFormat::k_CTX1 => BcFormat::Ctx1,
+            XenosSurfaceFormat::k_DXT3A => BcFormat::Dxt3a,
+            XenosSurfaceFormat::k_DXT3A_AS_1_1_1_1 => BcFormat::Dxt3aAs1111,
+            e => return Err(SwizzleError::FormatOutOfRange(e as u32)),
+        })
+    }
+}
+
+impl TryFrom<GcmSurfaceFormat> for BcFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: GcmSurfaceFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            GcmSurfaceFormat::COMPRESSED_DXT1 => BcFormat::Bc1,
+            GcmSurfaceFormat::COMPRESSED_DXT23 => BcFormat::Bc2,
+            GcmSurfaceFormat::COMPRESSED_DXT45 => BcFormat::Bc3,
+            e => return Err(SwizzleError::FormatOutOfRange(e as u32)),
+        })
+    }
+}
+
+/// Decodes the blocks of `source` to 8-bit RGBA texels, returning the number of bytes written.
+/// * `dimensions` - Size of the image in texels. Slices follow each other.
+///
+/// BC6H is clamped to 0-1, and signed BC4 and BC5 are mapped from -1-1 to 0-1.
+pub fn decode_rgba8(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: BcFormat,
+) -> Result<usize, SwizzleError> {
+    decode_image(source, dest, dimensions, format, 4, |texels, i, dest| {
+        dest.copy_from_slice(&texels.rgba8(i));
+    })
+}
+
+/// Decodes the blocks of `source` to little endian half precision RGBA texels, returning the
+/// number of bytes written.
+/// * `dimensions` - Size of the image in texels. Slices follow each other.
+pub fn decode_rgba16f(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: BcFormat,
+) -> Result<usize, SwizzleError> {
+    decode_image(source, dest, dimensions, format, 8, |texels, i, dest| {
+        for (dest, channel) in dest.chunks_exact_mut(2).zip(texels.rgba16f(i)) {
+            dest.copy_from_slice(&channel.to_le_bytes());
+        }
+    })
+}
+
+/// Decodes the blocks of `source` to little endian single precision RGBA texels, returning the
+/// number of bytes written.
+/// * `dimensions` - Size of the image in texels. Slices follow each other.
+pub fn decode_rgba32f(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: BcFormat,
+) -> Result<usize, SwizzleError> {
+    decode_image(source, dest, dimensions, format, 16, |texels, i, dest| {
+        for (dest, channel) in dest.chunks_exact_mut(4).zip(texels.rgba32f(i)) {
+            dest.copy_from_slice(&channel.to_le_bytes());
+        }
+    })
+}
+
+/// Decodes a single block to 8-bit RGBA texels, in rows of 4. See [`decode_rgba8`].
+pub fn decode_block_rgba8(block: &[u8], format: BcFormat) -> Result<[[u8; 4]; 16], SwizzleError> {
+    let texels = decode_block(slice(block, 0, format.block_size())?, format);
+    Ok(core::array::from_fn(|i| texels.rgba8(i)))
+}
+
+/// Decodes a single block to half precision RGBA texels, in rows of 4
+pub fn decode_block_rgba16f(
+    block: &[u8],
+    format: BcFormat,
+) -> Result<[[u16; 4]; 16], SwizzleError> {
+    let texels = decode_block(slice(block, 0, format.block_size())?, format);
+    Ok(core::array::from_fn(|i| texels.rgba16f(i)))
+}
+
+/// Decodes a single block to single precision RGBA texels, in rows of 4
+pub fn decode_block_rgba32f(
+    block: &[u8],
+    format: BcFormat,
+) -> Result<[[f32; 4]; 16], SwizzleError> {
+    let texels = decode_block(slice(block, 0, format.block_size())?, format);
+    Ok(core::array::from_fn(|i| texels.rgba32f(i)))
+}
+
+fn decode_image(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: BcFormat,
+    texel_size: usize,
+    write: impl Fn(&Texels, usize, &mut [u8]),
+) -> Result<usize, SwizzleError> {
+    let (width, height, depth) = dimensions;
+    let width_in_blocks = width.div_ceil(4);
+    let height_in_blocks = height.div_ceil(4);
+    let block_size = format.block_size();
+
+    let size = width * height * depth * texel_size;
+    let dest = dest
+        .get_mut(..size)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+
+    for z in 0..depth {
+        for block_y in 0..height_in_blocks {
+            for block_x in 0..width_in_blocks {
+                let offset =
+                    ((z * height_in_blocks + block_y) * width_in_blocks + block_x) * block_size;
+                let texels = decode_block(slice(source, offset, block_size)?, format);
+
+                for i in 0..16 {
+                    let (x, y) = (block_x * 4 + i % 4, block_y * 4 + i / 4);
+                    if x >= width || y >= height {
+                        continue;
+                    }
+                    let offset = ((z * height + y) * width + x) * texel_size;
+                    write(&texels, i, &mut dest[offset..offset + texel_size]);
+                }
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+/// The texels of a decoded block, in rows of 4
+enum Texels {
+    Unorm([[u8; 4]; 16]),
+    Snorm([[i8; 4]; 16]),
+    Half([[u16; 4]; 16]),
+}
+
+impl Texels {
+    fn rgba8(&self, i: usize) -> [u8; 4] {
+        match self {
+            Texels::Unorm(texels) => texels[i],
+            Texels::Snorm(texels) => {
+                texels[i].map(|c| ((c.max(-127) as i32 + 127) * 255 + 127).div_euclid(254) as u8)
+            }
+            Texels::Half(texels) => {
+                texels[i].map(|c| (half_to_f32(c).clamp(0.0, 1.0) * 255.0 + 0.5) as u8)
+            }
+        }
+    }
+
+    fn rgba16f(&self, i: usize) -> [u16; 4] {
+        match self {
+            Texels::Half(texels) => texels[i],
+            _ => self.rgba32f(i).map(f32_to_half),
+        }
+    }
+
+    fn rgba32f(&self, i: usize) -> [f32; 4] {
+        match self {
+            Texels::Unorm(texels) => texels[i].map(|c| c as f32 / 255.0),
+            Texels::Snorm(texels) => texels[i].map(|c| c.max(-127) as f32 / 127.0),
+            Texels::Half(texels) => texels[i].map(half_to_f32),
+        }
+    }
+}
+
+fn decode_block(block: &[u8], format: BcFormat) -> Texels {
+    match format {
+        BcFormat::Bc1 => Texels::Unorm(decode_bc1(block, false)),
+        BcFormat::Bc2 => {
+            let mut texels = decode_bc1(&block[8..], true);
+            let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+            for (i, texel) in texels.iter_mut().enumerate() {
+                texel[3] = ((alpha >> (i * 4)) & 0xF) as u8 * 17;
+            }
+            Texels::Unorm(texels)
+        }
+        BcFormat::Bc3 => {
+            let mut texels = decode_bc1(&block[8..], true);
+            let alpha = decode_alpha(block);
+            for (texel, alpha) in texels.iter_mut().zip(alpha) {
+                texel[3] = alpha;
+            }
+            Texels::Unorm(texels)
+        }
+        BcFormat::Bc4 => Texels::Unorm(decode_alpha(block).map(|r| [r, 0, 0, 0xFF])),
+        BcFormat::Bc4Signed => Texels::Snorm(decode_alpha_signed(block).map(|r| [r, 0, 0, 0x7F])),
+        BcFormat::Bc5 => {
+            let (red, green) = (decode_alpha(block), decode_alpha(&block[8..]));
+            Texels::Unorm(core::array::from_fn(|i| [red[i], green[i], 0, 0xFF]))
+        }
+        BcFormat::Bc5Signed => {
+            let (red, green) = (decode_alpha_signed(block), decode_alpha_signed(&block[8..]));
+            Texels::Snorm(core::array::from_fn(|i| [red[i], green[i], 0, 0x7F]))
+        }
+        BcFormat::Bc6h => Texels::Half(decode_bc6h(block, false)),
+        BcFormat::Bc6hSigned => Texels::Half(decode_bc6h(block, true)),
+        BcFormat::Bc7 => Texels::Unorm(decode_bc7(block)),
+        BcFormat::Ctx1 => {
+            let palette = ctx1_palette(block);
+            let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+            Texels::Unorm(core::array::from_fn(|i| {
+                let [x, y] = palette[(indices >> (i * 2)) as usize & 3];
+                [x, y, 0, 0xFF]
+            }))
+        }
+        BcFormat::Dxt3a => Texels::Unorm(dxt3a_values(block).map(|x| [x * 17, 0, 0, 0xFF])),
+        BcFormat::Dxt3aAs1111 => Texels::Unorm(
+            dxt3a_values(block).map(|value| core::array::from_fn(|bit| (value >> bit & 1) * 0xFF)),
+        ),
+    }
+}
+
+fn dxt3a_values(block: &[u8]) -> [u8; 16] {
+    let values = u64::from_le_bytes(block[..8].try_into().unwrap());
+    core::array::from_fn(|i| ((values >> (i * 4)) & 0xF) as u8)
+}
+
+/// The X and Y of the 4 values of a CTX1 block: its 2 endpoints, then 2 interpolated between
+/// them
+fn ctx1_palette(block: &[u8]) -> [[u8; 2]; 4] {
+    let (e0, e1) = ([block[0], block[1]], [block[2], block[3]]);
+    let mix = |w0: u32, w1: u32| {
+        core::array::from_fn(|i| ((w0 * e0[i] as u32 + w1 * e1[i] as u32 + 1) / 3) as u8)
+    };
+    [e0, e1, mix(2, 1), mix(1, 2)]
+}
+
+/// Converts Xbox 360 CTX1 blocks to BC5, returning the number of bytes written.
+///
+/// BC5 has no values a third of the way between its endpoints, so the interpolated CTX1
+/// values are moved to the nearest of the 6 that BC5 interpolates, off by less than 5% of the
+/// distance between the endpoints. Every whole block of `source` is converted, into 16 bytes of
+/// `dest` each.
+pub fn ctx1_to_bc5(source: &[u8], dest: &mut [u8]) -> Result<usize, SwizzleError> {
+    let blocks = source.len() / 8;
+    let dest = dest
+        .get_mut(..blocks * 16)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+
+    for (block, dest) in source.chunks_exact(8).zip(dest.chunks_exact_mut(16)) {
+        let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+        dest[..8].copy_from_slice(&ctx1_channel_to_bc4(block[0], block[2], indices));
+        dest[8..].copy_from_slice(&ctx1_channel_to_bc4(block[1], block[3], indices));
+    }
+    Ok(dest.len())
+}
+
+fn ctx1_channel_to_bc4(e0: u8, e1: u8, indices: u32) -> [u8; 8] {
+    // Indices of the BC4 values closest to each CTX1 value, always interpolating 8 values
+    let (a0, a1, remap) = match e0.cmp(&e1) {
+        core::cmp::Ordering::Greater => (e0, e1, [0, 1, 3, 6]),
+        core::cmp::Ordering::Less => (e1, e0, [1, 0, 6, 3]),
+        core::cmp::Ordering::Equal => (e0, e1, [0; 4]),
+    };
+
+    let mut bc4_indices = 0u64;
+    for i in 0..16 {
+        let index = remap[(indices >> (i * 2)) as usize & 3];
+        bc4_indices |= index << (i * 3);
+    }
+    let mut block = [0; 8];
+    block[0] = a0;
+    block[1] = a1;
+    block[2..].copy_from_slice(&bc4_indices.to_le_bytes()[..6]);
+    block
+}
+
+/// Decodes the color block of BC1, BC2 and BC3.
+/// * `four_colors` - Always interpolate 2 colors, as BC2 and BC3 do, rather than 1 color and
+///   transparent black when the first endpoint isn't greater than the second.
+fn decode_bc1(block: &[u8], four_colors: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+
+    let rgb = |c: u16| {
+        let (r, g, b) = ((c >> 11) as u8, ((c >> 5) & 0x3F) as u8, (c & 0x1F) as u8);
+        [
+            (r << 3) | (r >> 2),
+            (g << 2) | (g >> 4),
+            (b << 3) | (b >> 2),
+            0xFF,
+        ]
+    };
+    let (a, b) = (rgb(c0), rgb(c1));
+    let mix = |wa: u32, wb: u32| {
+        let d = wa + wb;
+        let mut texel = a;
+        for (c, (&a, &b)) in texel.iter_mut().zip(a.iter().zip(&b)).take(3) {
+            *c = ((wa * a as u32 + wb * b as u32 + d / 2) / d) as u8;
+        }
+        texel
+    };
+
+    let palette = if c0 > c1 || four_colors {
+        [a, b, mix(2, 1), mix(1, 2)]
+    } else {
+        [a, b, mix(1, 1), [0; 4]]
+    };
+    core::array::from_fn(|i| palette[(indices >> (i * 2)) as usize & 3])
+}
+
+/// Decodes an alpha block of BC3, or a channel of BC4 and BC5
+fn decode_alpha(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let palette: [u8; 8] = core::array::from_fn(|i| match i {
+        0 => a0 as u8,
+        1 => a1 as u8,
+        _ if a0 > a1 => (((8 - i as u32) * a0 + (i as u32 - 1) * a1 + 3) / 7) as u8,
+        6 => 0,
+        7 => 0xFF,
+        _ => (((6 - i as u32) * a0 + (i as u32 - 1) * a1 + 2) / 5) as u8,
+    });
+    alpha_indices(block).map(|i| palette[i])
+}
+
+/// Decodes a channel of signed BC4 and BC5
+fn decode_alpha_signed(block: &[u8]) -> [i8; 16] {
+    let (a0, a1) = (
+        (block[0] as i8).max(-127) as i32,
+        (block[1] as i8).max(-127) as i32,
+    );
+    let palette: [i8; 8] = core::array::from_fn(|i| match i {
+        0 => a0 as i8,
+        1 => a1 as i8,
+        _ if a0 > a1 => ((8 - i as i32) * a0 + (i as i32 - 1) * a1 + 3).div_euclid(7) as i8,
+        6 => -127,
+        7 => 127,
+        _ => ((6 - i as i32) * a0 + (i as i32 - 1) * a1 + 2).div_euclid(5) as i8,
+    });
+    alpha_indices(block).map(|i| palette[i])
+}
+
+fn alpha_indices(block: &[u8]) -> [usize; 16] {
+    let mut bytes = [0u8; 8];
+    bytes[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bytes);
+    core::array::from_fn(|i| (indices >> (i * 3)) as usize & 7)
+}
+
+/// Reads the fields of a BC6H or BC7 block, starting from its least significant bit
+struct BitReader(u128);
+
+impl BitReader {
+    fn new(block: &[u8]) -> Self {
+        BitReader(u128::from_le_bytes(block[..16].try_into().unwrap()))
+    }
+
+    fn read(&mut self, bits: u32) -> u32 {
+        let value = (self.0 & ((1 << bits) - 1)) as u32;
+        self.0 >>= bits;
+        value
+    }
+}
+
+const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+fn weight(bits: u32, index: u32) -> u32 {
+    match bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    }
+}
+
+/// Subset of each texel for the 2 subset partitions, 1 bit per texel
+const PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800,
+    0xFFE8, 0xFF00, 0xFFF0, 0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC,
+    0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Subset of each texel for the 3 subset partitions, 2 bits per texel
+const PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+/// Anchor texel of the second subset of the 2 subset partitions
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor texels of the second and third subsets of the 3 subset partitions
+#[rustfmt::skip]
+const ANCHORS_3: [[u8; 2]; 64] = [
+    [3, 15], [3, 8], [15, 8], [15, 3], [8, 15], [3, 15], [15, 3], [15, 8],
+    [8, 15], [8, 15], [6, 15], [6, 15], [6, 15], [5, 15], [3, 15], [3, 8],
+    [3, 15], [3, 8], [8, 15], [15, 3], [3, 15], [3, 8], [6, 15], [10, 8],
+    [5, 3], [8, 15], [8, 6], [6, 10], [8, 15], [5, 15], [15, 10], [15, 8],
+    [8, 15], [15, 3], [3, 15], [5, 10], [6, 10], [10, 8], [8, 9], [15, 10],
+    [15, 6], [3, 15], [15, 8], [5, 15], [15, 3], [15, 6], [15, 6], [15, 8],
+    [3, 15], [15, 3], [5, 15], [5, 15], [5, 15], [8, 15], [5, 15], [10, 15],
+    [5, 15], [10, 15], [8, 15], [13, 15], [15, 3], [12, 15], [3, 15], [3, 8],
+];
+
+fn subset(subsets: usize, partition: usize, texel: usize) -> usize {
+    match subsets {
+        2 => (PARTITIONS_2[partition] >> texel) as usize & 1,
+        3 => (PARTITIONS_3[partition] >> (texel * 2)) as usize & 3,
+        _ => 0,
+    }
+}
+
+fn is_anchor(subsets: usize, partition: usize, texel: usize) -> bool {
+    texel == 0
+        || match subsets {
+            2 => texel == ANCHORS_2[partition] as usize,
+            3 => ANCHORS_3[partition].contains(&(texel as u8)),
+            _ => false,
+        }
+}
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    /// One P-bit per endpoint
+    endpoint_p_bits: bool,
+    /// One P-bit per subset
+    shared_p_bits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+const fn bc7_mode(
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    (color_bits, alpha_bits): (u32, u32),
+    (endpoint_p_bits, shared_p_bits): (bool, bool),
+    (index_bits, secondary_index_bits): (u32, u32),
+) -> Bc7Mode {
+    Bc7Mode {
+        subsets,
+        partition_bits,
+        rotation_bits,
+        index_selection_bits,
+        color_bits,
+        alpha_bits,
+        endpoint_p_bits,
+        shared_p_bits,
+        index_bits,
+        secondary_index_bits,
+    }
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    bc7_mode(3, 4, 0, 0, (4, 0), (true, false), (3, 0)),
+    bc7_mode(2, 6, 0, 0, (6, 0), (false, true), (3, 0)),
+    bc7_mode(3, 6, 0, 0, (5, 0), (false, false), (2, 0)),
+    bc7_mode(2, 6, 0, 0, (7, 0), (true, false), (2, 0)),
+    bc7_mode(1, 0, 2, 1, (5, 6), (false, false), (2, 3)),
+    bc7_mode(1, 0, 2, 0, (7, 8), (false, false), (2, 2)),
+    bc7_mode(1, 0, 0, 0, (7, 7), (true, false), (4, 0)),
+    bc7_mode(2, 6, 0, 0, (5, 5), (true, false), (2, 0)),
+];
+
+fn decode_bc7(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut bits = BitReader::new(block);
+    let Some(mode) = (0..8).find(|_| bits.read(1) == 1) else {
+        // Reserved mode
+        return [[0; 4]; 16];
+    };
+    let mode = &BC7_MODES[mode];
+
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    let endpoint_count = mode.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = bits.read(mode.color_bits);
+        }
+    }
+    for endpoint in &mut endpoints[..endpoint_count] {
+        endpoint[3] = bits.read(mode.alpha_bits);
+    }
+
+    let has_p_bits = mode.endpoint_p_bits || mode.shared_p_bits;
+    if has_p_bits {
+        let mut p_bit = 0;
+        for (i, endpoint) in endpoints[..endpoint_count].iter_mut().enumerate() {
+            if mode.endpoint_p_bits || i % 2 == 0 {
+                p_bit = bits.read(1);
+            }
+            for channel in endpoint.iter_mut() {
+                *channel = (*channel << 1) | p_bit;
+            }
+        }
+    }
+
+    let color_bits = mode.color_bits + has_p_bits as u32;
+    let alpha_bits = mode.alpha_bits + (has_p_bits && mode.alpha_bits > 0) as u32;
+    let expand = |value: u32, bits: u32| (value << (8 - bits)) | (value >> (2 * bits - 8));
+    for endpoint in &mut endpoints[..endpoint_count] {
+        for channel in &mut endpoint[..3] {
+            *channel = expand(*channel, color_bits);
+        }
+        endpoint[3] = if alpha_bits > 0 {
+            expand(endpoint[3], alpha_bits)
+        } else {
+            0xFF
+        };
+    }
+
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        let anchor = is_anchor(mode.subsets, partition, texel);
+        *index = bits.read(mode.index_bits - anchor as u32);
+    }
+    let mut secondary_indices = [0u32; 16];
+    if mode.secondary_index_bits > 0 {
+        for (texel, index) in secondary_indices.iter_mut().enumerate() {
+            *index = bits.read(mode.secondary_index_bits - (texel == 0) as u32);
+        }
+    }
+
+    core::array::from_fn(|texel| {
+        let subset = subset(mode.subsets, partition, texel);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+
+        let primary = (indices[texel], mode.index_bits);
+        let secondary = (secondary_indices[texel], mode.secondary_index_bits);
+        let ((color_index, color_index_bits), (alpha_index, alpha_index_bits)) =
+            match (mode.secondary_index_bits, index_selection) {
+                (0, _) => (primary, primary),
+                (_, 0) => (primary, secondary),
+                _ => (secondary, primary),
+            };
+
+        let interpolate = |channel: usize, index: u32, bits: u32| {
+            let w = weight(bits, index);
+            (((64 - w) * e0[channel] + w * e1[channel] + 32) >> 6) as u8
+        };
+        let mut texel = [
+            interpolate(0, color_index, color_index_bits),
+            interpolate(1, color_index, color_index_bits),
+            interpolate(2, color_index, color_index_bits),
+            interpolate(3, alpha_index, alpha_index_bits),
+        ];
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+        texel
+    })
+}
+
+/// Where a run of bits of a BC6H block goes: (endpoint component, first bit, bit count).
+/// Endpoint components are R, G and B of endpoints 0 to 3.
+type Bc6hField = (u8, u8, u8);
+
+const R0: u8 = 0;
+const G0: u8 = 1;
+const B0: u8 = 2;
+const R1: u8 = 3;
+const G1: u8 = 4;
+const B1: u8 = 5;
+const R2: u8 = 6;
+const G2: u8 = 7;
+const B2: u8 = 8;
+const R3: u8 = 9;
+const G3: u8 = 10;
+const B3: u8 = 11;
+
+struct Bc6hMode {
+    /// Whether endpoints other than the first are stored as deltas from it
+    transformed: bool,
+    two_regions: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    fields: &'static [Bc6hField],
+}
+
+/// Returns the mode with the given mode bits, `None` for the reserved ones
+#[rustfmt::skip]
+fn bc6h_mode(mode: u32) -> Option<Bc6hMode> {
+    let delta = |endpoint_bits, delta_bits, fields| Bc6hMode {
+        transformed: true,
+        two_regions: true,
+        endpoint_bits,
+        delta_bits,
+        fields,
+    };
+    let one_region = |endpoint_bits, delta_bits, fields| Bc6hMode {
+        transformed: true,
+        two_regions: false,
+        endpoint_bits,
+        delta_bits,
+        fields,
+    };
+
+    Some(match mode {
+        0b00 => delta(10, [5, 5, 5], &[
+            (G2, 4, 1), (B2, 4, 1), (B3, 4, 1), (R0, 0, 10), (G0, 0, 10), (B0, 0, 10),
+            (R1, 0, 5), (G3, 4, 1), (G2, 0, 4), (G1, 0, 5), (B3, 0, 1), (G3, 0, 4),
+            (B1, 0, 5), (B3, 1, 1), (B2, 0, 4), (R2, 0, 5), (B3, 2, 1), (R3, 0, 5),
+            (B3, 3, 1),
+        ]),
+        0b01 => delta(7, [6, 6, 6], &[
+            (G2, 5, 1), (G3, 4, 1), (G3, 5, 1), (R0, 0, 7), (B3, 0, 1), (B3, 1, 1),
+            (B2, 4, 1), (G0, 0, 7), (B2, 5, 1), (B3, 2, 1), (G2, 4, 1), (B0, 0, 7),
+            (B3, 3, 1), (B3, 5, 1), (B3, 4, 1), (R1, 0, 6), (G2, 0, 4), (G1, 0, 6),
+            (G3, 0, 4), (B1, 0, 6), (B2, 0, 4), (R2, 0, 6), (R3, 0, 6),
+        ]),
+        0b00010 => delta(11, [5, 4, 4], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 5), (R0, 10, 1), (G2, 0, 4),
+            (G1, 0, 4), (G0, 10, 1), (B3, 0, 1), (G3, 0, 4), (B1, 0, 4), (B0, 10, 1),
+            (B3, 1, 1), (B2, 0, 4), (R2, 0, 5), (B3, 2, 1), (R3, 0, 5), (B3, 3, 1),
+        ]),
+        0b00110 => delta(11, [4, 5, 4], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 4), (R0, 10, 1), (G3, 4, 1),
+            (G2, 0, 4), (G1, 0, 5), (G0, 10, 1), (G3, 0, 4), (B1, 0, 4), (B0, 10, 1),
+            (B3, 1, 1), (B2, 0, 4), (R2, 0, 4), (B3, 0, 1), (B3, 2, 1), (R3, 0, 4),
+            (G2, 4, 1), (B3, 3, 1),
+        ]),
+        0b01010 => delta(11, [4, 4, 5], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 4), (R0, 10, 1), (B2, 4, 1),
+            (G2, 0, 4), (G1, 0, 4), (G0, 10, 1), (B3, 0, 1), (G3, 0, 4), (B1, 0, 5),
+            (B0, 10, 1), (B2, 0, 4), (R2, 0, 4), (B3, 1, 1), (B3, 2, 1), (R3, 0, 4),
+            (B3, 4, 1), (B3, 3, 1),
+        ]),
+        0b01110 => delta(9, [5, 5, 5], &[
+            (R0, 0, 9), (B2, 4, 1), (G0, 0, 9), (G2, 4, 1), (B0, 0, 9), (B3, 4, 1),
+            (R1, 0, 5), (G3, 4, 1), (G2, 0, 4), (G1, 0, 5), (B3, 0, 1), (G3, 0, 4),
+            (B1, 0, 5), (B3, 1, 1), (B2, 0, 4), (R2, 0, 5), (B3, 2, 1), (R3, 0, 5),
+            (B3, 3, 1),
+        ]),
+        0b10010 => delta(8, [6, 5, 5], &[
+            (R0, 0, 8), (G3, 4, 1), (B2, 4, 1), (G0, 0, 8), (B3, 2, 1), (G2, 4, 1),
+            (B0, 0, 8), (B3, 3, 1), (B3, 4, 1), (R1, 0, 6), (G2, 0, 4), (G1, 0, 5),
+            (B3, 0, 1), (G3, 0, 4), (B1, 0, 5), (B3, 1, 1), (B2, 0, 4), (R2, 0, 6),
+            (R3, 0, 6),
+        ]),
+        0b10110 => delta(8, [5, 6, 5], &[
+            (R0, 0, 8), (B3, 0, 1), (B2, 4, 1), (G0, 0, 8), (G2, 5, 1), (G2, 4, 1),
+            (B0, 0, 8), (G3, 5, 1), (B3, 4, 1), (R1, 0, 5), (G3, 4, 1), (G2, 0, 4),
+            (G1, 0, 6), (G3, 0, 4), (B1, 0, 5), (B3, 1, 1), (B2, 0, 4), (R2, 0, 5),
+            (B3, 2, 1), (R3, 0, 5), (B3, 3, 1),
+        ]),
+        0b11010 => delta(8, [5, 5, 6], &[
+            (R0, 0, 8), (B3, 1, 1), (B2, 4, 1), (G0, 0, 8), (B2, 5, 1), (G2, 4, 1),
+            (B0, 0, 8), (B3, 5, 1), (B3, 4, 1), (R1, 0, 5), (G3, 4, 1), (G2, 0, 4),
+            (G1, 0, 5), (B3, 0, 1), (G3, 0, 4), (B1, 0, 6), (B2, 0, 4), (R2, 0, 5),
+            (B3, 2, 1), (R3, 0, 5), (B3, 3, 1),
+        ]),
+        0b11110 => Bc6hMode {
+            transformed: false,
+            ..delta(6, [6, 6, 6], &[
+                (R0, 0, 6), (G3, 4, 1), (B3, 0, 1), (B3, 1, 1), (B2, 4, 1), (G0, 0, 6),
+                (G2, 5, 1), (B2, 5, 1), (B3, 2, 1), (G2, 4, 1), (B0, 0, 6), (G3, 5, 1),
+                (B3, 3, 1), (B3, 5, 1), (B3, 4, 1), (R1, 0, 6), (G2, 0, 4), (G1, 0, 6),
+                (G3, 0, 4), (B1, 0, 6), (B2, 0, 4), (R2, 0, 6), (R3, 0, 6),
+            ])
+        },
+        0b00011 => Bc6hMode {
+            transformed: false,
+            ..one_region(10, [10, 10, 10], &[
+                (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 10), (G1, 0, 10), (B1, 0, 10),
+            ])
+        },
+        0b00111 => one_region(11, [9, 9, 9], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 9), (R0, 10, 1), (G1, 0, 9),
+            (G0, 10, 1), (B1, 0, 9), (B0, 10, 1),
+        ]),
+        0b01011 => one_region(12, [8, 8, 8], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 8), (R0, 11, 1), (R0, 10, 1),
+            (G1, 0, 8), (G0, 11, 1), (G0, 10, 1), (B1, 0, 8), (B0, 11, 1), (B0, 10, 1),
+        ]),
+        0b01111 => one_region(16, [4, 4, 4], &[
+            (R0, 0, 10), (G0, 0, 10), (B0, 0, 10), (R1, 0, 4), (R0, 15, 1), (R0, 14, 1),
+            (R0, 13, 1), (R0, 12, 1), (R0, 11, 1), (R0, 10, 1), (G1, 0, 4), (G0, 15, 1),
+            (G0, 14, 1), (G0, 13, 1), (G0, 12, 1), (G0, 11, 1), (G0, 10, 1), (B1, 0, 4),
+            (B0, 15, 1), (B0, 14, 1), (B0, 13, 1), (B0, 12, 1), (B0, 11, 1), (B0, 10, 1),
+        ]),
+        _ => return None,
+    })
+}
+
+fn sign_extend(value: i32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    (value << shift) >> shift
+}
+
+fn decode_bc6h(block: &[u8], signed: bool) -> [[u16; 4]; 16] {
+    const ONE: u16 = 0x3C00;
+
+    let mut bits = BitReader::new(block);
+    let mut mode_bits = bits.read(2);
+    if mode_bits > 1 {
+        mode_bits |= bits.read(3) << 2;
+    }
+    let Some(mode) = bc6h_mode(mode_bits) else {
+        return [[0, 0, 0, ONE]; 16];
+    };
+
+    let mut endpoints = [0i32; 12];
+    for &(component, first_bit, count) in mode.fields {
+        endpoints[component as usize] |= (bits.read(count as u32) << first_bit) as i32;
+    }
+    let partition = if mode.two_regions {
+        bits.read(5) as usize
+    } else {
+        0
+    };
+    let component_count = if mode.two_regions { 12 } else { 6 };
+
+    let endpoint_bits = mode.endpoint_bits;
+    let mask = (1 << endpoint_bits) - 1;
+    if signed {
+        for component in &mut endpoints[..3] {
+            *component = sign_extend(*component, endpoint_bits);
+        }
+    }
+    for i in 3..component_count {
+        if mode.transformed {
+            let delta = sign_extend(endpoints[i], mode.delta_bits[i % 3]);
+            endpoints[i] = (endpoints[i % 3] + delta) & mask;
+        }
+        if signed {
+            endpoints[i] = sign_extend(endpoints[i], endpoint_bits);
+        }
+    }
+
+    let unquantize = |value: i32| -> i32 {
+        if signed {
+            if endpoint_bits >= 16 {
+                return value;
+            }
+            let magnitude = value.abs();
+            let unquantized = if magnitude == 0 {
+                0
+            } else if magnitude >= (1 << (endpoint_bits - 1)) - 1 {
+                0x7FFF
+            } else {
+                ((magnitude << 15) + 0x4000) >> (endpoint_bits - 1)
+            };
+            if value < 0 { -unquantized } else { unquantized }
+        } else if endpoint_bits >= 15 || value == 0 {
+            value
+        } else if value == mask {
+            0xFFFF
+        } else {
+            ((value << 16) + 0x8000) >> endpoint_bits
+        }
+    };
+    for component in &mut endpoints[..component_count] {
+        *component = unquantize(*component);
+    }
+
+    let (subsets, index_bits) = if mode.two_regions { (2, 3) } else { (1, 4) };
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        let anchor = is_anchor(subsets, partition, texel);
+        *index = bits.read(index_bits - anchor as u32);
+    }
+
+    core::array::from_fn(|texel| {
+        let region = subset(subsets, partition, texel) * 6;
+        let w = weight(index_bits, indices[texel]) as i32;
+        let mut rgba = [0, 0, 0, ONE];
+        for (channel, value) in rgba.iter_mut().take(3).enumerate() {
+            let (e0, e1) = (endpoints[region + channel], endpoints[region + 3 + channel]);
+            let interpolated = ((64 - w) * e0 + w * e1 + 32) >> 6;
+            *value = if signed {
+                let magnitude = (interpolated.abs() * 31) >> 5;
+                if interpolated < 0 {
+                    0x8000 | magnitude as u16
+                } else {
+                    magnitude as u16
+                }
+            } else {
+                ((interpolated * 31) >> 6) as u16
+            };
+        }
+        rgba
+    })
+}
+
+/// Formats whose uncompressed texels can be decoded with [`decode_texel_rgba8`] and
+/// [`decode_texel_rgba32f`]. Implemented by every format enum, and by GNF texture descriptors,
+/// which also carry the numeric format of their texels.
+///
+/// Components are read the way the hardware reads them:
+/// * DXGI texels are little endian, with the first component in the least significant bits.
+///   TYPELESS formats decode as UNORM, or as FLOAT for 32-bit components.
+/// * GCN texels follow the masks in the [`GcnSurfaceFormat`] docs, with X to W as R to A. They
+///   decode as UNORM, except for the floating point only formats.
+/// * Xenos texels are expected to be byte swapped to little endian, as the deswizzlers do,
+///   and follow [`XenosSurfaceFormat::component_bits`].
+/// * GCM texels are big endian, and follow [`GcmSurfaceFormat::components`].
+/// * GNF texture descriptors decode their texels with their `num_format`. Scaled formats decode
+///   like integers, and SRGB like UNORM.
+pub trait TexelFormat: sealed::Sealed {}
+
+mod sealed {
+    use crate::swizzle::SwizzleError;
+
+    pub trait Sealed {
+        fn layout(&self) -> Result<Layout, SwizzleError>;
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    pub enum Numeric {
+        Unorm,
+        Snorm,
+        Uint,
+        Sint,
+        Float,
+        /// The 10-bit extended range of `R10G10B10_XR_BIAS_A2_UNORM`
+        XrBias,
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct Channel {
+        pub shift: u32,
+        pub width: u32,
+        pub numeric: Numeric,
+    }
+
+    /// Where the R, G, B and A channels of a texel are
+    pub struct Layout {
+        pub size: usize,
+        pub big_endian: bool,
+        /// R, G and B are mantissas sharing a 5-bit exponent in the top bits
+        pub shared_exponent: bool,
+        pub channels: [Option<Channel>; 4],
+    }
+}
+
+use sealed::{Channel, Layout, Numeric};
+
+const R: usize = 0;
+const G: usize = 1;
+const B: usize = 2;
+const A: usize = 3;
+/// Unused bits
+const X: usize = 4;
+
+impl Layout {
+    /// Channels packed one after the other, from the least significant bit
+    fn packed(numeric: Numeric, fields: &[(usize, u32)]) -> Self {
+        let mut channels = [None; 4];
+        let mut shift = 0;
+        for &(channel, width) in fields {
+            if channel != X {
+                channels[channel] = Some(Channel {
+                    shift,
+                    width,
+                    numeric,
+                });
+            }
+            shift += width;
+        }
+        Layout {
+            size: shift as usize / 8,
+            big_endian: false,
+            shared_exponent: false,
+            channels,
+        }
+    }
+
+    fn with_numeric(mut self, channel: usize, numeric: Numeric) -> Self {
+        if let Some(channel) = &mut self.channels[channel] {
+            channel.numeric = numeric;
+        }
+        self
+    }
+
+    fn shared_exponent() -> Self {
+        Layout {
+            shared_exponent: true,
+            ..Layout::packed(Numeric::Unorm, &[(R, 9), (G, 9), (B, 9), (X, 5)])
+        }
+    }
+
+    fn decode(&self, texel: &[u8]) -> Result<[f32; 4], SwizzleError> {
+        let texel = slice(texel, 0, self.size)?;
+        let mut bytes = [0u8; 16];
+        let value = if self.big_endian {
+            bytes[16 - self.size..].copy_from_slice(texel);
+            u128::from_be_bytes(bytes)
+        } else {
+            bytes[..self.size].copy_from_slice(texel);
+            u128::from_le_bytes(bytes)
+        };
+
+        let scale = if self.shared_exponent {
+            // The mantissas have no implied one, so the exponent bias of 15 grows by 9
+            exp2((value >> 27) as i32 - 24)
+        } else {
+            1.0
+        };
+        Ok(core::array::from_fn(|i| match self.channels[i] {
+            Some(channel) if self.shared_exponent => {
+                ((value >> channel.shift) & 0x1FF) as f32 * scale
+            }
+            Some(channel) => channel.decode(value),
+            None if i == A => 1.0,
+            None => 0.0,
+        }))
+    }
+
+    fn decode_rgba8(&self, texel: &[u8]) -> Result<[u8; 4], SwizzleError> {
+        let rgba = self.decode(texel)?;
+        Ok(core::array::from_fn(|i| {
+            let value = match self.channels[i] {
+                Some(channel) if channel.numeric == Numeric::Snorm => (rgba[i] + 1.0) / 2.0,
+                _ => rgba[i],
+            };
+            (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+        }))
+    }
+}
+
+impl Channel {
+    fn decode(self, texel: u128) -> f32 {
+        let max = u128::MAX >> (128 - self.width);
+        let value = (texel >> self.shift) & max;
+        let signed = || {
+            let shift = 128 - self.width;
+            ((value << shift) as i128) >> shift
+        };
+
+        match (self.numeric, self.width) {
+            (Numeric::Float, 10) => half_to_f32((value << 5) as u16),
+            (Numeric::Float, 11) => half_to_f32((value << 4) as u16),
+            (Numeric::Float, 16) => half_to_f32(value as u16),
+            (Numeric::Float, 32) => f32::from_bits(value as u32),
+            (Numeric::Snorm, _) => (signed() as f64 / (max >> 1) as f64).max(-1.0) as f32,
+            (Numeric::Uint, _) => value as f32,
+            (Numeric::Sint, _) => signed() as f32,
+            (Numeric::XrBias, _) => (value as f32 - 384.0) / 510.0,
+            _ => (value as f64 / max as f64) as f32,
+        }
+    }
+}
+
+/// 2 to the power of `exponent`, which must be in the normal range of `f32`
+fn exp2(exponent: i32) -> f32 {
+    f32::from_bits(((exponent + 127) as u32) << 23)
+}
+
+impl TexelFormat for DxgiFormat {}
+
+impl sealed::Sealed for DxgiFormat {
+    fn layout(&self) -> Result<Layout, SwizzleError> {
+        use Numeric::*;
+
+        let rgba32 = [(R, 32), (G, 32), (B, 32), (A, 32)];
+        let rgba16 = [(R, 16), (G, 16), (B, 16), (A, 16)];
+        let rgba8 = [(R, 8), (G, 8), (B, 8), (A, 8)];
+        let bgra8 = [(B, 8), (G, 8), (R, 8), (A, 8)];
+        let bgrx8 = [(B, 8), (G, 8), (R, 8), (X, 8)];
+        let rgb10a2 = [(R, 10), (G, 10), (B, 10), (A, 2)];
+
+        Ok(match self {
+            DxgiFormat::R32G32B32A32_TYPELESS | DxgiFormat::R32G32B32A32_FLOAT => {
+                Layout::packed(Float, &rgba32)
+            }
+            DxgiFormat::R32G32B32A32_UINT => Layout::packed(Uint, &rgba32),
+            DxgiFormat::R32G32B32A32_SINT => Layout::packed(Sint, &rgba32),
+            DxgiFormat::R32G32B32_TYPELESS | DxgiFormat::R32G32B32_FLOAT => {
+                Layout::packed(Float, &rgba32[..3])
+            }
+            DxgiFormat::R32G32B32_UINT => Layout::packed(Uint, &rgba32[..3]),
+            DxgiFormat::R32G32B32_SINT => Layout::packed(Sint, &rgba32[..3]),
+            DxgiFormat::R16G16B16A16_FLOAT => Layout::packed(Float, &rgba16),
+            DxgiFormat::R16G16B16A16_TYPELESS | DxgiFormat::R16G16B16A16_UNORM => {
+                Layout::packed(Unorm, &rgba16)
+            }
+            DxgiFormat::R16G16B16A16_UINT => Layout::packed(Uint, &rgba16),
+            DxgiFormat::R16G16B16A16_SNORM => Layout::packed(Snorm, &rgba16),
+            DxgiFormat::R16G16B16A16_SINT => Layout::packed(Sint, &rgba16),
+            DxgiFormat::R32G32_TYPELESS | DxgiFormat::R32G32_FLOAT => {
+                Layout::packed(Float, &rgba32[..2])
+            }
+            DxgiFormat::R32G32_UINT => Layout::packed(Uint, &rgba32[..2]),
+            DxgiFormat::R32G32_SINT => Layout::packed(Sint, &rgba32[..2]),
+            DxgiFormat::R32G8X24_TYPELESS | DxgiFormat::D32_FLOAT_S8X24_UINT => {
+                Layout::packed(Float, &[(R, 32), (G, 8), (X, 24)]).with_numeric(G, Uint)
+            }
+            DxgiFormat::R32_FLOAT_X8X24_TYPELESS => Layout::packed(Float, &[(R, 32), (X, 32)]),
+            DxgiFormat::X32_TYPELESS_G8X24_UINT => {
+                Layout::packed(Uint, &[(X, 32), (G, 8), (X, 24)])
+            }
+            DxgiFormat::R10G10B10A2_TYPELESS | DxgiFormat::R10G10B10A2_UNORM => {
+                Layout::packed(Unorm, &rgb10a2)
+            }
+            DxgiFormat::R10G10B10A2_UINT => Layout::packed(Uint, &rgb10a2),
+            DxgiFormat::R10G10B10_XR_BIAS_A2_UNORM => {
+                Layout::packed(XrBias, &rgb10a2).with_numeric(A, Unorm)
+            }
+            DxgiFormat::R11G11B10_FLOAT => Layout::packed(Float, &[(R, 11), (G, 11), (B, 10)]),
+            DxgiFormat::R8G8B8A8_TYPELESS
+            | DxgiFormat::R8G8B8A8_UNORM
+            | DxgiFormat::R8G8B8A8_UNORM_SRGB => Layout::packed(Unorm, &rgba8),
+            DxgiFormat::R8G8B8A8_UINT => Layout::packed(Uint, &rgba8),
+            DxgiFormat::R8G8B8A8_SNORM => Layout::packed(Snorm, &rgba8),
+            DxgiFormat::R8G8B8A8_SINT => Layout::packed(Sint, &rgba8),
+            DxgiFormat::R16G16_FLOAT => Layout::packed(Float, &rgba16[..2]),
+            DxgiFormat::R16G16_TYPELESS | DxgiFormat::R16G16_UNORM => {
+                Layout::packed(Unorm, &rgba16[..2])
+            }
+            DxgiFormat::R16G16_UINT => Layout::packed(Uint, &rgba16[..2]),
+            DxgiFormat::R16G16_SNORM => Layout::packed(Snorm, &rgba16[..2]),
+            DxgiFormat::R16G16_SINT => Layout::packed(Sint, &rgba16[..2]),
+            DxgiFormat::R32_TYPELESS | DxgiFormat::D32_FLOAT | DxgiFormat::R32_FLOAT => {
+                Layout::packed(Float, &rgba32[..1])
+            }
+            DxgiFormat::R32_UINT => Layout::packed(Uint, &rgba32[..1]),
+            DxgiFormat::R32_SINT => Layout::packed(Sint, &rgba32[..1]),
+            DxgiFormat::R24G8_TYPELESS | DxgiFormat::D24_UNORM_S8_UINT => {
+                Layout::packed(Unorm, &[(R, 24), (G, 8)]).with_numeric(G, Uint)
+            }
+            DxgiFormat::R24_UNORM_X8_TYPELESS => Layout::packed(Unorm, &[(R, 24), (X, 8)]),
+            DxgiFormat::X24_TYPELESS_G8_UINT => Layout::packed(Uint, &[(X, 24), (G, 8)]),
+            DxgiFormat::R8G8_TYPELESS | DxgiFormat::R8G8_UNORM => {
+                Layout::packed(Unorm, &rgba8[..2])
+            }
+            DxgiFormat::R8G8_UINT => Layout::packed(Uint, &rgba8[..2]),
+            DxgiFormat::R8G8_SNORM => Layout::packed(Snorm, &rgba8[..2]),
+            DxgiFormat::R8G8_SINT => Layout::packed(Sint, &rgba8[..2]),
+            DxgiFormat::R16_FLOAT => Layout::packed(Float, &rgba16[..1]),
+            DxgiFormat::R16_TYPELESS | DxgiFormat::D16_UNORM | DxgiFormat::R16_UNORM => {
+                Layout::packed(Unorm, &rgba16[..1])
+            }
+            DxgiFormat::R16_UINT => Layout::packed(Uint, &rgba16[..1]),
+            DxgiFormat::R16_SNORM => Layout::packed(Snorm, &rgba16[..1]),
+            DxgiFormat::R16_SINT => Layout::packed(Sint, &rgba16[..1]),
+            DxgiFormat::R8_TYPELESS | DxgiFormat::R8_UNORM => Layout::packed(Unorm, &rgba8[..1]),
+            DxgiFormat::R8_UINT => Layout::packed(Uint, &rgba8[..1]),
+            DxgiFormat::R8_SNORM => Layout::packed(Snorm, &rgba8[..1]),
+            DxgiFormat::R8_SINT => Layout::packed(Sint, &rgba8[..1]),
+            DxgiFormat::A8_UNORM => Layout::packed(Unorm, &[(A, 8)]),
+            DxgiFormat::R9G9B9E5_SHAREDEXP => Layout::shared_exponent(),
+            DxgiFormat::B5G6R5_UNORM => Layout::packed(Unorm, &[(B, 5), (G, 6), (R, 5)]),
+            DxgiFormat::B5G5R5A1_UNORM => Layout::packed(Unorm, &[(B, 5), (G, 5), (R, 5), (A, 1)]),
+            DxgiFormat::B8G8R8A8_TYPELESS
+            | DxgiFormat::B8G8R8A8_UNORM
+            | DxgiFormat::B8G8R8A8_UNORM_SRGB => Layout::packed(Unorm, &bgra8),
+            DxgiFormat::B8G8R8X8_TYPELESS
+            | DxgiFormat::B8G8R8X8_UNORM
+            | DxgiFormat::B8G8R8X8_UNORM_SRGB => Layout::packed(Unorm, &bgrx8),
+            DxgiFormat::B4G4R4A4_UNORM => Layout::packed(Unorm, &[(B, 4), (G, 4), (R, 4), (A, 4)]),
+            e => return Err(SwizzleError::UnsupportedFormat((*e).into())),
+        })
+    }
+}
+
+impl TexelFormat for GcnSurfaceFormat {}
+
+impl sealed::Sealed for GcnSurfaceFormat {
+    fn layout(&self) -> Result<Layout, SwizzleError> {
+        gcn_layout(*self, Numeric::Unorm)
+    }
+}
+
+impl TexelFormat for TextureDescriptor {}
+
+impl sealed::Sealed for TextureDescriptor {
+    fn layout(&self) -> Result<Layout, SwizzleError> {
+        let numeric = match self.num_format {
+            1 => Numeric::Snorm,
+            2 | 4 => Numeric::Uint,
+            3 | 5 => Numeric::Sint,
+            7 => Numeric::Float,
+            _ => Numeric::Unorm,
+        };
+        gcn_layout(self.data_format, numeric)
+    }
+}
+
+fn gcn_layout(format: GcnSurfaceFormat, numeric: Numeric) -> Result<Layout, SwizzleError> {
+    let packed = |fields: &[(usize, u32)]| Layout::packed(numeric, fields);
+    let rgba32 = [(R, 32), (G, 32), (B, 32), (A, 32)];
+    let rgba16 = [(R, 16), (G, 16), (B, 16), (A, 16)];
+    let rgba8 = [(R, 8), (G, 8), (B, 8), (A, 8)];
+
+    Ok(match format {
+        GcnSurfaceFormat::Format8 => packed(&rgba8[..1]),
+        GcnSurfaceFormat::Format16 => packed(&rgba16[..1]),
+        GcnSurfaceFormat::Format8_8 => packed(&rgba8[..2]),
+        GcnSurfaceFormat::Format32 => packed(&rgba32[..1]),
+        GcnSurfaceFormat::Format16_16 => packed(&rgba16[..2]),
+        // Only ever read as floating point
+        GcnSurfaceFormat::Format10_11_11 => {
+            Layout::packed(Numeric::Float, &[(R, 11), (G, 11), (B, 10)])
+        }
+        GcnSurfaceFormat::Format11_11_10 => {
+            Layout::packed(Numeric::Float, &[(R, 10), (G, 11), (B, 11)])
+        }
+        GcnSurfaceFormat::Format10_10_10_2 => packed(&[(R, 2), (G, 10), (B, 10), (A, 10)]),
+        GcnSurfaceFormat::Format2_10_10_10 => packed(&[(R, 10), (G, 10), (B, 10), (A, 2)]),
+        GcnSurfaceFormat::Format8_8_8_8 => packed(&rgba8),
+        GcnSurfaceFormat::Format32_32 => packed(&rgba32[..2]),
+        GcnSurfaceFormat::Format16_16_16_16 => packed(&rgba16),
+        GcnSurfaceFormat::Format32_32_32 => packed(&rgba32[..3]),
+        GcnSurfaceFormat::Format32_32_32_32 => packed(&rgba32),
+        GcnSurfaceFormat::Format5_6_5 => packed(&[(R, 5), (G, 6), (B, 5)]),
+        GcnSurfaceFormat::Format1_5_5_5 => packed(&[(R, 5), (G, 5), (B, 5), (A, 1)]),
+        GcnSurfaceFormat::Format5_5_5_1 => packed(&[(R, 1), (G, 5), (B, 5), (A, 5)]),
+        GcnSurfaceFormat::Format4_4_4_4 => packed(&[(R, 4), (G, 4), (B, 4), (A, 4)]),
+        GcnSurfaceFormat::Format8_24 => packed(&[(R, 24), (G, 8)]),
+        GcnSurfaceFormat::Format24_8 => packed(&[(R, 8), (G, 24)]),
+        GcnSurfaceFormat::FormatX24_8_32 => packed(&[(R, 32), (G, 8), (X, 24)]),
+        GcnSurfaceFormat::Format5_9_9_9 => Layout::shared_exponent(),
+        e => return Err(SwizzleError::UnsupportedFormat(e as u32)),
+    })
+}
+
+impl TexelFormat for XenosSurfaceFormat {}
+
+impl sealed::Sealed for XenosSurfaceFormat {
+    fn layout(&self) -> Result<Layout, SwizzleError> {
+        let Some(component_bits) = self.component_bits() else {
+            return Err(SwizzleError::UnsupportedFormat(*self as u32));
+        };
+        // The EXPAND formats are read as half precision floats
+        let numeric = match self {
+            _ if self.is_float() => Numeric::Float,
+            XenosSurfaceFormat::k_16_EXPAND
+            | XenosSurfaceFormat::k_16_16_EXPAND
+            | XenosSurfaceFormat::k_16_16_16_16_EXPAND => Numeric::Float,
+            _ => Numeric::Unorm,
+        };
+
+        let mut fields = [(X, 0); 4];
+        for (i, &width) in component_bits.iter().enumerate() {
+            fields[i] = (i, width);
+        }
+        Ok(Layout::packed(numeric, &fields[..component_bits.len()]))
+    }
+}
+
+impl TexelFormat for GcmSurfaceFormat {}
+
+impl sealed::Sealed for GcmSurfaceFormat {
+    fn layout(&self) -> Result<Layout, SwizzleError> {
+        let Some([a, r, g, b]) = self.components() else {
+            return Err(SwizzleError::UnsupportedFormat(*self as u32));
+        };
+        let numeric = if self.is_float() {
+            Numeric::Float
+        } else {
+            Numeric::Unorm
+        };
+
+        let channels = [r, g, b, a].map(|component| {
+            component.map(|(shift, width)| Channel {
+                shift,
+                width,
+                numeric,
+            })
+        });
+        // Padding such as the D of D8R8G8B8 is read along with the components
+        Ok(Layout {
+            size: self.block_size()?,
+            big_endian: true,
+            shared_exponent: false,
+            channels,
+        })
+    }
+}
+
+/// Size in bytes of an uncompressed texel of `format`
+pub fn texel_size(format: impl TexelFormat) -> Result<usize, SwizzleError> {
+    Ok(format.layout()?.size)
+}
+
+/// Decodes an uncompressed texel to 8-bit RGBA.
+///
+/// Missing color channels are 0 and missing alpha is 1. Floating point and integer channels
+/// are clamped to 0-1, and SNORM channels are mapped from -1-1 to 0-1.
+pub fn decode_texel_rgba8(texel: &[u8], format: impl TexelFormat) -> Result<[u8; 4], SwizzleError> {
+    format.layout()?.decode_rgba8(texel)
+}
+
+/// Decodes an uncompressed texel to single precision RGBA.
+///
+/// Missing color channels are 0 and missing alpha is 1. Integer channels keep their value.
+pub fn decode_texel_rgba32f(
+    texel: &[u8],
+    format: impl TexelFormat,
+) -> Result<[f32; 4], SwizzleError> {
+    format.layout()?.decode(texel)
+}
+
+/// Decodes the uncompressed texels of `source` to 8-bit RGBA, returning the number of bytes
+/// written. See [`decode_texel_rgba8`].
+/// * `dimensions` - Size of the image in texels. Slices follow each other.
+pub fn decode_texels_rgba8(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: impl TexelFormat,
+) -> Result<usize, SwizzleError> {
+    let layout = format.layout()?;
+    decode_texels(source, dest, dimensions, layout.size, 4, |texel, dest| {
+        dest.copy_from_slice(&layout.decode_rgba8(texel)?);
+        Ok(())
+    })
+}
+
+/// Decodes the uncompressed texels of `source` to little endian single precision RGBA, returning
+/// the number of bytes written. See [`decode_texel_rgba32f`].
+/// * `dimensions` - Size of the image in texels. Slices follow each other.
+pub fn decode_texels_rgba32f(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: impl TexelFormat,
+) -> Result<usize, SwizzleError> {
+    let layout = format.layout()?;
+    decode_texels(source, dest, dimensions, layout.size, 16, |texel, dest| {
+        for (dest, channel) in dest.chunks_exact_mut(4).zip(layout.decode(texel)?) {
+            dest.copy_from_slice(&channel.to_le_bytes());
+        }
+        Ok(())
+    })
+}
+
+fn decode_texels(
+    source: &[u8],
+    dest: &mut [u8],
+    (width, height, depth): (usize, usize, usize),
+    texel_size: usize,
+    pixel_size: usize,
+    write: impl Fn(&[u8], &mut [u8]) -> Result<(), SwizzleError>,
+) -> Result<usize, SwizzleError> {
+    let count = width * height * depth;
+    let source = slice(source, 0, count * texel_size)?;
+    let dest = dest
+        .get_mut(..count * pixel_size)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+
+    for (texel, dest) in source
+        .chunks_exact(texel_size)
+        .zip(dest.chunks_exact_mut(pixel_size))
+    {
+        write(texel, dest)?;
+    }
+    Ok(dest.len())
+}
+
+fn half_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1F) as u32;
+    let mantissa = (half & 0x3FF) as u32;
+
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // Subnormal, normalize it
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x3FF;
+            sign | ((113 - shift) << 23) | (mantissa << 13)
+        }
+        0x1F => sign | 0x7F80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7F_FFFF;
+
+    if exponent == 0xFF {
+        return sign | 0x7C00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+
+    // Rounds the bits that don't fit to the nearest even value
+    let round = |value: u32, shift: u32| {
+        let rest = value & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let value = value >> shift;
+        value + (rest > half || (rest == half && value & 1 == 1)) as u32
+    };
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        return sign | round(mantissa | 0x80_0000, (14 - exponent) as u32) as u16;
+    }
+    sign | round(((exponent as u32) << 23) | mantissa, 13) as u16
+}
@@ -1,132 +1,178 @@
 use crate::swizzle::{Format, SwizzleError};
-use core::mem::transmute;
-
-#[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
-#[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
-pub enum DxgiFormat {
-    Unknown = 0,
-    R32G32B32A32_TYPELESS = 1,
-    R32G32B32A32_FLOAT = 2,
-    R32G32B32A32_UINT = 3,
-    R32G32B32A32_SINT = 4,
-    R32G32B32_TYPELESS = 5,
-    R32G32B32_FLOAT = 6,
-    R32G32B32_UINT = 7,
-    R32G32B32_SINT = 8,
-    R16G16B16A16_TYPELESS = 9,
-    R16G16B16A16_FLOAT = 10,
-    R16G16B16A16_UNORM = 11,
-    R16G16B16A16_UINT = 12,
-    R16G16B16A16_SNORM = 13,
-    R16G16B16A16_SINT = 14,
-    R32G32_TYPELESS = 15,
-    R32G32_FLOAT = 16,
-    R32G32_UINT = 17,
-    R32G32_SINT = 18,
-    R32G8X24_TYPELESS = 19,
-    D32_FLOAT_S8X24_UINT = 20,
-    R32_FLOAT_X8X24_TYPELESS = 21,
-    X32_TYPELESS_G8X24_UINT = 22,
-    R10G10B10A2_TYPELESS = 23,
-    R10G10B10A2_UNORM = 24,
-    R10G10B10A2_UINT = 25,
-    R11G11B10_FLOAT = 26,
-    R8G8B8A8_TYPELESS = 27,
-    R8G8B8A8_UNORM = 28,
-    R8G8B8A8_UNORM_SRGB = 29,
-    R8G8B8A8_UINT = 30,
-    R8G8B8A8_SNORM = 31,
-    R8G8B8A8_SINT = 32,
-    R16G16_TYPELESS = 33,
-    R16G16_FLOAT = 34,
-    R16G16_UNORM = 35,
-    R16G16_UINT = 36,
-    R16G16_SNORM = 37,
-    R16G16_SINT = 38,
-    R32_TYPELESS = 39,
-    D32_FLOAT = 40,
-    R32_FLOAT = 41,
-    R32_UINT = 42,
-    R32_SINT = 43,
-    R24G8_TYPELESS = 44,
-    D24_UNORM_S8_UINT = 45,
-    R24_UNORM_X8_TYPELESS = 46,
-    X24_TYPELESS_G8_UINT = 47,
-    R8G8_TYPELESS = 48,
-    R8G8_UNORM = 49,
-    R8G8_UINT = 50,
-    R8G8_SNORM = 51,
-    R8G8_SINT = 52,
-    R16_TYPELESS = 53,
-    R16_FLOAT = 54,
-    D16_UNORM = 55,
-    R16_UNORM = 56,
-    R16_UINT = 57,
-    R16_SNORM = 58,
-    R16_SINT = 59,
-    R8_TYPELESS = 60,
-    R8_UNORM = 61,
-    R8_UINT = 62,
-    R8_SNORM = 63,
-    R8_SINT = 64,
-    A8_UNORM = 65,
-    R1_UNORM = 66,
-    R9G9B9E5_SHAREDEXP = 67,
-    R8G8_B8G8_UNORM = 68,
-    G8R8_G8B8_UNORM = 69,
-    BC1_TYPELESS = 70,
-    BC1_UNORM = 71,
-    BC1_UNORM_SRGB = 72,
-    BC2_TYPELESS = 73,
-    BC2_UNORM = 74,
-    BC2_UNORM_SRGB = 75,
-    BC3_TYPELESS = 76,
-    BC3_UNORM = 77,
-    BC3_UNORM_SRGB = 78,
-    BC4_TYPELESS = 79,
-    BC4_UNORM = 80,
-    BC4_SNORM = 81,
-    BC5_TYPELESS = 82,
-    BC5_UNORM = 83,
-    BC5_SNORM = 84,
-    B5G6R5_UNORM = 85,
-    B5G5R5A1_UNORM = 86,
-    B8G8R8A8_UNORM = 87,
-    B8G8R8X8_UNORM = 88,
-    R10G10B10_XR_BIAS_A2_UNORM = 89,
-    B8G8R8A8_TYPELESS = 90,
-    B8G8R8A8_UNORM_SRGB = 91,
-    B8G8R8X8_TYPELESS = 92,
-    B8G8R8X8_UNORM_SRGB = 93,
-    BC6H_TYPELESS = 94,
-    BC6H_UF16 = 95,
-    BC6H_SF16 = 96,
-    BC7_TYPELESS = 97,
-    BC7_UNORM = 98,
-    BC7_UNORM_SRGB = 99,
-    AYUV = 100,
-    Y410 = 101,
-    Y416 = 102,
-    NV12 = 103,
-    P010 = 104,
-    P016 = 105,
-    OPAQUE420 = 106,
-    YUY2 = 107,
-    Y210 = 108,
-    Y216 = 109,
-    NV11 = 110,
-    AI44 = 111,
-    IA44 = 112,
-    P8 = 113,
-    A8P8 = 114,
-    B4G4R4A4_UNORM = 115,
-    P208 = 130,
-    V208 = 131,
-    V408 = 132,
-    SAMPLER_FEEDBACK_MIN_MIP_OPAQUE,
-    SAMPLER_FEEDBACK_MIP_REGION_USED_OPAQUE,
-    FORCE_UINT = 0xffffffff,
+
+/// Declares a format enum from the table of its variants and their values, along with the
+/// conversions from and to its integer representation. Values that aren't in the table are
+/// rejected with [`SwizzleError::FormatOutOfRange`].
+macro_rules! format_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident: $repr:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr($repr)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant = $value,
+            )*
+        }
+
+        impl $name {
+            /// Every variant, in order of declaration
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+        }
+
+        impl From<$name> for $repr {
+            fn from(value: $name) -> Self {
+                value as $repr
+            }
+        }
+
+        impl TryFrom<$repr> for $name {
+            type Error = SwizzleError;
+
+            fn try_from(value: $repr) -> Result<Self, Self::Error> {
+                Ok(match value {
+                    $($value => $name::$variant,)*
+                    e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+                })
+            }
+        }
+    };
+}
+
+format_enum! {
+    #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, PartialEq, Hash)]
+    pub enum DxgiFormat: u32 {
+        Unknown = 0,
+        R32G32B32A32_TYPELESS = 1,
+        R32G32B32A32_FLOAT = 2,
+        R32G32B32A32_UINT = 3,
+        R32G32B32A32_SINT = 4,
+        R32G32B32_TYPELESS = 5,
+        R32G32B32_FLOAT = 6,
+        R32G32B32_UINT = 7,
+        R32G32B32_SINT = 8,
+        R16G16B16A16_TYPELESS = 9,
+        R16G16B16A16_FLOAT = 10,
+        R16G16B16A16_UNORM = 11,
+        R16G16B16A16_UINT = 12,
+        R16G16B16A16_SNORM = 13,
+        R16G16B16A16_SINT = 14,
+        R32G32_TYPELESS = 15,
+        R32G32_FLOAT = 16,
+        R32G32_UINT = 17,
+        R32G32_SINT = 18,
+        R32G8X24_TYPELESS = 19,
+        D32_FLOAT_S8X24_UINT = 20,
+        R32_FLOAT_X8X24_TYPELESS = 21,
+        X32_TYPELESS_G8X24_UINT = 22,
+        R10G10B10A2_TYPELESS = 23,
+        R10G10B10A2_UNORM = 24,
+        R10G10B10A2_UINT = 25,
+        R11G11B10_FLOAT = 26,
+        R8G8B8A8_TYPELESS = 27,
+        R8G8B8A8_UNORM = 28,
+        R8G8B8A8_UNORM_SRGB = 29,
+        R8G8B8A8_UINT = 30,
+        R8G8B8A8_SNORM = 31,
+        R8G8B8A8_SINT = 32,
+        R16G16_TYPELESS = 33,
+        R16G16_FLOAT = 34,
+        R16G16_UNORM = 35,
+        R16G16_UINT = 36,
+        R16G16_SNORM = 37,
+        R16G16_SINT = 38,
+        R32_TYPELESS = 39,
+        D32_FLOAT = 40,
+        R32_FLOAT = 41,
+        R32_UINT = 42,
+        R32_SINT = 43,
+        R24G8_TYPELESS = 44,
+        D24_UNORM_S8_UINT = 45,
+        R24_UNORM_X8_TYPELESS = 46,
+        X24_TYPELESS_G8_UINT = 47,
+        R8G8_TYPELESS = 48,
+        R8G8_UNORM = 49,
+        R8G8_UINT = 50,
+        R8G8_SNORM = 51,
+        R8G8_SINT = 52,
+        R16_TYPELESS = 53,
+        R16_FLOAT = 54,
+        D16_UNORM = 55,
+        R16_UNORM = 56,
+        R16_UINT = 57,
+        R16_SNORM = 58,
+        R16_SINT = 59,
+        R8_TYPELESS = 60,
+        R8_UNORM = 61,
+        R8_UINT = 62,
+        R8_SNORM = 63,
+        R8_SINT = 64,
+        A8_UNORM = 65,
+        R1_UNORM = 66,
+        R9G9B9E5_SHAREDEXP = 67,
+        R8G8_B8G8_UNORM = 68,
+        G8R8_G8B8_UNORM = 69,
+        BC1_TYPELESS = 70,
+        BC1_UNORM = 71,
+        BC1_UNORM_SRGB = 72,
+        BC2_TYPELESS = 73,
+        BC2_UNORM = 74,
+        BC2_UNORM_SRGB = 75,
+        BC3_TYPELESS = 76,
+        BC3_UNORM = 77,
+        BC3_UNORM_SRGB = 78,
+        BC4_TYPELESS = 79,
+        BC4_UNORM = 80,
+        BC4_SNORM = 81,
+        BC5_TYPELESS = 82,
+        BC5_UNORM = 83,
+        BC5_SNORM = 84,
+        B5G6R5_UNORM = 85,
+        B5G5R5A1_UNORM = 86,
+        B8G8R8A8_UNORM = 87,
+        B8G8R8X8_UNORM = 88,
+        R10G10B10_XR_BIAS_A2_UNORM = 89,
+        B8G8R8A8_TYPELESS = 90,
+        B8G8R8A8_UNORM_SRGB = 91,
+        B8G8R8X8_TYPELESS = 92,
+        B8G8R8X8_UNORM_SRGB = 93,
+        BC6H_TYPELESS = 94,
+        BC6H_UF16 = 95,
+        BC6H_SF16 = 96,
+        BC7_TYPELESS = 97,
+        BC7_UNORM = 98,
+        BC7_UNORM_SRGB = 99,
+        AYUV = 100,
+        Y410 = 101,
+        Y416 = 102,
+        NV12 = 103,
+        P010 = 104,
+        P016 = 105,
+        OPAQUE420 = 106,
+        YUY2 = 107,
+        Y210 = 108,
+        Y216 = 109,
+        NV11 = 110,
+        AI44 = 111,
+        IA44 = 112,
+        P8 = 113,
+        A8P8 = 114,
+        B4G4R4A4_UNORM = 115,
+        P208 = 130,
+        V208 = 131,
+        V408 = 132,
+        SAMPLER_FEEDBACK_MIN_MIP_OPAQUE = 189,
+        SAMPLER_FEEDBACK_MIP_REGION_USED_OPAQUE = 190,
+        FORCE_UINT = 0xffffffff,
+    }
 }
 
 impl DxgiFormat {
@@ -143,8 +189,99 @@ impl DxgiFormat {
         )
     }
 
-    pub fn calculate_pitch(&self, width: usize, height: usize) -> (usize, usize) {
-        match *self {
+    /// The sRGB variant of the format, or the format itself if it has none
+    pub fn to_srgb(self) -> Self {
+        match self {
+            DxgiFormat::R8G8B8A8_TYPELESS | DxgiFormat::R8G8B8A8_UNORM => {
+                DxgiFormat::R8G8B8A8_UNORM_SRGB
+            }
+            DxgiFormat::BC1_TYPELESS | DxgiFormat::BC1_UNORM => DxgiFormat::BC1_UNORM_SRGB,
+            DxgiFormat::BC2_TYPELESS | DxgiFormat::BC2_UNORM => DxgiFormat::BC2_UNORM_SRGB,
+            DxgiFormat::BC3_TYPELESS | DxgiFormat::BC3_UNORM => DxgiFormat::BC3_UNORM_SRGB,
+            DxgiFormat::B8G8R8A8_TYPELESS | DxgiFormat::B8G8R8A8_UNORM => {
+                DxgiFormat::B8G8R8A8_UNORM_SRGB
+            }
+            DxgiFormat::B8G8R8X8_TYPELESS | DxgiFormat::B8G8R8X8_UNORM => {
+                DxgiFormat::B8G8R8X8_UNORM_SRGB
+            }
+            DxgiFormat::BC7_TYPELESS | DxgiFormat::BC7_UNORM => DxgiFormat::BC7_UNORM_SRGB,
+            f => f,
+        }
+    }
+
+    /// The typeless variant of the format, or the format itself if it has none
+    pub fn to_typeless(self) -> Self {
+        match self {
+            DxgiFormat::R32G32B32A32_FLOAT
+            | DxgiFormat::R32G32B32A32_UINT
+            | DxgiFormat::R32G32B32A32_SINT => DxgiFormat::R32G32B32A32_TYPELESS,
+            DxgiFormat::R32G32B32_FLOAT
+            | DxgiFormat::R32G32B32_UINT
+            | DxgiFormat::R32G32B32_SINT => DxgiFormat::R32G32B32_TYPELESS,
+            DxgiFormat::R16G16B16A16_FLOAT
+            | DxgiFormat::R16G16B16A16_UNORM
+            | DxgiFormat::R16G16B16A16_UINT
+            | DxgiFormat::R16G16B16A16_SNORM
+            | DxgiFormat::R16G16B16A16_SINT => DxgiFormat::R16G16B16A16_TYPELESS,
+            DxgiFormat::R32G32_FLOAT | DxgiFormat::R32G32_UINT | DxgiFormat::R32G32_SINT => {
+                DxgiFormat::R32G32_TYPELESS
+            }
+            DxgiFormat::D32_FLOAT_S8X24_UINT => DxgiFormat::R32G8X24_TYPELESS,
+            DxgiFormat::R10G10B10A2_UNORM | DxgiFormat::R10G10B10A2_UINT => {
+                DxgiFormat::R10G10B10A2_TYPELESS
+            }
+            DxgiFormat::R8G8B8A8_UNORM
+            | DxgiFormat::R8G8B8A8_UNORM_SRGB
+            | DxgiFormat::R8G8B8A8_UINT
+            | DxgiFormat::R8G8B8A8_SNORM
+            | DxgiFormat::R8G8B8A8_SINT => DxgiFormat::R8G8B8A8_TYPELESS,
+            DxgiFormat::R16G16_FLOAT
+            | DxgiFormat::R16G16_UNORM
+            | DxgiFormat::R16G16_UINT
+            | DxgiFormat::R16G16_SNORM
+            | DxgiFormat::R16G16_SINT => DxgiFormat::R16G16_TYPELESS,
+            DxgiFormat::D32_FLOAT
+            | DxgiFormat::R32_FLOAT
+            | DxgiFormat::R32_UINT
+            | DxgiFormat::R32_SINT => DxgiFormat::R32_TYPELESS,
+            DxgiFormat::D24_UNORM_S8_UINT => DxgiFormat::R24G8_TYPELESS,
+            DxgiFormat::R8G8_UNORM
+            | DxgiFormat::R8G8_UINT
+            | DxgiFormat::R8G8_SNORM
+            | DxgiFormat::R8G8_SINT => DxgiFormat::R8G8_TYPELESS,
+            DxgiFormat::R16_FLOAT
+            | DxgiFormat::D16_UNORM
+            | DxgiFormat::R16_UNORM
+            | DxgiFormat::R16_UINT
+            | DxgiFormat::R16_SNORM
+            | DxgiFormat::R16_SINT => DxgiFormat::R16_TYPELESS,
+            DxgiFormat::R8_UNORM
+            | DxgiFormat::R8_UINT
+            | DxgiFormat::R8_SNORM
+            | DxgiFormat::R8_SINT => DxgiFormat::R8_TYPELESS,
+            DxgiFormat::BC1_UNORM | DxgiFormat::BC1_UNORM_SRGB => DxgiFormat::BC1_TYPELESS,
+            DxgiFormat::BC2_UNORM | DxgiFormat::BC2_UNORM_SRGB => DxgiFormat::BC2_TYPELESS,
+            DxgiFormat::BC3_UNORM | DxgiFormat::BC3_UNORM_SRGB => DxgiFormat::BC3_TYPELESS,
+            DxgiFormat::BC4_UNORM | DxgiFormat::BC4_SNORM => DxgiFormat::BC4_TYPELESS,
+            DxgiFormat::BC5_UNORM | DxgiFormat::BC5_SNORM => DxgiFormat::BC5_TYPELESS,
+            DxgiFormat::B8G8R8A8_UNORM | DxgiFormat::B8G8R8A8_UNORM_SRGB => {
+                DxgiFormat::B8G8R8A8_TYPELESS
+            }
+            DxgiFormat::B8G8R8X8_UNORM | DxgiFormat::B8G8R8X8_UNORM_SRGB => {
+                DxgiFormat::B8G8R8X8_TYPELESS
+            }
+            DxgiFormat::BC6H_UF16 | DxgiFormat::BC6H_SF16 => DxgiFormat::BC6H_TYPELESS,
+            DxgiFormat::BC7_UNORM | DxgiFormat::BC7_UNORM_SRGB => DxgiFormat::BC7_TYPELESS,
+            f => f,
+        }
+    }
+
+    pub fn calculate_pitch(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> Result<(usize, usize), SwizzleError> {
+        Ok(match *self {
             DxgiFormat::BC1_TYPELESS
             | DxgiFormat::BC1_UNORM
             | DxgiFormat::BC1_UNORM_SRGB
@@ -179,16 +316,25 @@ impl DxgiFormat {
                 (pitch, pitch * nbh)
             }
             _ => {
-                let pitch = (width * self.bpp()).div_ceil(8);
+                let pitch = (width * self.bpp()?).div_ceil(8);
                 (pitch, height * pitch)
             }
-        }
+        })
+    }
+}
+
+/// Size in bytes of a single pixel, for formats that are not block compressed
+fn whole_bytes(bpp: usize, format: u32) -> Result<usize, SwizzleError> {
+    if bpp.is_multiple_of(8) {
+        Ok(bpp / 8)
+    } else {
+        Err(SwizzleError::UnsupportedFormat(format))
     }
 }
 
 impl Format for DxgiFormat {
-    fn bpp(&self) -> usize {
-        match self {
+    fn bpp(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             DxgiFormat::R32G32B32A32_TYPELESS
             | DxgiFormat::R32G32B32A32_FLOAT
             | DxgiFormat::R32G32B32A32_UINT
@@ -301,8 +447,8 @@ impl Format for DxgiFormat {
             | DxgiFormat::BC7_TYPELESS
             | DxgiFormat::BC7_UNORM
             | DxgiFormat::BC7_UNORM_SRGB => 8,
-            u => panic!("{u:?}"),
-        }
+            u => return Err(SwizzleError::UnsupportedFormat((*u).into())),
+        })
     }
 
     fn is_compressed(&self) -> bool {
@@ -332,8 +478,8 @@ impl Format for DxgiFormat {
         )
     }
 
-    fn block_size(&self) -> usize {
-        match self {
+    fn block_size(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             DxgiFormat::BC1_TYPELESS
             | DxgiFormat::BC1_UNORM
             | DxgiFormat::BC1_UNORM_SRGB
@@ -355,8 +501,14 @@ impl Format for DxgiFormat {
             | DxgiFormat::BC7_TYPELESS
             | DxgiFormat::BC7_UNORM
             | DxgiFormat::BC7_UNORM_SRGB => 16,
-            u => u.bpp() / 8,
-        }
+            // Luma and chroma are stored in separate planes
+            DxgiFormat::NV12
+            | DxgiFormat::P010
+            | DxgiFormat::P016
+            | DxgiFormat::OPAQUE420
+            | DxgiFormat::NV11 => return Err(SwizzleError::UnsupportedFormat((*self).into())),
+            u => whole_bytes(u.bpp()?, *u as u32)?,
+        })
     }
 
     fn pixel_block_size(&self) -> usize {
@@ -391,131 +543,115 @@ impl Format for DxgiFormat {
     }
 }
 
-impl From<DxgiFormat> for u32 {
-    fn from(val: DxgiFormat) -> Self {
-        unsafe { transmute(val) }
-    }
-}
-
-impl TryFrom<u32> for DxgiFormat {
-    type Error = SwizzleError;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        Ok(match value {
-            0..=115 | 130..=132 => unsafe { transmute::<u32, DxgiFormat>(value) },
-            e => return Err(SwizzleError::FormatOutOfRange(e)),
-        })
-    }
-}
-
 // https://github.com/tge-was-taken/GFD-Studio/blob/master/GFDLibrary/Textures/GNF/SurfaceFormat.cs
-#[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
-#[repr(u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
-pub enum GcnSurfaceFormat {
-    /// <summary>Invalid surface format.</summary>
-    Invalid = 0x00000000,
-    /// <summary>One 8-bit channel. X=0xFF</summary>
-    Format8 = 0x00000001,
-    /// <summary>One 16-bit channel. X=0xFFFF</summary>
-    Format16 = 0x00000002,
-    /// <summary>Two 8-bit channels. X=0x00FF, Y=0xFF00</summary>
-    Format8_8 = 0x00000003,
-    /// <summary>One 32-bit channel. X=0xFFFFFFFF</summary>
-    Format32 = 0x00000004,
-    /// <summary>Two 16-bit channels. X=0x0000FFFF, Y=0xFFFF0000</summary>
-    Format16_16 = 0x00000005,
-    /// <summary>One 10-bit channel (Z) and two 11-bit channels (Y,X). X=0x000007FF, Y=0x003FF800, Z=0xFFC00000 Interpreted only as floating-point by texture unit, but also as integer by rasterizer.</summary>
-    Format10_11_11 = 0x00000006,
-    /// <summary>Two 11-bit channels (Z,Y) and one 10-bit channel (X). X=0x000003FF, Y=0x001FFC00, Z=0xFFE00000 Interpreted only as floating-point by texture unit, but also as integer by rasterizer.</summary>
-    Format11_11_10 = 0x00000007,
-    /// <summary>Three 10-bit channels (W,Z,Y) and one 2-bit channel (X). X=0x00000003, Y=0x00000FFC, Z=0x003FF000, W=0xFFC00000 X is never negative, even when YZW are.</summary>
-    Format10_10_10_2 = 0x00000008,
-    /// <summary>One 2-bit channel (W) and three 10-bit channels (Z,Y,X). X=0x000003FF, Y=0x000FFC00, Z=0x3FF00000, W=0xC0000000 W is never negative, even when XYZ are.</summary>
-    Format2_10_10_10 = 0x00000009,
-    /// <summary>Four 8-bit channels. X=0x000000FF, Y=0x0000FF00, Z=0x00FF0000, W=0xFF000000</summary>
-    Format8_8_8_8 = 0x0000000a,
-    /// <summary>Two 32-bit channels.</summary>
-    Format32_32 = 0x0000000b,
-    /// <summary>Four 16-bit channels.</summary>
-    Format16_16_16_16 = 0x0000000c,
-    /// <summary>Three 32-bit channels.</summary>
-    Format32_32_32 = 0x0000000d,
-    /// <summary>Four 32-bit channels.</summary>
-    Format32_32_32_32 = 0x0000000e,
-    /// <summary>One 5-bit channel (Z), one 6-bit channel (Y), and a second 5-bit channel (X). X=0x001F, Y=0x07E0, Z=0xF800</summary>
-    Format5_6_5 = 0x00000010,
-    /// <summary>One 1-bit channel (W) and three 5-bit channels (Z,Y,X). X=0x001F, Y=0x03E0, Z=0x7C00, W=0x8000</summary>
-    Format1_5_5_5 = 0x00000011,
-    /// <summary>Three 5-bit channels (W,Z,Y) and one 1-bit channel (X). X=0x0001, Y=0x003E, Z=0x07C0, W=0xF800</summary>
-    Format5_5_5_1 = 0x00000012,
-    /// <summary>Four 4-bit channels. X=0x000F, Y=0x00F0, Z=0x0F00, W=0xF000</summary>
-    Format4_4_4_4 = 0x00000013,
-    /// <summary>One 8-bit channel and one 24-bit channel.</summary>
-    Format8_24 = 0x00000014,
-    /// <summary>One 24-bit channel and one 8-bit channel.</summary>
-    Format24_8 = 0x00000015,
-    /// <summary>One 24-bit channel, one 8-bit channel, and one 32-bit channel.</summary>
-    FormatX24_8_32 = 0x00000016,
-    /// <summary>To be documented.</summary>
-    GbGr = 0x00000020,
-    /// <summary>To be documented.</summary>
-    BgRg = 0x00000021,
-    /// <summary>One 5-bit channel (W) and three 9-bit channels (Z,Y,X). X=0x000001FF, Y=0x0003FE00, Z=0x07FC0000, W=0xF8000000. Interpreted only as three 9-bit denormalized mantissas, and one shared 5-bit exponent.</summary>
-    Format5_9_9_9 = 0x00000022,
-    /// <summary>BC1 block-compressed surface.</summary>
-    BC1 = 0x00000023,
-    /// <summary>BC2 block-compressed surface.</summary>
-    BC2 = 0x00000024,
-    /// <summary>BC3 block-compressed surface.</summary>
-    BC3 = 0x00000025,
-    /// <summary>BC4 block-compressed surface.</summary>
-    BC4 = 0x00000026,
-    /// <summary>BC5 block-compressed surface.</summary>
-    BC5 = 0x00000027,
-    /// <summary>BC6 block-compressed surface.</summary>
-    BC6 = 0x00000028,
-    /// <summary>BC7 block-compressed surface.</summary>
-    BC7 = 0x00000029,
-    // /// <summary>8 bits-per-element FMASK surface (2 samples, 1 fragment).</summary>
-    // Fmask8_S2_F1 = 0x0000002C,
-    // /// <summary>8 bits-per-element FMASK surface (4 samples, 1 fragment).</summary>
-    // Fmask8_S4_F1 = 0x0000002D,
-    // /// <summary>8 bits-per-element FMASK surface (8 samples, 1 fragment).</summary>
-    // Fmask8_S8_F1 = 0x0000002E,
-    // /// <summary>8 bits-per-element FMASK surface (2 samples, 2 fragments).</summary>
-    // Fmask8_S2_F2 = 0x0000002F,
-    // /// <summary>8 bits-per-element FMASK surface (8 samples, 2 fragments).</summary>
-    // Fmask8_S4_F2 = 0x00000030,
-    // /// <summary>8 bits-per-element FMASK surface (4 samples, 4 fragments).</summary>
-    // Fmask8_S4_F4 = 0x00000031,
-    // /// <summary>16 bits-per-element FMASK surface (16 samples, 1 fragment).</summary>
-    // Fmask16_S16_F1 = 0x00000032,
-    // /// <summary>16 bits-per-element FMASK surface (8 samples, 2 fragments).</summary>
-    // Fmask16_S8_F2 = 0x00000033,
-    // /// <summary>32 bits-per-element FMASK surface (16 samples, 2 fragments).</summary>
-    // Fmask32_S16_F2 = 0x00000034,
-    // /// <summary>32 bits-per-element FMASK surface (8 samples, 4 fragments).</summary>
-    // Fmask32_S8_F4 = 0x00000035,
-    // /// <summary>32 bits-per-element FMASK surface (8 samples, 8 fragments).</summary>
-    // Fmask32_S8_F8 = 0x00000036,
-    // /// <summary>64 bits-per-element FMASK surface (16 samples, 4 fragments).</summary>
-    // Fmask64_S16_F4 = 0x00000037,
-    // /// <summary>64 bits-per-element FMASK surface (16 samples, 8 fragments).</summary>
-    // Fmask64_S16_F8 = 0x00000038,
-    // /// <summary>Two 4-bit channels (Y,X). X=0x0F, Y=0xF0</summary>
-    // Format4_4 = 0x00000039,
-    // /// <summary>One 6-bit channel (Z) and two 5-bit channels (Y,X). X=0x001F, Y=0x03E0, Z=0xFC00</summary>
-    // Format6_5_5 = 0x0000003A,
-    // /// <summary>One 1-bit channel. 8 pixels per byte, with pixel index increasing from LSB to MSB.</summary>
-    // Format1 = 0x0000003B,
-    // /// <summary>One 1-bit channel. 8 pixels per byte, with pixel index increasing from MSB to LSB.</summary>
-    // Format1Reversed = 0x0000003C,
+format_enum! {
+    #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, PartialEq, Hash)]
+    pub enum GcnSurfaceFormat: u16 {
+        /// <summary>Invalid surface format.</summary>
+        Invalid = 0x00000000,
+        /// <summary>One 8-bit channel. X=0xFF</summary>
+        Format8 = 0x00000001,
+        /// <summary>One 16-bit channel. X=0xFFFF</summary>
+        Format16 = 0x00000002,
+        /// <summary>Two 8-bit channels. X=0x00FF, Y=0xFF00</summary>
+        Format8_8 = 0x00000003,
+        /// <summary>One 32-bit channel. X=0xFFFFFFFF</summary>
+        Format32 = 0x00000004,
+        /// <summary>Two 16-bit channels. X=0x0000FFFF, Y=0xFFFF0000</summary>
+        Format16_16 = 0x00000005,
+        /// <summary>One 10-bit channel (Z) and two 11-bit channels (Y,X). X=0x000007FF, Y=0x003FF800, Z=0xFFC00000 Interpreted only as floating-point by texture unit, but also as integer by rasterizer.</summary>
+        Format10_11_11 = 0x00000006,
+        /// <summary>Two 11-bit channels (Z,Y) and one 10-bit channel (X). X=0x000003FF, Y=0x001FFC00, Z=0xFFE00000 Interpreted only as floating-point by texture unit, but also as integer by rasterizer.</summary>
+        Format11_11_10 = 0x00000007,
+        /// <summary>Three 10-bit channels (W,Z,Y) and one 2-bit channel (X). X=0x00000003, Y=0x00000FFC, Z=0x003FF000, W=0xFFC00000 X is never negative, even when YZW are.</summary>
+        Format10_10_10_2 = 0x00000008,
+        /// <summary>One 2-bit channel (W) and three 10-bit channels (Z,Y,X). X=0x000003FF, Y=0x000FFC00, Z=0x3FF00000, W=0xC0000000 W is never negative, even when XYZ are.</summary>
+        Format2_10_10_10 = 0x00000009,
+        /// <summary>Four 8-bit channels. X=0x000000FF, Y=0x0000FF00, Z=0x00FF0000, W=0xFF000000</summary>
+        Format8_8_8_8 = 0x0000000a,
+        /// <summary>Two 32-bit channels.</summary>
+        Format32_32 = 0x0000000b,
+        /// <summary>Four 16-bit channels.</summary>
+        Format16_16_16_16 = 0x0000000c,
+        /// <summary>Three 32-bit channels.</summary>
+        Format32_32_32 = 0x0000000d,
+        /// <summary>Four 32-bit channels.</summary>
+        Format32_32_32_32 = 0x0000000e,
+        /// <summary>One 5-bit channel (Z), one 6-bit channel (Y), and a second 5-bit channel (X). X=0x001F, Y=0x07E0, Z=0xF800</summary>
+        Format5_6_5 = 0x00000010,
+        /// <summary>One 1-bit channel (W) and three 5-bit channels (Z,Y,X). X=0x001F, Y=0x03E0, Z=0x7C00, W=0x8000</summary>
+        Format1_5_5_5 = 0x00000011,
+        /// <summary>Three 5-bit channels (W,Z,Y) and one 1-bit channel (X). X=0x0001, Y=0x003E, Z=0x07C0, W=0xF800</summary>
+        Format5_5_5_1 = 0x00000012,
+        /// <summary>Four 4-bit channels. X=0x000F, Y=0x00F0, Z=0x0F00, W=0xF000</summary>
+        Format4_4_4_4 = 0x00000013,
+        /// <summary>One 8-bit channel and one 24-bit channel.</summary>
+        Format8_24 = 0x00000014,
+        /// <summary>One 24-bit channel and one 8-bit channel.</summary>
+        Format24_8 = 0x00000015,
+        /// <summary>One 24-bit channel, one 8-bit channel, and one 32-bit channel.</summary>
+        FormatX24_8_32 = 0x00000016,
+        /// <summary>To be documented.</summary>
+        GbGr = 0x00000020,
+        /// <summary>To be documented.</summary>
+        BgRg = 0x00000021,
+        /// <summary>One 5-bit channel (W) and three 9-bit channels (Z,Y,X). X=0x000001FF, Y=0x0003FE00, Z=0x07FC0000, W=0xF8000000. Interpreted only as three 9-bit denormalized mantissas, and one shared 5-bit exponent.</summary>
+        Format5_9_9_9 = 0x00000022,
+        /// <summary>BC1 block-compressed surface.</summary>
+        BC1 = 0x00000023,
+        /// <summary>BC2 block-compressed surface.</summary>
+        BC2 = 0x00000024,
+        /// <summary>BC3 block-compressed surface.</summary>
+        BC3 = 0x00000025,
+        /// <summary>BC4 block-compressed surface.</summary>
+        BC4 = 0x00000026,
+        /// <summary>BC5 block-compressed surface.</summary>
+        BC5 = 0x00000027,
+        /// <summary>BC6 block-compressed surface.</summary>
+        BC6 = 0x00000028,
+        /// <summary>BC7 block-compressed surface.</summary>
+        BC7 = 0x00000029,
+        // /// <summary>8 bits-per-element FMASK surface (2 samples, 1 fragment).</summary>
+        // Fmask8_S2_F1 = 0x0000002C,
+        // /// <summary>8 bits-per-element FMASK surface (4 samples, 1 fragment).</summary>
+        // Fmask8_S4_F1 = 0x0000002D,
+        // /// <summary>8 bits-per-element FMASK surface (8 samples, 1 fragment).</summary>
+        // Fmask8_S8_F1 = 0x0000002E,
+        // /// <summary>8 bits-per-element FMASK surface (2 samples, 2 fragments).</summary>
+        // Fmask8_S2_F2 = 0x0000002F,
+        // /// <summary>8 bits-per-element FMASK surface (8 samples, 2 fragments).</summary>
+        // Fmask8_S4_F2 = 0x00000030,
+        // /// <summary>8 bits-per-element FMASK surface (4 samples, 4 fragments).</summary>
+        // Fmask8_S4_F4 = 0x00000031,
+        // /// <summary>16 bits-per-element FMASK surface (16 samples, 1 fragment).</summary>
+        // Fmask16_S16_F1 = 0x00000032,
+        // /// <summary>16 bits-per-element FMASK surface (8 samples, 2 fragments).</summary>
+        // Fmask16_S8_F2 = 0x00000033,
+        // /// <summary>32 bits-per-element FMASK surface (16 samples, 2 fragments).</summary>
+        // Fmask32_S16_F2 = 0x00000034,
+        // /// <summary>32 bits-per-element FMASK surface (8 samples, 4 fragments).</summary>
+        // Fmask32_S8_F4 = 0x00000035,
+        // /// <summary>32 bits-per-element FMASK surface (8 samples, 8 fragments).</summary>
+        // Fmask32_S8_F8 = 0x00000036,
+        // /// <summary>64 bits-per-element FMASK surface (16 samples, 4 fragments).</summary>
+        // Fmask64_S16_F4 = 0x00000037,
+        // /// <summary>64 bits-per-element FMASK surface (16 samples, 8 fragments).</summary>
+        // Fmask64_S16_F8 = 0x00000038,
+        // /// <summary>Two 4-bit channels (Y,X). X=0x0F, Y=0xF0</summary>
+        // Format4_4 = 0x00000039,
+        // /// <summary>One 6-bit channel (Z) and two 5-bit channels (Y,X). X=0x001F, Y=0x03E0, Z=0xFC00</summary>
+        // Format6_5_5 = 0x0000003A,
+        // /// <summary>One 1-bit channel. 8 pixels per byte, with pixel index increasing from LSB to MSB.</summary>
+        // Format1 = 0x0000003B,
+        // /// <summary>One 1-bit channel. 8 pixels per byte, with pixel index increasing from MSB to LSB.</summary>
+        // Format1Reversed = 0x0000003C,
+    }
 }
 
 impl Format for GcnSurfaceFormat {
-    fn bpp(&self) -> usize {
-        match self {
+    fn bpp(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             GcnSurfaceFormat::Format8 => 8,
             GcnSurfaceFormat::Format16 => 16,
             GcnSurfaceFormat::Format8_8 => 16,
@@ -529,12 +665,9 @@ impl Format for GcnSurfaceFormat {
             GcnSurfaceFormat::Format16_16_16_16 => 64,
             GcnSurfaceFormat::Format32_32_32_32 => 128,
             GcnSurfaceFormat::Format8_24 => 32,
-            GcnSurfaceFormat::Invalid => 0,
             GcnSurfaceFormat::Format1_5_5_5 => 16,
             GcnSurfaceFormat::Format24_8 => 32,
             GcnSurfaceFormat::FormatX24_8_32 => 64,
-            GcnSurfaceFormat::GbGr => 0,
-            GcnSurfaceFormat::BgRg => 0,
             GcnSurfaceFormat::BC1 | GcnSurfaceFormat::BC4 => 4,
             GcnSurfaceFormat::BC2
             | GcnSurfaceFormat::BC3
@@ -547,19 +680,22 @@ impl Format for GcnSurfaceFormat {
             GcnSurfaceFormat::Format5_5_5_1 => 16,
             GcnSurfaceFormat::Format4_4_4_4 => 16,
             GcnSurfaceFormat::Format5_9_9_9 => 32,
-        }
+            GcnSurfaceFormat::Invalid | GcnSurfaceFormat::GbGr | GcnSurfaceFormat::BgRg => {
+                return Err(SwizzleError::UnsupportedFormat(*self as u32));
+            }
+        })
     }
 
-    fn block_size(&self) -> usize {
-        match self {
+    fn block_size(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             GcnSurfaceFormat::BC1 | GcnSurfaceFormat::BC4 => 8,
             GcnSurfaceFormat::BC2
             | GcnSurfaceFormat::BC3
             | GcnSurfaceFormat::BC5
             | GcnSurfaceFormat::BC6
             | GcnSurfaceFormat::BC7 => 16,
-            u => u.bpp() / 8,
-        }
+            u => whole_bytes(u.bpp()?, *u as u32)?,
+        })
     }
 
     fn pixel_block_size(&self) -> usize {
@@ -593,105 +729,164 @@ impl Format for GcnSurfaceFormat {
     }
 }
 
-impl TryFrom<u16> for GcnSurfaceFormat {
-    type Error = SwizzleError;
+format_enum! {
+    #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, PartialEq, Hash)]
+    pub enum XenosSurfaceFormat: u8 {
+        k_1_REVERSE = 0,
+        k_1 = 1,
+        k_8 = 2,
+        k_1_5_5_5 = 3,
+        k_5_6_5 = 4,
+        k_6_5_5 = 5,
+        k_8_8_8_8 = 6,
+        k_2_10_10_10 = 7,
+        k_8_A = 8,
+        k_8_B = 9,
+        k_8_8 = 10,
+        k_Cr_Y1_Cb_Y0_REP = 11,
+        k_Y1_Cr_Y0_Cb_REP = 12,
+        k_16_16_EDRAM = 13,
+        k_8_8_8_8_A = 14,
+        k_4_4_4_4 = 15,
+        k_10_11_11 = 16,
+        k_11_11_10 = 17,
+        k_DXT1 = 18,
+        k_DXT2_3 = 19,
+        k_DXT4_5 = 20,
+        k_16_16_16_16_EDRAM = 21,
+        k_24_8 = 22,
+        k_24_8_FLOAT = 23,
+        k_16 = 24,
+        k_16_16 = 25,
+        k_16_16_16_16 = 26,
+        k_16_EXPAND = 27,
+        k_16_16_EXPAND = 28,
+        k_16_16_16_16_EXPAND = 29,
+        k_16_FLOAT = 30,
+        k_16_16_FLOAT = 31,
+        k_16_16_16_16_FLOAT = 32,
+        k_32 = 33,
+        k_32_32 = 34,
+        k_32_32_32_32 = 35,
+        k_32_FLOAT = 36,
+        k_32_32_FLOAT = 37,
+        k_32_32_32_32_FLOAT = 38,
+        k_32_AS_8 = 39,
+        k_32_AS_8_8 = 40,
+        k_16_MPEG = 41,
+        k_16_16_MPEG = 42,
+        k_8_INTERLACED = 43,
+        k_32_AS_8_INTERLACED = 44,
+        k_32_AS_8_8_INTERLACED = 45,
+        k_16_INTERLACED = 46,
+        k_16_MPEG_INTERLACED = 47,
+        k_16_16_MPEG_INTERLACED = 48,
+        k_DXN = 49,
+        k_8_8_8_8_AS_16_16_16_16 = 50,
+        k_DXT1_AS_16_16_16_16 = 51,
+        k_DXT2_3_AS_16_16_16_16 = 52,
+        k_DXT4_5_AS_16_16_16_16 = 53,
+        k_2_10_10_10_AS_16_16_16_16 = 54,
+        k_10_11_11_AS_16_16_16_16 = 55,
+        k_11_11_10_AS_16_16_16_16 = 56,
+        k_32_32_32_FLOAT = 57,
+        k_DXT3A = 58,
+        k_DXT5A = 59,
+        k_CTX1 = 60,
+        k_DXT3A_AS_1_1_1_1 = 61,
+        k_8_8_8_8_GAMMA_EDRAM = 62,
+        k_2_10_10_10_FLOAT_EDRAM = 63,
+    }
+}
 
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
-        Ok(match value {
-            0..=0x29 => unsafe { transmute::<u16, GcnSurfaceFormat>(value) },
-            e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+impl XenosSurfaceFormat {
+    /// Widths in bits of the X, Y, Z and W components of an uncompressed texel, starting from its
+    /// least significant bits once its byte order is swapped. Returns `None` for compressed
+    /// formats and for formats whose components aren't plain numbers, such as YUV or depth.
+    pub fn component_bits(&self) -> Option<&'static [u32]> {
+        Some(match self {
+            XenosSurfaceFormat::k_8
+            | XenosSurfaceFormat::k_8_A
+            | XenosSurfaceFormat::k_8_B
+            | XenosSurfaceFormat::k_8_INTERLACED => &[8],
+            XenosSurfaceFormat::k_1_5_5_5 => &[5, 5, 5, 1],
+            XenosSurfaceFormat::k_5_6_5 => &[5, 6, 5],
+            XenosSurfaceFormat::k_6_5_5 => &[5, 5, 6],
+            XenosSurfaceFormat::k_8_8_8_8
+            | XenosSurfaceFormat::k_8_8_8_8_A
+            | XenosSurfaceFormat::k_8_8_8_8_AS_16_16_16_16
+            | XenosSurfaceFormat::k_8_8_8_8_GAMMA_EDRAM => &[8, 8, 8, 8],
+            XenosSurfaceFormat::k_2_10_10_10 | XenosSurfaceFormat::k_2_10_10_10_AS_16_16_16_16 => {
+                &[10, 10, 10, 2]
+            }
+            XenosSurfaceFormat::k_8_8 => &[8, 8],
+            XenosSurfaceFormat::k_4_4_4_4 => &[4, 4, 4, 4],
+            XenosSurfaceFormat::k_10_11_11 | XenosSurfaceFormat::k_10_11_11_AS_16_16_16_16 => {
+                &[11, 11, 10]
+            }
+            XenosSurfaceFormat::k_11_11_10 | XenosSurfaceFormat::k_11_11_10_AS_16_16_16_16 => {
+                &[10, 11, 11]
+            }
+            XenosSurfaceFormat::k_16
+            | XenosSurfaceFormat::k_16_EXPAND
+            | XenosSurfaceFormat::k_16_FLOAT
+            | XenosSurfaceFormat::k_16_INTERLACED => &[16],
+            XenosSurfaceFormat::k_16_16
+            | XenosSurfaceFormat::k_16_16_EDRAM
+            | XenosSurfaceFormat::k_16_16_EXPAND
+            | XenosSurfaceFormat::k_16_16_FLOAT => &[16, 16],
+            XenosSurfaceFormat::k_16_16_16_16
+            | XenosSurfaceFormat::k_16_16_16_16_EDRAM
+            | XenosSurfaceFormat::k_16_16_16_16_EXPAND
+            | XenosSurfaceFormat::k_16_16_16_16_FLOAT => &[16, 16, 16, 16],
+            XenosSurfaceFormat::k_32 | XenosSurfaceFormat::k_32_FLOAT => &[32],
+            XenosSurfaceFormat::k_32_32 | XenosSurfaceFormat::k_32_32_FLOAT => &[32, 32],
+            XenosSurfaceFormat::k_32_32_32_FLOAT => &[32, 32, 32],
+            XenosSurfaceFormat::k_32_32_32_32 | XenosSurfaceFormat::k_32_32_32_32_FLOAT => {
+                &[32, 32, 32, 32]
+            }
+            _ => return None,
         })
     }
-}
 
-#[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
-#[repr(u8)]
-pub enum XenosSurfaceFormat {
-    k_1_REVERSE = 0,
-    k_1 = 1,
-    k_8 = 2,
-    k_1_5_5_5 = 3,
-    k_5_6_5 = 4,
-    k_6_5_5 = 5,
-    k_8_8_8_8 = 6,
-    k_2_10_10_10 = 7,
-    k_8_A = 8,
-    k_8_B = 9,
-    k_8_8 = 10,
-    k_Cr_Y1_Cb_Y0_REP = 11,
-    k_Y1_Cr_Y0_Cb_REP = 12,
-    k_16_16_EDRAM = 13,
-    k_8_8_8_8_A = 14,
-    k_4_4_4_4 = 15,
-    k_10_11_11 = 16,
-    k_11_11_10 = 17,
-    k_DXT1 = 18,
-    k_DXT2_3 = 19,
-    k_DXT4_5 = 20,
-    k_16_16_16_16_EDRAM = 21,
-    k_24_8 = 22,
-    k_24_8_FLOAT = 23,
-    k_16 = 24,
-    k_16_16 = 25,
-    k_16_16_16_16 = 26,
-    k_16_EXPAND = 27,
-    k_16_16_EXPAND = 28,
-    k_16_16_16_16_EXPAND = 29,
-    k_16_FLOAT = 30,
-    k_16_16_FLOAT = 31,
-    k_16_16_16_16_FLOAT = 32,
-    k_32 = 33,
-    k_32_32 = 34,
-    k_32_32_32_32 = 35,
-    k_32_FLOAT = 36,
-    k_32_32_FLOAT = 37,
-    k_32_32_32_32_FLOAT = 38,
-    k_32_AS_8 = 39,
-    k_32_AS_8_8 = 40,
-    k_16_MPEG = 41,
-    k_16_16_MPEG = 42,
-    k_8_INTERLACED = 43,
-    k_32_AS_8_INTERLACED = 44,
-    k_32_AS_8_8_INTERLACED = 45,
-    k_16_INTERLACED = 46,
-    k_16_MPEG_INTERLACED = 47,
-    k_16_16_MPEG_INTERLACED = 48,
-    k_DXN = 49,
-    k_8_8_8_8_AS_16_16_16_16 = 50,
-    k_DXT1_AS_16_16_16_16 = 51,
-    k_DXT2_3_AS_16_16_16_16 = 52,
-    k_DXT4_5_AS_16_16_16_16 = 53,
-    k_2_10_10_10_AS_16_16_16_16 = 54,
-    k_10_11_11_AS_16_16_16_16 = 55,
-    k_11_11_10_AS_16_16_16_16 = 56,
-    k_32_32_32_FLOAT = 57,
-    k_DXT3A = 58,
-    k_DXT5A = 59,
-    k_CTX1 = 60,
-    k_DXT3A_AS_1_1_1_1 = 61,
-    k_8_8_8_8_GAMMA_EDRAM = 62,
-    k_2_10_10_10_FLOAT_EDRAM = 63,
+    /// Whether the components of a texel are floating point numbers
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            XenosSurfaceFormat::k_16_FLOAT
+                | XenosSurfaceFormat::k_16_16_FLOAT
+                | XenosSurfaceFormat::k_16_16_16_16_FLOAT
+                | XenosSurfaceFormat::k_32_FLOAT
+                | XenosSurfaceFormat::k_32_32_FLOAT
+                | XenosSurfaceFormat::k_32_32_32_FLOAT
+                | XenosSurfaceFormat::k_32_32_32_32_FLOAT
+                | XenosSurfaceFormat::k_24_8_FLOAT
+                | XenosSurfaceFormat::k_2_10_10_10_FLOAT_EDRAM
+        )
+    }
 }
 
 impl Format for XenosSurfaceFormat {
-    fn block_size(&self) -> usize {
-        match &self {
-            XenosSurfaceFormat::k_DXT1 | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16 => 8,
+    fn block_size(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
+            XenosSurfaceFormat::k_DXT1
+            | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16
+            | XenosSurfaceFormat::k_CTX1
+            | XenosSurfaceFormat::k_DXT3A
+            | XenosSurfaceFormat::k_DXT3A_AS_1_1_1_1
+            | XenosSurfaceFormat::k_DXT5A => 8,
             XenosSurfaceFormat::k_DXN
             | XenosSurfaceFormat::k_DXT2_3
             | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16
-            | XenosSurfaceFormat::k_DXT3A
-            | XenosSurfaceFormat::k_DXT3A_AS_1_1_1_1
             | XenosSurfaceFormat::k_DXT4_5
-            | XenosSurfaceFormat::k_DXT4_5_AS_16_16_16_16
-            | XenosSurfaceFormat::k_DXT5A => 16,
-            u => u.bpp() / 8,
-        }
+            | XenosSurfaceFormat::k_DXT4_5_AS_16_16_16_16 => 16,
+            u => whole_bytes(u.bpp()?, *u as u32)?,
+        })
     }
 
-    fn bpp(&self) -> usize {
-        match self {
+    fn bpp(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             XenosSurfaceFormat::k_1_REVERSE | XenosSurfaceFormat::k_1 => 1,
             XenosSurfaceFormat::k_DXT1
             | XenosSurfaceFormat::k_DXT3A
@@ -754,13 +949,15 @@ impl Format for XenosSurfaceFormat {
             | XenosSurfaceFormat::k_32_32_FLOAT => 64,
             XenosSurfaceFormat::k_32_32_32_FLOAT => 96,
             XenosSurfaceFormat::k_32_32_32_32 | XenosSurfaceFormat::k_32_32_32_32_FLOAT => 128,
-        }
+        })
     }
 
     fn pixel_block_size(&self) -> usize {
         match self {
             XenosSurfaceFormat::k_DXT1
             | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16
+            | XenosSurfaceFormat::k_DXN
+            | XenosSurfaceFormat::k_CTX1
             | XenosSurfaceFormat::k_DXT2_3
             | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16
             | XenosSurfaceFormat::k_DXT3A
@@ -777,6 +974,8 @@ impl Format for XenosSurfaceFormat {
             self,
             XenosSurfaceFormat::k_DXT1
                 | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16
+                | XenosSurfaceFormat::k_DXN
+                | XenosSurfaceFormat::k_CTX1
                 | XenosSurfaceFormat::k_DXT2_3
                 | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16
                 | XenosSurfaceFormat::k_DXT3A
@@ -793,6 +992,7 @@ impl Format for XenosSurfaceFormat {
             XenosSurfaceFormat::k_DXT1
                 | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16
                 | XenosSurfaceFormat::k_DXN
+                | XenosSurfaceFormat::k_CTX1
                 | XenosSurfaceFormat::k_DXT2_3
                 | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16
                 | XenosSurfaceFormat::k_DXT3A
@@ -804,53 +1004,111 @@ impl Format for XenosSurfaceFormat {
     }
 }
 
-impl TryFrom<u8> for XenosSurfaceFormat {
-    type Error = SwizzleError;
+format_enum! {
+    #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, PartialEq, Hash)]
+    pub enum GcmSurfaceFormat: u8 {
+        B8 = 0x81,
+        A1R5G5B5 = 0x82,
+        A4R4G4B4 = 0x83,
+        R5G6B5 = 0x84,
+        A8R8G8B8 = 0x85,
+        COMPRESSED_DXT1 = 0x86,
+        COMPRESSED_DXT23 = 0x87,
+        COMPRESSED_DXT45 = 0x88,
+        G8B8 = 0x8B,
+        COMPRESSED_B8R8_G8R8 = 0x8D,
+        COMPRESSED_R8B8_R8G8 = 0x8E,
+        R6G5B5 = 0x8F,
+        DEPTH24_D8 = 0x90,
+        DEPTH24_D8_FLOAT = 0x91,
+        DEPTH16 = 0x92,
+        DEPTH16_FLOAT = 0x93,
+        X16 = 0x94,
+        Y16_X16 = 0x95,
+        R5G5B5A1 = 0x97,
+        COMPRESSED_HILO8 = 0x98,
+        COMPRESSED_HILO_S8 = 0x99,
+        W16_Z16_Y16_X16_FLOAT = 0x9A,
+        W32_Z32_Y32_X32_FLOAT = 0x9B,
+        X32_FLOAT = 0x9C,
+        D1R5G5B5 = 0x9D,
+        D8R8G8B8 = 0x9E,
+        Y16_X16_FLOAT = 0x9F,
+    }
+}
 
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        Ok(match value {
-            0..=63 => unsafe { transmute::<u8, XenosSurfaceFormat>(value) },
-            e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+impl GcmSurfaceFormat {
+    /// Bit offset and width of the A, R, G and B components the RSX reads from a texel, counted
+    /// from the least significant bit of its big endian value. Formats with two components read
+    /// them as both A and R, and G and B. A component the format lacks, such as the alpha of
+    /// `R5G6B5`, is `None` and reads as one.
+    /// Returns `None` for compressed and depth formats.
+    pub fn components(&self) -> Option<[Option<(u32, u32)>; 4]> {
+        Some(match self {
+            GcmSurfaceFormat::B8 => [Some((0, 8)); 4],
+            GcmSurfaceFormat::A1R5G5B5 => {
+                [Some((15, 1)), Some((10, 5)), Some((5, 5)), Some((0, 5))]
+            }
+            GcmSurfaceFormat::A4R4G4B4 => [Some((12, 4)), Some((8, 4)), Some((4, 4)), Some((0, 4))],
+            GcmSurfaceFormat::R5G6B5 => [None, Some((11, 5)), Some((5, 6)), Some((0, 5))],
+            GcmSurfaceFormat::A8R8G8B8 => {
+                [Some((24, 8)), Some((16, 8)), Some((8, 8)), Some((0, 8))]
+            }
+            GcmSurfaceFormat::G8B8 => [Some((8, 8)), Some((0, 8)), Some((8, 8)), Some((0, 8))],
+            GcmSurfaceFormat::R6G5B5 => [None, Some((10, 6)), Some((5, 5)), Some((0, 5))],
+            GcmSurfaceFormat::X16 => [Some((0, 16)); 4],
+            GcmSurfaceFormat::X32_FLOAT => [Some((0, 32)); 4],
+            GcmSurfaceFormat::Y16_X16 | GcmSurfaceFormat::Y16_X16_FLOAT => {
+                [Some((16, 16)), Some((0, 16)), Some((16, 16)), Some((0, 16))]
+            }
+            GcmSurfaceFormat::R5G5B5A1 => [Some((0, 1)), Some((11, 5)), Some((6, 5)), Some((1, 5))],
+            GcmSurfaceFormat::W16_Z16_Y16_X16_FLOAT => [
+                Some((48, 16)),
+                Some((0, 16)),
+                Some((16, 16)),
+                Some((32, 16)),
+            ],
+            GcmSurfaceFormat::W32_Z32_Y32_X32_FLOAT => [
+                Some((96, 32)),
+                Some((0, 32)),
+                Some((32, 32)),
+                Some((64, 32)),
+            ],
+            GcmSurfaceFormat::D1R5G5B5 => [None, Some((10, 5)), Some((5, 5)), Some((0, 5))],
+            GcmSurfaceFormat::D8R8G8B8 => [None, Some((16, 8)), Some((8, 8)), Some((0, 8))],
+            _ => return None,
         })
     }
-}
 
-#[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
-#[repr(u8)]
-pub enum GcmSurfaceFormat {
-    B8 = 0x81,
-    A1R5G5B5 = 0x82,
-    A4R4G4B4 = 0x83,
-    R5G6B5 = 0x84,
-    A8R8G8B8 = 0x85,
-    COMPRESSED_DXT1 = 0x86,
-    COMPRESSED_DXT23 = 0x87,
-    COMPRESSED_DXT45 = 0x88,
-    G8B8 = 0x8B,
-    COMPRESSED_B8R8_G8R8 = 0x8D,
-    COMPRESSED_R8B8_R8G8 = 0x8E,
-    R6G5B5 = 0x8F,
-    DEPTH24_D8 = 0x90,
-    DEPTH24_D8_FLOAT = 0x91,
-    DEPTH16 = 0x92,
-    DEPTH16_FLOAT = 0x93,
-    X16 = 0x94,
-    Y16_X16 = 0x95,
-    R5G5B5A1 = 0x97,
-    COMPRESSED_HILO8 = 0x98,
-    COMPRESSED_HILO_S8 = 0x99,
-    W16_Z16_Y16_X16_FLOAT = 0x9A,
-    W32_Z32_Y32_X32_FLOAT = 0x9B,
-    X32_FLOAT = 0x9C,
-    D1R5G5B5 = 0x9D,
-    D8R8G8B8 = 0x9E,
-    Y16_X16_FLOAT = 0x9F,
+    /// Reverses the bytes of every texel of `data` in place, converting detiled texels between
+    /// the big endian layout the RSX reads and the little endian layout of the [`DxgiFormat`]
+    /// they convert to. Compressed blocks are little endian already and are left as they are.
+    pub fn swap_byte_order_in_place(&self, data: &mut [u8]) -> Result<(), SwizzleError> {
+        if !self.is_compressed() {
+            data.chunks_exact_mut(self.block_size()?)
+                .for_each(|texel| texel.reverse());
+        }
+        Ok(())
+    }
+
+    /// Whether the components of a texel are floating point numbers
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            GcmSurfaceFormat::DEPTH24_D8_FLOAT
+                | GcmSurfaceFormat::DEPTH16_FLOAT
+                | GcmSurfaceFormat::W16_Z16_Y16_X16_FLOAT
+                | GcmSurfaceFormat::W32_Z32_Y32_X32_FLOAT
+                | GcmSurfaceFormat::X32_FLOAT
+                | GcmSurfaceFormat::Y16_X16_FLOAT
+        )
+    }
 }
 
 impl Format for GcmSurfaceFormat {
-    fn bpp(&self) -> usize {
-        match self {
+    fn bpp(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             GcmSurfaceFormat::B8 => 8,
             GcmSurfaceFormat::A1R5G5B5 => 16,
             GcmSurfaceFormat::A4R4G4B4 => 16,
@@ -867,16 +1125,23 @@ impl Format for GcmSurfaceFormat {
             GcmSurfaceFormat::Y16_X16 => 32,
             GcmSurfaceFormat::R5G5B5A1 => 16,
             GcmSurfaceFormat::Y16_X16_FLOAT => 32,
-            u => unimplemented!("Unsupported GCM surface format bpp ({u:?} => ??)"),
-        }
+            GcmSurfaceFormat::COMPRESSED_B8R8_G8R8 | GcmSurfaceFormat::COMPRESSED_R8B8_R8G8 => 16,
+            GcmSurfaceFormat::R6G5B5 => 16,
+            GcmSurfaceFormat::COMPRESSED_HILO8 | GcmSurfaceFormat::COMPRESSED_HILO_S8 => 16,
+            GcmSurfaceFormat::W16_Z16_Y16_X16_FLOAT => 64,
+            GcmSurfaceFormat::W32_Z32_Y32_X32_FLOAT => 128,
+            GcmSurfaceFormat::X32_FLOAT => 32,
+            GcmSurfaceFormat::D1R5G5B5 => 16,
+            GcmSurfaceFormat::D8R8G8B8 => 32,
+        })
     }
 
-    fn block_size(&self) -> usize {
-        match self {
+    fn block_size(&self) -> Result<usize, SwizzleError> {
+        Ok(match self {
             GcmSurfaceFormat::COMPRESSED_DXT1 => 8,
             GcmSurfaceFormat::COMPRESSED_DXT23 | GcmSurfaceFormat::COMPRESSED_DXT45 => 16,
-            u => u.bpp() / 8,
-        }
+            u => whole_bytes(u.bpp()?, *u as u32)?,
+        })
     }
 
     fn pixel_block_size(&self) -> usize {
@@ -906,12 +1171,390 @@ impl Format for GcmSurfaceFormat {
     }
 }
 
-impl TryFrom<u8> for GcmSurfaceFormat {
+/// GCN formats only describe the bit layout of a texel, so the DXGI equivalent is the `UNORM`
+/// variant, or `FLOAT` where the layout only exists as floating-point. sRGB and signed variants
+/// can be picked afterwards from the numeric format of the T# descriptor.
+impl TryFrom<GcnSurfaceFormat> for DxgiFormat {
     type Error = SwizzleError;
 
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
+    fn try_from(value: GcnSurfaceFormat) -> Result<Self, Self::Error> {
         Ok(match value {
-            0x81..=0x9F => unsafe { transmute::<u8, GcmSurfaceFormat>(value) },
+            GcnSurfaceFormat::Format8 => DxgiFormat::R8_UNORM,
+            GcnSurfaceFormat::Format16 => DxgiFormat::R16_UNORM,
+            GcnSurfaceFormat::Format8_8 => DxgiFormat::R8G8_UNORM,
+            GcnSurfaceFormat::Format32 => DxgiFormat::R32_FLOAT,
+            GcnSurfaceFormat::Format16_16 => DxgiFormat::R16G16_UNORM,
+            GcnSurfaceFormat::Format10_11_11 => DxgiFormat::R11G11B10_FLOAT,
+            GcnSurfaceFormat::Format2_10_10_10 => DxgiFormat::R10G10B10A2_UNORM,
+            GcnSurfaceFormat::Format8_8_8_8 => DxgiFormat::R8G8B8A8_UNORM,
+            GcnSurfaceFormat::Format32_32 => DxgiFormat::R32G32_FLOAT,
+            GcnSurfaceFormat::Format16_16_16_16 => DxgiFormat::R16G16B16A16_UNORM,
+            GcnSurfaceFormat::Format32_32_32 => DxgiFormat::R32G32B32_FLOAT,
+            GcnSurfaceFormat::Format32_32_32_32 => DxgiFormat::R32G32B32A32_FLOAT,
+            GcnSurfaceFormat::Format5_6_5 => DxgiFormat::B5G6R5_UNORM,
+            GcnSurfaceFormat::Format1_5_5_5 => DxgiFormat::B5G5R5A1_UNORM,
+            GcnSurfaceFormat::Format4_4_4_4 => DxgiFormat::B4G4R4A4_UNORM,
+            GcnSurfaceFormat::Format8_24 => DxgiFormat::D24_UNORM_S8_UINT,
+            GcnSurfaceFormat::FormatX24_8_32 => DxgiFormat::D32_FLOAT_S8X24_UINT,
+            GcnSurfaceFormat::GbGr => DxgiFormat::R8G8_B8G8_UNORM,
+            GcnSurfaceFormat::BgRg => DxgiFormat::G8R8_G8B8_UNORM,
+            GcnSurfaceFormat::Format5_9_9_9 => DxgiFormat::R9G9B9E5_SHAREDEXP,
+            GcnSurfaceFormat::BC1 => DxgiFormat::BC1_UNORM,
+            GcnSurfaceFormat::BC2 => DxgiFormat::BC2_UNORM,
+            GcnSurfaceFormat::BC3 => DxgiFormat::BC3_UNORM,
+            GcnSurfaceFormat::BC4 => DxgiFormat::BC4_UNORM,
+            GcnSurfaceFormat::BC5 => DxgiFormat::BC5_UNORM,
+            GcnSurfaceFormat::BC6 => DxgiFormat::BC6H_UF16,
+            GcnSurfaceFormat::BC7 => DxgiFormat::BC7_UNORM,
+            GcnSurfaceFormat::Invalid
+            | GcnSurfaceFormat::Format11_11_10
+            | GcnSurfaceFormat::Format10_10_10_2
+            | GcnSurfaceFormat::Format5_5_5_1
+            | GcnSurfaceFormat::Format24_8 => {
+                return Err(SwizzleError::FormatOutOfRange(value as u32));
+            }
+        })
+    }
+}
+
+/// Every numeric, sRGB and typeless variant maps to the GCN format with the same bit layout.
+/// Channel order is not part of a GCN format, so RGBA and BGRA layouts map to the same format.
+impl TryFrom<DxgiFormat> for GcnSurfaceFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: DxgiFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            DxgiFormat::R8_TYPELESS
+            | DxgiFormat::R8_UNORM
+            | DxgiFormat::R8_UINT
+            | DxgiFormat::R8_SNORM
+            | DxgiFormat::R8_SINT
+            | DxgiFormat::A8_UNORM => GcnSurfaceFormat::Format8,
+            DxgiFormat::R16_TYPELESS
+            | DxgiFormat::R16_FLOAT
+            | DxgiFormat::D16_UNORM
+            | DxgiFormat::R16_UNORM
+            | DxgiFormat::R16_UINT
+            | DxgiFormat::R16_SNORM
+            | DxgiFormat::R16_SINT => GcnSurfaceFormat::Format16,
+            DxgiFormat::R8G8_TYPELESS
+            | DxgiFormat::R8G8_UNORM
+            | DxgiFormat::R8G8_UINT
+            | DxgiFormat::R8G8_SNORM
+            | DxgiFormat::R8G8_SINT => GcnSurfaceFormat::Format8_8,
+            DxgiFormat::R32_TYPELESS
+            | DxgiFormat::D32_FLOAT
+            | DxgiFormat::R32_FLOAT
+            | DxgiFormat::R32_UINT
+            | DxgiFormat::R32_SINT => GcnSurfaceFormat::Format32,
+            DxgiFormat::R16G16_TYPELESS
+            | DxgiFormat::R16G16_FLOAT
+            | DxgiFormat::R16G16_UNORM
+            | DxgiFormat::R16G16_UINT
+            | DxgiFormat::R16G16_SNORM
+            | DxgiFormat::R16G16_SINT => GcnSurfaceFormat::Format16_16,
+            DxgiFormat::R11G11B10_FLOAT => GcnSurfaceFormat::Format10_11_11,
+            DxgiFormat::R10G10B10A2_TYPELESS
+            | DxgiFormat::R10G10B10A2_UNORM
+            | DxgiFormat::R10G10B10A2_UINT
+            | DxgiFormat::R10G10B10_XR_BIAS_A2_UNORM => GcnSurfaceFormat::Format2_10_10_10,
+            DxgiFormat::R8G8B8A8_TYPELESS
+            | DxgiFormat::R8G8B8A8_UNORM
+            | DxgiFormat::R8G8B8A8_UNORM_SRGB
+            | DxgiFormat::R8G8B8A8_UINT
+            | DxgiFormat::R8G8B8A8_SNORM
+            | DxgiFormat::R8G8B8A8_SINT
+            | DxgiFormat::B8G8R8A8_TYPELESS
+            | DxgiFormat::B8G8R8A8_UNORM
+            | DxgiFormat::B8G8R8A8_UNORM_SRGB
+            | DxgiFormat::B8G8R8X8_TYPELESS
+            | DxgiFormat::B8G8R8X8_UNORM
+            | DxgiFormat::B8G8R8X8_UNORM_SRGB => GcnSurfaceFormat::Format8_8_8_8,
+            DxgiFormat::R32G32_TYPELESS
+            | DxgiFormat::R32G32_FLOAT
+            | DxgiFormat::R32G32_UINT
+            | DxgiFormat::R32G32_SINT => GcnSurfaceFormat::Format32_32,
+            DxgiFormat::R16G16B16A16_TYPELESS
+            | DxgiFormat::R16G16B16A16_FLOAT
+            | DxgiFormat::R16G16B16A16_UNORM
+            | DxgiFormat::R16G16B16A16_UINT
+            | DxgiFormat::R16G16B16A16_SNORM
+            | DxgiFormat::R16G16B16A16_SINT => GcnSurfaceFormat::Format16_16_16_16,
+            DxgiFormat::R32G32B32_TYPELESS
+            | DxgiFormat::R32G32B32_FLOAT
+            | DxgiFormat::R32G32B32_UINT
+            | DxgiFormat::R32G32B32_SINT => GcnSurfaceFormat::Format32_32_32,
+            DxgiFormat::R32G32B32A32_TYPELESS
+            | DxgiFormat::R32G32B32A32_FLOAT
+            | DxgiFormat::R32G32B32A32_UINT
+            | DxgiFormat::R32G32B32A32_SINT => GcnSurfaceFormat::Format32_32_32_32,
+            DxgiFormat::B5G6R5_UNORM => GcnSurfaceFormat::Format5_6_5,
+            DxgiFormat::B5G5R5A1_UNORM => GcnSurfaceFormat::Format1_5_5_5,
+            DxgiFormat::B4G4R4A4_UNORM => GcnSurfaceFormat::Format4_4_4_4,
+            DxgiFormat::R24G8_TYPELESS
+            | DxgiFormat::D24_UNORM_S8_UINT
+            | DxgiFormat::R24_UNORM_X8_TYPELESS
+            | DxgiFormat::X24_TYPELESS_G8_UINT => GcnSurfaceFormat::Format8_24,
+            DxgiFormat::R32G8X24_TYPELESS
+            | DxgiFormat::D32_FLOAT_S8X24_UINT
+            | DxgiFormat::R32_FLOAT_X8X24_TYPELESS
+            | DxgiFormat::X32_TYPELESS_G8X24_UINT => GcnSurfaceFormat::FormatX24_8_32,
+            DxgiFormat::R8G8_B8G8_UNORM => GcnSurfaceFormat::GbGr,
+            DxgiFormat::G8R8_G8B8_UNORM => GcnSurfaceFormat::BgRg,
+            DxgiFormat::R9G9B9E5_SHAREDEXP => GcnSurfaceFormat::Format5_9_9_9,
+            DxgiFormat::BC1_TYPELESS | DxgiFormat::BC1_UNORM | DxgiFormat::BC1_UNORM_SRGB => {
+                GcnSurfaceFormat::BC1
+            }
+            DxgiFormat::BC2_TYPELESS | DxgiFormat::BC2_UNORM | DxgiFormat::BC2_UNORM_SRGB => {
+                GcnSurfaceFormat::BC2
+            }
+            DxgiFormat::BC3_TYPELESS | DxgiFormat::BC3_UNORM | DxgiFormat::BC3_UNORM_SRGB => {
+                GcnSurfaceFormat::BC3
+            }
+            DxgiFormat::BC4_TYPELESS | DxgiFormat::BC4_UNORM | DxgiFormat::BC4_SNORM => {
+                GcnSurfaceFormat::BC4
+            }
+            DxgiFormat::BC5_TYPELESS | DxgiFormat::BC5_UNORM | DxgiFormat::BC5_SNORM => {
+                GcnSurfaceFormat::BC5
+            }
+            DxgiFormat::BC6H_TYPELESS | DxgiFormat::BC6H_UF16 | DxgiFormat::BC6H_SF16 => {
+                GcnSurfaceFormat::BC6
+            }
+            DxgiFormat::BC7_TYPELESS | DxgiFormat::BC7_UNORM | DxgiFormat::BC7_UNORM_SRGB => {
+                GcnSurfaceFormat::BC7
+            }
+            e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+        })
+    }
+}
+
+/// Gamma is set in the fetch constant rather than the format, so textures map to `UNORM`
+/// variants. Layouts that only exist in EDRAM or with a non-DXGI interpretation, like the
+/// expanded 16-bit fixed-point formats, have no equivalent.
+impl TryFrom<XenosSurfaceFormat> for DxgiFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: XenosSurfaceFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            XenosSurfaceFormat::k_1 => DxgiFormat::R1_UNORM,
+            XenosSurfaceFormat::k_8 => DxgiFormat::R8_UNORM,
+            XenosSurfaceFormat::k_8_A => DxgiFormat::A8_UNORM,
+            XenosSurfaceFormat::k_1_5_5_5 => DxgiFormat::B5G5R5A1_UNORM,
+            XenosSurfaceFormat::k_5_6_5 => DxgiFormat::B5G6R5_UNORM,
+            XenosSurfaceFormat::k_4_4_4_4 => DxgiFormat::B4G4R4A4_UNORM,
+            XenosSurfaceFormat::k_8_8_8_8
+            | XenosSurfaceFormat::k_8_8_8_8_A
+            | XenosSurfaceFormat::k_8_8_8_8_AS_16_16_16_16 => DxgiFormat::R8G8B8A8_UNORM,
+            XenosSurfaceFormat::k_8_8_8_8_GAMMA_EDRAM => DxgiFormat::R8G8B8A8_UNORM_SRGB,
+            XenosSurfaceFormat::k_2_10_10_10 | XenosSurfaceFormat::k_2_10_10_10_AS_16_16_16_16 => {
+                DxgiFormat::R10G10B10A2_UNORM
+            }
+            XenosSurfaceFormat::k_8_8 => DxgiFormat::R8G8_UNORM,
+            XenosSurfaceFormat::k_Cr_Y1_Cb_Y0_REP => DxgiFormat::YUY2,
+            XenosSurfaceFormat::k_16 => DxgiFormat::R16_UNORM,
+            XenosSurfaceFormat::k_16_16 => DxgiFormat::R16G16_UNORM,
+            XenosSurfaceFormat::k_16_16_16_16 => DxgiFormat::R16G16B16A16_UNORM,
+            XenosSurfaceFormat::k_16_FLOAT => DxgiFormat::R16_FLOAT,
+            XenosSurfaceFormat::k_16_16_FLOAT => DxgiFormat::R16G16_FLOAT,
+            XenosSurfaceFormat::k_16_16_16_16_FLOAT => DxgiFormat::R16G16B16A16_FLOAT,
+            XenosSurfaceFormat::k_32 => DxgiFormat::R32_UINT,
+            XenosSurfaceFormat::k_32_32 => DxgiFormat::R32G32_UINT,
+            XenosSurfaceFormat::k_32_32_32_32 => DxgiFormat::R32G32B32A32_UINT,
+            XenosSurfaceFormat::k_32_FLOAT => DxgiFormat::R32_FLOAT,
+            XenosSurfaceFormat::k_32_32_FLOAT => DxgiFormat::R32G32_FLOAT,
+            XenosSurfaceFormat::k_32_32_32_FLOAT => DxgiFormat::R32G32B32_FLOAT,
+            XenosSurfaceFormat::k_32_32_32_32_FLOAT => DxgiFormat::R32G32B32A32_FLOAT,
+            XenosSurfaceFormat::k_DXT1 | XenosSurfaceFormat::k_DXT1_AS_16_16_16_16 => {
+                DxgiFormat::BC1_UNORM
+            }
+            XenosSurfaceFormat::k_DXT2_3 | XenosSurfaceFormat::k_DXT2_3_AS_16_16_16_16 => {
+                DxgiFormat::BC2_UNORM
+            }
+            XenosSurfaceFormat::k_DXT4_5 | XenosSurfaceFormat::k_DXT4_5_AS_16_16_16_16 => {
+                DxgiFormat::BC3_UNORM
+            }
+            XenosSurfaceFormat::k_DXT5A => DxgiFormat::BC4_UNORM,
+            XenosSurfaceFormat::k_DXN => DxgiFormat::BC5_UNORM,
+            e => return Err(SwizzleError::FormatOutOfRange(e as u32)),
+        })
+    }
+}
+
+/// Every numeric, sRGB and typeless variant maps to the Xenos format with the same bit layout.
+impl TryFrom<DxgiFormat> for XenosSurfaceFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: DxgiFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            DxgiFormat::R1_UNORM => XenosSurfaceFormat::k_1,
+            DxgiFormat::R8_TYPELESS
+            | DxgiFormat::R8_UNORM
+            | DxgiFormat::R8_UINT
+            | DxgiFormat::R8_SNORM
+            | DxgiFormat::R8_SINT => XenosSurfaceFormat::k_8,
+            DxgiFormat::A8_UNORM => XenosSurfaceFormat::k_8_A,
+            DxgiFormat::B5G5R5A1_UNORM => XenosSurfaceFormat::k_1_5_5_5,
+            DxgiFormat::B5G6R5_UNORM => XenosSurfaceFormat::k_5_6_5,
+            DxgiFormat::B4G4R4A4_UNORM => XenosSurfaceFormat::k_4_4_4_4,
+            DxgiFormat::R8G8B8A8_TYPELESS
+            | DxgiFormat::R8G8B8A8_UNORM
+            | DxgiFormat::R8G8B8A8_UNORM_SRGB
+            | DxgiFormat::R8G8B8A8_UINT
+            | DxgiFormat::R8G8B8A8_SNORM
+            | DxgiFormat::R8G8B8A8_SINT
+            | DxgiFormat::B8G8R8A8_TYPELESS
+            | DxgiFormat::B8G8R8A8_UNORM
+            | DxgiFormat::B8G8R8A8_UNORM_SRGB
+            | DxgiFormat::B8G8R8X8_TYPELESS
+            | DxgiFormat::B8G8R8X8_UNORM
+            | DxgiFormat::B8G8R8X8_UNORM_SRGB => XenosSurfaceFormat::k_8_8_8_8,
+            DxgiFormat::R10G10B10A2_TYPELESS
+            | DxgiFormat::R10G10B10A2_UNORM
+            | DxgiFormat::R10G10B10A2_UINT => XenosSurfaceFormat::k_2_10_10_10,
+            DxgiFormat::R8G8_TYPELESS
+            | DxgiFormat::R8G8_UNORM
+            | DxgiFormat::R8G8_UINT
+            | DxgiFormat::R8G8_SNORM
+            | DxgiFormat::R8G8_SINT => XenosSurfaceFormat::k_8_8,
+            DxgiFormat::YUY2 => XenosSurfaceFormat::k_Cr_Y1_Cb_Y0_REP,
+            DxgiFormat::R16_TYPELESS
+            | DxgiFormat::D16_UNORM
+            | DxgiFormat::R16_UNORM
+            | DxgiFormat::R16_UINT
+            | DxgiFormat::R16_SNORM
+            | DxgiFormat::R16_SINT => XenosSurfaceFormat::k_16,
+            DxgiFormat::R16G16_TYPELESS
+            | DxgiFormat::R16G16_UNORM
+            | DxgiFormat::R16G16_UINT
+            | DxgiFormat::R16G16_SNORM
+            | DxgiFormat::R16G16_SINT => XenosSurfaceFormat::k_16_16,
+            DxgiFormat::R16G16B16A16_TYPELESS
+            | DxgiFormat::R16G16B16A16_UNORM
+            | DxgiFormat::R16G16B16A16_UINT
+            | DxgiFormat::R16G16B16A16_SNORM
+            | DxgiFormat::R16G16B16A16_SINT => XenosSurfaceFormat::k_16_16_16_16,
+            DxgiFormat::R16_FLOAT => XenosSurfaceFormat::k_16_FLOAT,
+            DxgiFormat::R16G16_FLOAT => XenosSurfaceFormat::k_16_16_FLOAT,
+            DxgiFormat::R16G16B16A16_FLOAT => XenosSurfaceFormat::k_16_16_16_16_FLOAT,
+            DxgiFormat::R32_TYPELESS | DxgiFormat::R32_UINT | DxgiFormat::R32_SINT => {
+                XenosSurfaceFormat::k_32
+            }
+            DxgiFormat::R32G32_TYPELESS | DxgiFormat::R32G32_UINT | DxgiFormat::R32G32_SINT => {
+                XenosSurfaceFormat::k_32_32
+            }
+            DxgiFormat::R32G32B32A32_TYPELESS
+            | DxgiFormat::R32G32B32A32_UINT
+            | DxgiFormat::R32G32B32A32_SINT => XenosSurfaceFormat::k_32_32_32_32,
+            DxgiFormat::R32_FLOAT | DxgiFormat::D32_FLOAT => XenosSurfaceFormat::k_32_FLOAT,
+            DxgiFormat::R32G32_FLOAT => XenosSurfaceFormat::k_32_32_FLOAT,
+            DxgiFormat::R32G32B32_FLOAT => XenosSurfaceFormat::k_32_32_32_FLOAT,
+            DxgiFormat::R32G32B32A32_FLOAT => XenosSurfaceFormat::k_32_32_32_32_FLOAT,
+            DxgiFormat::BC1_TYPELESS | DxgiFormat::BC1_UNORM | DxgiFormat::BC1_UNORM_SRGB => {
+                XenosSurfaceFormat::k_DXT1
+            }
+            DxgiFormat::BC2_TYPELESS | DxgiFormat::BC2_UNORM | DxgiFormat::BC2_UNORM_SRGB => {
+                XenosSurfaceFormat::k_DXT2_3
+            }
+            DxgiFormat::BC3_TYPELESS | DxgiFormat::BC3_UNORM | DxgiFormat::BC3_UNORM_SRGB => {
+                XenosSurfaceFormat::k_DXT4_5
+            }
+            DxgiFormat::BC4_TYPELESS | DxgiFormat::BC4_UNORM | DxgiFormat::BC4_SNORM => {
+                XenosSurfaceFormat::k_DXT5A
+            }
+            DxgiFormat::BC5_TYPELESS | DxgiFormat::BC5_UNORM | DxgiFormat::BC5_SNORM => {
+                XenosSurfaceFormat::k_DXN
+            }
+            e => return Err(SwizzleError::FormatOutOfRange(e.into())),
+        })
+    }
+}
+
+/// GCM names its channels from the most significant bits of a big endian texel down, so once
+/// [`GcmSurfaceFormat::swap_byte_order_in_place`] has made the texels little endian, `A8R8G8B8`
+/// is DXGI's `B8G8R8A8`. Gamma is set per texture rather than per format, so textures map to
+/// `UNORM` variants. Depth with stencil keeps its depth in the high bits, which no DXGI format
+/// does, so `DEPTH24_D8` has no equivalent.
+impl TryFrom<GcmSurfaceFormat> for DxgiFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: GcmSurfaceFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            GcmSurfaceFormat::B8 => DxgiFormat::R8_UNORM,
+            GcmSurfaceFormat::A1R5G5B5 => DxgiFormat::B5G5R5A1_UNORM,
+            GcmSurfaceFormat::A4R4G4B4 => DxgiFormat::B4G4R4A4_UNORM,
+            GcmSurfaceFormat::R5G6B5 => DxgiFormat::B5G6R5_UNORM,
+            GcmSurfaceFormat::A8R8G8B8 => DxgiFormat::B8G8R8A8_UNORM,
+            GcmSurfaceFormat::D8R8G8B8 => DxgiFormat::B8G8R8X8_UNORM,
+            GcmSurfaceFormat::COMPRESSED_DXT1 => DxgiFormat::BC1_UNORM,
+            GcmSurfaceFormat::COMPRESSED_DXT23 => DxgiFormat::BC2_UNORM,
+            GcmSurfaceFormat::COMPRESSED_DXT45 => DxgiFormat::BC3_UNORM,
+            GcmSurfaceFormat::G8B8 | GcmSurfaceFormat::COMPRESSED_HILO8 => DxgiFormat::R8G8_UNORM,
+            GcmSurfaceFormat::COMPRESSED_HILO_S8 => DxgiFormat::R8G8_SNORM,
+            GcmSurfaceFormat::DEPTH16 => DxgiFormat::D16_UNORM,
+            GcmSurfaceFormat::X16 => DxgiFormat::R16_UNORM,
+            GcmSurfaceFormat::Y16_X16 => DxgiFormat::R16G16_UNORM,
+            GcmSurfaceFormat::Y16_X16_FLOAT => DxgiFormat::R16G16_FLOAT,
+            GcmSurfaceFormat::W16_Z16_Y16_X16_FLOAT => DxgiFormat::R16G16B16A16_FLOAT,
+            GcmSurfaceFormat::W32_Z32_Y32_X32_FLOAT => DxgiFormat::R32G32B32A32_FLOAT,
+            GcmSurfaceFormat::X32_FLOAT => DxgiFormat::R32_FLOAT,
+            e => return Err(SwizzleError::FormatOutOfRange(e as u32)),
+        })
+    }
+}
+
+/// Every numeric, sRGB and typeless variant maps to the GCM format with the same bit layout,
+/// once [`GcmSurfaceFormat::swap_byte_order_in_place`] has made the texels big endian. GCM has no
+/// RGBA counterpart of `A8R8G8B8`, so `R8G8B8A8` formats are rejected rather than having their
+/// red and blue swapped.
+impl TryFrom<DxgiFormat> for GcmSurfaceFormat {
+    type Error = SwizzleError;
+
+    fn try_from(value: DxgiFormat) -> Result<Self, Self::Error> {
+        Ok(match value {
+            DxgiFormat::R8_TYPELESS
+            | DxgiFormat::R8_UNORM
+            | DxgiFormat::R8_UINT
+            | DxgiFormat::R8_SNORM
+            | DxgiFormat::R8_SINT
+            | DxgiFormat::A8_UNORM => GcmSurfaceFormat::B8,
+            DxgiFormat::B5G5R5A1_UNORM => GcmSurfaceFormat::A1R5G5B5,
+            DxgiFormat::B4G4R4A4_UNORM => GcmSurfaceFormat::A4R4G4B4,
+            DxgiFormat::B5G6R5_UNORM => GcmSurfaceFormat::R5G6B5,
+            DxgiFormat::B8G8R8A8_TYPELESS
+            | DxgiFormat::B8G8R8A8_UNORM
+            | DxgiFormat::B8G8R8A8_UNORM_SRGB => GcmSurfaceFormat::A8R8G8B8,
+            DxgiFormat::B8G8R8X8_TYPELESS
+            | DxgiFormat::B8G8R8X8_UNORM
+            | DxgiFormat::B8G8R8X8_UNORM_SRGB => GcmSurfaceFormat::D8R8G8B8,
+            DxgiFormat::BC1_TYPELESS | DxgiFormat::BC1_UNORM | DxgiFormat::BC1_UNORM_SRGB => {
+                GcmSurfaceFormat::COMPRESSED_DXT1
+            }
+            DxgiFormat::BC2_TYPELESS | DxgiFormat::BC2_UNORM | DxgiFormat::BC2_UNORM_SRGB => {
+                GcmSurfaceFormat::COMPRESSED_DXT23
+            }
+            DxgiFormat::BC3_TYPELESS | DxgiFormat::BC3_UNORM | DxgiFormat::BC3_UNORM_SRGB => {
+                GcmSurfaceFormat::COMPRESSED_DXT45
+            }
+            DxgiFormat::R8G8_TYPELESS
+            | DxgiFormat::R8G8_UNORM
+            | DxgiFormat::R8G8_UINT
+            | DxgiFormat::R8G8_SNORM
+            | DxgiFormat::R8G8_SINT => GcmSurfaceFormat::G8B8,
+            DxgiFormat::D16_UNORM => GcmSurfaceFormat::DEPTH16,
+            DxgiFormat::R16_TYPELESS
+            | DxgiFormat::R16_UNORM
+            | DxgiFormat::R16_UINT
+            | DxgiFormat::R16_SNORM
+            | DxgiFormat::R16_SINT => GcmSurfaceFormat::X16,
+            DxgiFormat::R16G16_TYPELESS
+            | DxgiFormat::R16G16_UNORM
+            | DxgiFormat::R16G16_UINT
+            | DxgiFormat::R16G16_SNORM
+            | DxgiFormat::R16G16_SINT => GcmSurfaceFormat::Y16_X16,
+            DxgiFormat::R16G16_FLOAT => GcmSurfaceFormat::Y16_X16_FLOAT,
+            DxgiFormat::R16G16B16A16_FLOAT => GcmSurfaceFormat::W16_Z16_Y16_X16_FLOAT,
+            DxgiFormat::R32G32B32A32_FLOAT => GcmSurfaceFormat::W32_Z32_Y32_X32_FLOAT,
+            DxgiFormat::R32_FLOAT => GcmSurfaceFormat::X32_FLOAT,
             e => return Err(SwizzleError::FormatOutOfRange(e.into())),
         })
     }
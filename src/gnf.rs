@@ -0,0 +1,367 @@
+//! Reading and writing PS4 GNF texture files.
+//!
+//! A GNF file holds one or more textures, each described by a GCN T# image resource descriptor,
+//! followed by their swizzled data. The descriptors carry everything [`Ps4`] needs to deswizzle
+//! the data: format, dimensions, pitch, mip levels and tile mode.
+//!
+//! ```
+//! # use swizzleinator::{format::GcnSurfaceFormat, gnf::{self, Gnf, TextureDescriptor}, swizzle::{Deswizzler, SurfaceDesc, TextureLayout, ps::Ps4}};
+//! let desc = SurfaceDesc::builder(GcnSurfaceFormat::BC1)
+//!     .width(128)
+//!     .height(128)
+//!     .mip_count(8)
+//!     .build()
+//!     .unwrap();
+//! let swizzled = vec![0u8; Ps4::swizzled_size(&desc).unwrap()];
+//! let descriptor = TextureDescriptor::from_desc(&desc).unwrap();
+//!
+//! let mut file = vec![0u8; gnf::file_size(&[(descriptor, &swizzled)])];
+//! gnf::write(&mut file, &[(descriptor, &swizzled)]).unwrap();
+//!
+//! let gnf = Gnf::read(&file).unwrap();
+//! let texture = gnf.texture(0).unwrap();
+//! let desc = texture.descriptor().surface_desc().unwrap();
+//!
+//! let mut linear = vec![0u8; Ps4::linear_size(&desc).unwrap()];
+//! Ps4::deswizzle_surface(texture.data(), &mut linear, &desc).unwrap();
+//! ```
+//!
+//! [`Ps4`]: crate::swizzle::ps::Ps4
+
+use crate::{
+    bytes::{read_u32, slice, write_u32},
+    format::GcnSurfaceFormat,
+    swizzle::{
+        AlignmentPolicy, SurfaceDesc, SwizzleError, TextureKind, TextureLayout, TextureSlice,
+        gcn::TileMode, ps::Ps4,
+    },
+};
+
+const MAGIC: u32 = u32::from_le_bytes(*b"GNF ");
+const VERSION: u8 = 2;
+const HEADER_SIZE: usize = 8;
+const CONTENTS_HEADER_SIZE: usize = 8;
+const DESCRIPTOR_SIZE: usize = 32;
+/// log2 of the alignment of the texture data
+const DATA_ALIGNMENT: u8 = 8;
+
+/// `SQ_RSRC_IMG_*` resource types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TextureType {
+    Texture1D = 8,
+    Texture2D = 9,
+    Texture3D = 10,
+    Cube = 11,
+    Array1D = 12,
+    Array2D = 13,
+    Msaa2D = 14,
+    MsaaArray2D = 15,
+}
+
+impl TryFrom<u32> for TextureType {
+    type Error = SwizzleError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            8 => TextureType::Texture1D,
+            9 => TextureType::Texture2D,
+            10 => TextureType::Texture3D,
+            11 => TextureType::Cube,
+            12 => TextureType::Array1D,
+            13 => TextureType::Array2D,
+            14 => TextureType::Msaa2D,
+            15 => TextureType::MsaaArray2D,
+            t => return Err(SwizzleError::InvalidTextureType(t)),
+        })
+    }
+}
+
+/// A T# image resource descriptor, as stored in a GNF file.
+///
+/// GNF files keep the size of the texture data in the last dword, which the hardware ignores.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub struct TextureDescriptor {
+    /// Offset of the texture data from the start of the data section, in bytes
+    pub base_address: u64,
+    /// Minimum LOD, in 4.8 fixed point
+    pub min_lod: u16,
+    pub data_format: GcnSurfaceFormat,
+    /// `BUF_NUM_FORMAT_*`, such as 0 for UNORM, 7 for FLOAT or 9 for SRGB
+    pub num_format: u8,
+    pub width: usize,
+    pub height: usize,
+    /// Depth of a volume, or the number of layers of an array
+    pub depth: usize,
+    /// Pitch of the base level in pixels
+    pub pitch: usize,
+    /// Channel selects of the X, Y, Z and W components: 0 and 1 for constants, 4 to 7 for the
+    /// X to W channels of the texel
+    pub dst_sel: [u8; 4],
+    pub base_level: usize,
+    pub last_level: usize,
+    pub tile_mode: TileMode,
+    /// Pads the base level of compressed textures to a power of two, as
+    /// [`AlignmentPolicy::PowerOfTwo`] does. Uncompressed base levels are never padded, and the
+    /// levels after the first always are, whether or not this is set.
+    pub pow2_pad: bool,
+    pub texture_type: TextureType,
+    pub base_array: usize,
+    pub last_array: usize,
+    /// Size of the texture data in bytes
+    pub size: u32,
+}
+
+impl TextureDescriptor {
+    /// Describes a surface swizzled by [`Ps4`], with identity channel selects and a UNORM numeric
+    /// format
+    pub fn from_desc(desc: &SurfaceDesc<GcnSurfaceFormat>) -> Result<Self, SwizzleError> {
+        let (texture_type, depth) = match desc.kind() {
+            TextureKind::Texture2D => (TextureType::Texture2D, 1),
+            TextureKind::Volume => (TextureType::Texture3D, desc.depth()),
+            TextureKind::Cube => (TextureType::Cube, 1),
+            TextureKind::Array(layers) => (TextureType::Array2D, layers),
+        };
+
+        Ok(TextureDescriptor {
+            base_address: 0,
+            min_lod: 0,
+            data_format: *desc.format(),
+            num_format: 0,
+            width: desc.width(),
+            height: desc.height(),
+            depth,
+            pitch: desc.pitch().unwrap_or(desc.width()),
+            dst_sel: [4, 5, 6, 7],
+            base_level: 0,
+            last_level: desc.mip_count() - 1,
+            tile_mode: desc.tile_mode().unwrap_or(Ps4::DEFAULT_TILE_MODE),
+            pow2_pad: desc.alignment() == AlignmentPolicy::PowerOfTwo,
+            texture_type,
+            base_array: 0,
+            last_array: desc.array_size() - 1,
+            size: Ps4::swizzled_size(desc)? as u32,
+        })
+    }
+
+    pub fn from_words(words: [u32; 8]) -> Result<Self, SwizzleError> {
+        let data_format = GcnSurfaceFormat::try_from(((words[1] >> 20) & 0x3F) as u16)?;
+        Ok(TextureDescriptor {
+            base_address: (words[0] as u64 | ((words[1] as u64 & 0xFF) << 32)) << 8,
+            min_lod: ((words[1] >> 8) & 0xFFF) as u16,
+            data_format,
+            num_format: ((words[1] >> 26) & 0xF) as u8,
+            width: (words[2] & 0x3FFF) as usize + 1,
+            height: ((words[2] >> 14) & 0x3FFF) as usize + 1,
+            depth: (words[4] & 0x1FFF) as usize + 1,
+            pitch: ((words[4] >> 13) & 0x3FFF) as usize + 1,
+            dst_sel: [
+                (words[3] & 0x7) as u8,
+                ((words[3] >> 3) & 0x7) as u8,
+                ((words[3] >> 6) & 0x7) as u8,
+                ((words[3] >> 9) & 0x7) as u8,
+            ],
+            base_level: ((words[3] >> 12) & 0xF) as usize,
+            last_level: ((words[3] >> 16) & 0xF) as usize,
+            tile_mode: TileMode::try_from((words[3] >> 20) & 0x1F)?,
+            pow2_pad: (words[3] >> 25) & 1 != 0,
+            texture_type: TextureType::try_from(words[3] >> 28)?,
+            base_array: (words[5] & 0x1FFF) as usize,
+            last_array: ((words[5] >> 13) & 0x1FFF) as usize,
+            size: words[7],
+        })
+    }
+
+    pub fn to_words(&self) -> [u32; 8] {
+        let base_address = self.base_address >> 8;
+        [
+            base_address as u32,
+            ((base_address >> 32) as u32 & 0xFF)
+                | (self.min_lod as u32 & 0xFFF) << 8
+                | (self.data_format as u32 & 0x3F) << 20
+                | (self.num_format as u32 & 0xF) << 26,
+            ((self.width as u32 - 1) & 0x3FFF) | ((self.height as u32 - 1) & 0x3FFF) << 14,
+            (self.dst_sel[0] as u32 & 0x7)
+                | (self.dst_sel[1] as u32 & 0x7) << 3
+                | (self.dst_sel[2] as u32 & 0x7) << 6
+                | (self.dst_sel[3] as u32 & 0x7) << 9
+                | (self.base_level as u32 & 0xF) << 12
+                | (self.last_level as u32 & 0xF) << 16
+                | (self.tile_mode as u32 & 0x1F) << 20
+                | (self.pow2_pad as u32) << 25
+                | (self.texture_type as u32) << 28,
+            ((self.depth as u32 - 1) & 0x1FFF) | ((self.pitch as u32 - 1) & 0x3FFF) << 13,
+            (self.base_array as u32 & 0x1FFF) | (self.last_array as u32 & 0x1FFF) << 13,
+            0,
+            self.size,
+        ]
+    }
+
+    /// Describes the surface for [`Ps4`].
+    /// Multisampled textures are described as their first sample.
+    pub fn surface_desc(&self) -> Result<SurfaceDesc<GcnSurfaceFormat>, SwizzleError> {
+        let layers = self.last_array.saturating_sub(self.base_array) + 1;
+        let (kind, depth) = match self.texture_type {
+            TextureType::Texture1D | TextureType::Texture2D | TextureType::Msaa2D => {
+                (TextureKind::Texture2D, 1)
+            }
+            TextureType::Texture3D => (TextureKind::Volume, self.depth),
+            TextureType::Cube => (TextureKind::Cube, 1),
+            TextureType::Array1D | TextureType::Array2D | TextureType::MsaaArray2D => {
+                (TextureKind::Array(layers), 1)
+            }
+        };
+
+        SurfaceDesc::builder(self.data_format)
+            .width(self.width)
+            .height(self.height)
+            .kind(kind)
+            .depth(depth)
+            .mip_count(self.last_level.saturating_sub(self.base_level) + 1)
+            .pitch(self.pitch)
+            .tile_mode(self.tile_mode)
+            .alignment(if self.pow2_pad {
+                AlignmentPolicy::PowerOfTwo
+            } else {
+                AlignmentPolicy::Exact
+            })
+            .build()
+    }
+}
+
+/// A GNF file, borrowing its data from the file
+#[derive(Debug, Clone, Copy)]
+pub struct Gnf<'a> {
+    descriptors: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Gnf<'a> {
+    /// Parses the headers of a GNF file. Descriptors are only parsed by [`Gnf::texture`].
+    pub fn read(file: &'a [u8]) -> Result<Self, SwizzleError> {
+        let magic = read_u32(file, 0)?;
+        if magic != MAGIC {
+            return Err(SwizzleError::InvalidMagic(magic));
+        }
+        let contents_size = read_u32(file, 4)? as usize;
+
+        let contents = slice(file, HEADER_SIZE, CONTENTS_HEADER_SIZE)?;
+        if contents[0] != VERSION {
+            return Err(SwizzleError::UnsupportedVersion(contents[0] as u32));
+        }
+        let texture_count = contents[1] as usize;
+
+        let data_offset = HEADER_SIZE + contents_size;
+        Ok(Gnf {
+            descriptors: slice(
+                file,
+                HEADER_SIZE + CONTENTS_HEADER_SIZE,
+                texture_count * DESCRIPTOR_SIZE,
+            )?,
+            data: file
+                .get(data_offset..)
+                .ok_or(SwizzleError::OutOfBounds(TextureSlice::Source))?,
+        })
+    }
+
+    /// Number of textures in the file
+    pub fn len(&self) -> usize {
+        self.descriptors.len() / DESCRIPTOR_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn texture(&self, index: usize) -> Result<GnfTexture<'a>, SwizzleError> {
+        let words = slice(self.descriptors, index * DESCRIPTOR_SIZE, DESCRIPTOR_SIZE)?;
+        let mut descriptor_words = [0; 8];
+        for (i, word) in descriptor_words.iter_mut().enumerate() {
+            *word = read_u32(words, i * 4)?;
+        }
+
+        let descriptor = TextureDescriptor::from_words(descriptor_words)?;
+        let data = slice(
+            self.data,
+            descriptor.base_address as usize,
+            descriptor.size as usize,
+        )?;
+        Ok(GnfTexture { descriptor, data })
+    }
+}
+
+/// A texture of a GNF file
+#[derive(Debug, Clone, Copy)]
+pub struct GnfTexture<'a> {
+    descriptor: TextureDescriptor,
+    data: &'a [u8],
+}
+
+impl<'a> GnfTexture<'a> {
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+
+    /// The swizzled data of every layer and level
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Size in bytes of the headers of a file holding `texture_count` textures
+pub fn header_size(texture_count: usize) -> usize {
+    (HEADER_SIZE + CONTENTS_HEADER_SIZE + texture_count * DESCRIPTOR_SIZE)
+        .next_multiple_of(1 << DATA_ALIGNMENT)
+}
+
+/// Size in bytes of a file holding the given textures
+pub fn file_size(textures: &[(TextureDescriptor, &[u8])]) -> usize {
+    header_size(textures.len())
+        + textures
+            .iter()
+            .map(|(_, data)| data.len().next_multiple_of(1 << DATA_ALIGNMENT))
+            .sum::<usize>()
+}
+
+/// Writes a GNF file holding the swizzled `data` of each texture, and returns the number of bytes
+/// written. The base address and size of each descriptor are set from where its data is placed.
+pub fn write(
+    dest: &mut [u8],
+    textures: &[(TextureDescriptor, &[u8])],
+) -> Result<usize, SwizzleError> {
+    let header_size = header_size(textures.len());
+    let file_size = file_size(textures);
+    if textures.len() > u8::MAX as usize {
+        return Err(SwizzleError::InvalidArraySize(textures.len()));
+    }
+    let file = dest
+        .get_mut(..file_size)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+    file.fill(0);
+
+    write_u32(file, 0, MAGIC);
+    write_u32(file, 4, (header_size - HEADER_SIZE) as u32);
+    file[8] = VERSION;
+    file[9] = textures.len() as u8;
+    file[10] = DATA_ALIGNMENT;
+    write_u32(file, 12, file_size as u32);
+
+    let mut data_offset = 0;
+    for (i, (descriptor, data)) in textures.iter().enumerate() {
+        let descriptor = TextureDescriptor {
+            base_address: data_offset as u64,
+            size: data.len() as u32,
+            ..*descriptor
+        };
+        let descriptor_offset = HEADER_SIZE + CONTENTS_HEADER_SIZE + i * DESCRIPTOR_SIZE;
+        for (j, word) in descriptor.to_words().into_iter().enumerate() {
+            write_u32(file, descriptor_offset + j * 4, word);
+        }
+
+        let offset = header_size + data_offset;
+        file[offset..offset + data.len()].copy_from_slice(data);
+        data_offset += data.len().next_multiple_of(1 << DATA_ALIGNMENT);
+    }
+
+    Ok(file_size)
+}
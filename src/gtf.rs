@@ -0,0 +1,360 @@
+//! Reading and writing PS3 GTF texture files.
+//!
+//! A GTF file holds one or more textures, each described by a `CellGtfTextureAttribute` and its
+//! `CellGcmTexture`, followed by their data. All fields are big-endian.
+//!
+//! Only textures without the LN flag in their format go through [`Ps3`], compressed ones included.
+//! Linear textures are copied row by row instead.
+//!
+//! ```
+//! # use swizzleinator::{format::GcmSurfaceFormat, gtf::{self, GcmTexture, Gtf}, swizzle::{SurfaceDesc, TextureLayout, ps::Ps3}};
+//! let desc = SurfaceDesc::builder(GcmSurfaceFormat::A8R8G8B8)
+//!     .width(64)
+//!     .height(64)
+//!     .mip_count(7)
+//!     .build()
+//!     .unwrap();
+//! let swizzled = vec![0u8; Ps3::swizzled_size(&desc).unwrap()];
+//! let texture = GcmTexture::from_desc(&desc, false).unwrap();
+//!
+//! let mut file = vec![0u8; gtf::file_size(&[(texture, &swizzled)])];
+//! gtf::write(&mut file, &[(texture, &swizzled)]).unwrap();
+//!
+//! let gtf = Gtf::read(&file).unwrap();
+//! let texture = gtf.texture(0).unwrap();
+//! let mut linear = vec![0u8; Ps3::linear_size(&desc).unwrap()];
+//! texture.deswizzle(&mut linear).unwrap();
+//! ```
+//!
+//! [`Ps3`]: crate::swizzle::ps::Ps3
+
+use crate::{
+    bytes::{read_u16_be, read_u32_be, slice, write_u16_be, write_u32_be},
+    format::GcmSurfaceFormat,
+    swizzle::{
+        Deswizzler, Format, MipChain, SurfaceDesc, SwizzleError, TextureKind, TextureLayout,
+        TextureSlice,
+        ps::{GcmRemap, Ps3},
+    },
+};
+
+const VERSION: u32 = 0x0202_0000;
+const HEADER_SIZE: usize = 12;
+const ATTRIBUTE_SIZE: usize = 36;
+const DATA_ALIGNMENT: usize = 128;
+
+/// `CELL_GCM_TEXTURE_LN`, set in the format byte of linear textures
+const FORMAT_LINEAR: u8 = 0x20;
+/// `CELL_GCM_TEXTURE_UN`, set in the format byte of textures sampled with unnormalized
+/// coordinates
+const FORMAT_UNNORMALIZED: u8 = 0x40;
+
+/// Identity `CELL_GCM_TEXTURE_REMAP_*` value
+const DEFAULT_REMAP: u32 = 0xAAE4;
+
+/// `CellGcmTexture`, the texture state of the RSX
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub struct GcmTexture {
+    pub format: GcmSurfaceFormat,
+    /// Whether the texture is stored linearly (`CELL_GCM_TEXTURE_LN`) rather than swizzled
+    pub linear: bool,
+    /// Whether the texture is sampled with unnormalized coordinates (`CELL_GCM_TEXTURE_UN`)
+    pub unnormalized: bool,
+    pub mipmap: u8,
+    /// 1, 2 or 3
+    pub dimension: u8,
+    pub cubemap: bool,
+    /// Channel remapping (`CELL_GCM_TEXTURE_REMAP_*`)
+    pub remap: u32,
+    pub width: u16,
+    pub height: u16,
+    pub depth: u16,
+    pub location: u8,
+    /// Distance in bytes between rows of a linear texture, 0 for swizzled textures
+    pub pitch: u32,
+    /// Offset of the data in the texture's memory location
+    pub offset: u32,
+}
+
+impl GcmTexture {
+    const SIZE: usize = 24;
+
+    /// Describes a surface with the identity remap. Arrays other than cubemaps can't be described
+    /// by a GCM texture.
+    /// * `linear` - Whether the data is laid out linearly instead of swizzled by [`Ps3`].
+    pub fn from_desc(
+        desc: &SurfaceDesc<GcmSurfaceFormat>,
+        linear: bool,
+    ) -> Result<Self, SwizzleError> {
+        let dimension = match desc.kind() {
+            TextureKind::Volume => 3,
+            TextureKind::Texture2D | TextureKind::Cube => 2,
+            TextureKind::Array(_) => return Err(SwizzleError::InvalidTextureType(2)),
+        };
+        let format = desc.format();
+        let pitch = if linear || format.is_compressed() {
+            desc.width().div_ceil(format.pixel_block_size()) * format.block_size()?
+        } else {
+            0
+        };
+
+        Ok(GcmTexture {
+            format: *format,
+            linear,
+            unnormalized: false,
+            mipmap: desc.mip_count() as u8,
+            dimension,
+            cubemap: desc.kind() == TextureKind::Cube,
+            remap: DEFAULT_REMAP,
+            width: desc.width() as u16,
+            height: desc.height() as u16,
+            depth: desc.depth() as u16,
+            location: 0,
+            pitch: pitch as u32,
+            offset: 0,
+        })
+    }
+
+    pub fn read(data: &[u8]) -> Result<Self, SwizzleError> {
+        let data = slice(data, 0, Self::SIZE)?;
+        let format = data[0];
+        Ok(GcmTexture {
+            format: GcmSurfaceFormat::try_from(format & !(FORMAT_LINEAR | FORMAT_UNNORMALIZED))?,
+            linear: format & FORMAT_LINEAR != 0,
+            unnormalized: format & FORMAT_UNNORMALIZED != 0,
+            mipmap: data[1],
+            dimension: data[2],
+            cubemap: data[3] != 0,
+            remap: read_u32_be(data, 4)?,
+            width: read_u16_be(data, 8)?,
+            height: read_u16_be(data, 10)?,
+            depth: read_u16_be(data, 12)?,
+            location: data[14],
+            pitch: read_u32_be(data, 16)?,
+            offset: read_u32_be(data, 20)?,
+        })
+    }
+
+    pub fn write(&self, data: &mut [u8]) {
+        data[0] = self.format_byte();
+        data[1] = self.mipmap;
+        data[2] = self.dimension;
+        data[3] = self.cubemap as u8;
+        write_u32_be(data, 4, self.remap);
+        write_u16_be(data, 8, self.width);
+        write_u16_be(data, 10, self.height);
+        write_u16_be(data, 12, self.depth);
+        data[14] = self.location;
+        data[15] = 0;
+        write_u32_be(data, 16, self.pitch);
+        write_u32_be(data, 20, self.offset);
+    }
+
+    /// The format with its LN and UN flags, as stored in the texture state
+    pub fn format_byte(&self) -> u8 {
+        let mut format = self.format as u8;
+        if self.linear {
+            format |= FORMAT_LINEAR;
+        }
+        if self.unnormalized {
+            format |= FORMAT_UNNORMALIZED;
+        }
+        format
+    }
+
+    /// Whether the data is swizzled, which is decided by the LN flag alone
+    pub fn is_swizzled(&self) -> bool {
+        !self.linear
+    }
+
+    pub fn surface_desc(&self) -> Result<SurfaceDesc<GcmSurfaceFormat>, SwizzleError> {
+        let (kind, depth) = match (self.dimension, self.cubemap) {
+            (3, _) => (TextureKind::Volume, self.depth as usize),
+            (_, true) => (TextureKind::Cube, 1),
+            (1 | 2, false) => (TextureKind::Texture2D, 1),
+            (d, _) => return Err(SwizzleError::InvalidTextureType(d as u32)),
+        };
+
+        SurfaceDesc::builder(self.format)
+            .width(self.width as usize)
+            .height(self.height as usize)
+            .kind(kind)
+            .depth(depth)
+            .mip_count((self.mipmap as usize).max(1))
+            .build()
+    }
+}
+
+/// A GTF file, borrowing its data from the file
+#[derive(Debug, Clone, Copy)]
+pub struct Gtf<'a> {
+    file: &'a [u8],
+    version: u32,
+    texture_count: usize,
+}
+
+impl<'a> Gtf<'a> {
+    /// Parses the header of a GTF file. Textures are only parsed by [`Gtf::texture`].
+    pub fn read(file: &'a [u8]) -> Result<Self, SwizzleError> {
+        let version = read_u32_be(file, 0)?;
+        let texture_count = read_u32_be(file, 8)? as usize;
+        slice(file, HEADER_SIZE, texture_count * ATTRIBUTE_SIZE)?;
+
+        Ok(Gtf {
+            file,
+            version,
+            texture_count,
+        })
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Number of textures in the file
+    pub fn len(&self) -> usize {
+        self.texture_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texture_count == 0
+    }
+
+    pub fn texture(&self, index: usize) -> Result<GtfTexture<'a>, SwizzleError> {
+        if index >= self.texture_count {
+            return Err(SwizzleError::OutOfBounds(TextureSlice::Source));
+        }
+        let attribute = slice(
+            self.file,
+            HEADER_SIZE + index * ATTRIBUTE_SIZE,
+            ATTRIBUTE_SIZE,
+        )?;
+        let id = read_u32_be(attribute, 0)?;
+        let offset = read_u32_be(attribute, 4)? as usize;
+        let size = read_u32_be(attribute, 8)? as usize;
+
+        Ok(GtfTexture {
+            id,
+            texture: GcmTexture::read(&attribute[12..])?,
+            data: slice(self.file, offset, size)?,
+        })
+    }
+}
+
+/// A texture of a GTF file
+#[derive(Debug, Clone, Copy)]
+pub struct GtfTexture<'a> {
+    id: u32,
+    texture: GcmTexture,
+    data: &'a [u8],
+}
+
+impl<'a> GtfTexture<'a> {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn texture(&self) -> &GcmTexture {
+        &self.texture
+    }
+
+    /// The data of every layer and level, as stored in the file
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Copies the texture to `dest` in the linear layout of [`Ps3`], deswizzling it only if it is
+    /// swizzled, and removing the row padding of linear textures.
+    /// The `remap` of the texture is then applied with [`GcmRemap::apply`].
+    pub fn deswizzle(&self, dest: &mut [u8]) -> Result<(), SwizzleError> {
+        let desc = self.texture.surface_desc()?;
+        let format = desc.format();
+        let chain = Ps3::mip_chain(&desc)?;
+        let dest = dest
+            .get_mut(..chain.linear_size())
+            .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+
+        if self.texture.is_swizzled() {
+            Ps3::deswizzle_surface(self.data, dest, &desc)?;
+        } else {
+            self.copy_linear(&desc, &chain, dest)?;
+        }
+
+        GcmRemap::from_bits(self.texture.remap).apply(dest, *format);
+        Ok(())
+    }
+
+    /// Copies the rows of a linear texture, skipping their padding
+    fn copy_linear(
+        &self,
+        desc: &SurfaceDesc<GcmSurfaceFormat>,
+        chain: &MipChain,
+        dest: &mut [u8],
+    ) -> Result<(), SwizzleError> {
+        let format = desc.format();
+
+        let mut source_offset = 0usize;
+        for layer in 0..chain.layers() {
+            source_offset = source_offset.next_multiple_of(DATA_ALIGNMENT);
+            for level in chain.iter() {
+                let (width, height, depth) = level.dimensions;
+                let row_size = width.div_ceil(format.pixel_block_size()) * format.block_size()?;
+                let rows = height.div_ceil(format.pixel_block_size()) * depth;
+                let pitch = (self.texture.pitch as usize).max(row_size);
+
+                let dest_offset = layer * chain.linear_layer_stride() + level.linear_offset;
+                for row in 0..rows {
+                    let source = slice(self.data, source_offset + row * pitch, row_size)?;
+                    let offset = dest_offset + row * row_size;
+                    dest[offset..offset + row_size].copy_from_slice(source);
+                }
+                source_offset += rows * pitch;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn header_size(texture_count: usize) -> usize {
+    (HEADER_SIZE + texture_count * ATTRIBUTE_SIZE).next_multiple_of(DATA_ALIGNMENT)
+}
+
+/// Size in bytes of a file holding the given textures
+pub fn file_size(textures: &[(GcmTexture, &[u8])]) -> usize {
+    header_size(textures.len())
+        + textures
+            .iter()
+            .map(|(_, data)| data.len().next_multiple_of(DATA_ALIGNMENT))
+            .sum::<usize>()
+}
+
+/// Writes a GTF file holding the `data` of each texture, as laid out by [`Ps3`] or linearly
+/// depending on the texture, and returns the number of bytes written. Textures are numbered in
+/// order.
+pub fn write(dest: &mut [u8], textures: &[(GcmTexture, &[u8])]) -> Result<usize, SwizzleError> {
+    let header_size = header_size(textures.len());
+    let file_size = file_size(textures);
+    let file = dest
+        .get_mut(..file_size)
+        .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+    file.fill(0);
+
+    write_u32_be(file, 0, VERSION);
+    write_u32_be(file, 4, (file_size - header_size) as u32);
+    write_u32_be(file, 8, textures.len() as u32);
+
+    let mut data_offset = header_size;
+    for (i, (texture, data)) in textures.iter().enumerate() {
+        let attribute = HEADER_SIZE + i * ATTRIBUTE_SIZE;
+        write_u32_be(file, attribute, i as u32);
+        write_u32_be(file, attribute + 4, data_offset as u32);
+        write_u32_be(file, attribute + 8, data.len() as u32);
+        texture.write(&mut file[attribute + 12..attribute + ATTRIBUTE_SIZE]);
+
+        file[data_offset..data_offset + data.len()].copy_from_slice(data);
+        data_offset += data.len().next_multiple_of(DATA_ALIGNMENT);
+    }
+
+    Ok(file_size)
+}
@@ -1,7 +1,14 @@
 #![doc = include_str!("../README.md")]
 
+mod bytes;
+pub mod dds;
+#[cfg(feature = "decode")]
+pub mod decode;
 pub mod format;
+pub mod gnf;
+pub mod gtf;
 pub mod swizzle;
+pub mod xpr;
 
 #[cfg(test)]
 mod tests {
@@ -30,18 +37,27 @@ mod tests {
                 #[test]
                 #[allow(non_snake_case)]
                 fn [<$test_name _ $operation _ $width _ $height _ $depth _ $image_format>]() {
-                    use $crate::{swizzle::{Swizzler, Deswizzler, Format}};
+                    use $crate::swizzle::{Swizzler, Deswizzler, SurfaceDesc, TextureKind, TextureLayout};
 
                     let swizzled_data = &mut include_bytes!(concat!("../testdata/", concat!($file_path, ".bin"))).to_vec();
                     let unswizzled_data = &mut include_bytes!(concat!("../testdata/", concat!($file_path, "-unswizzled.bin"))).to_vec();
 
-                    let mut dest = vec![0u8; ($width * $height * $depth * $image_format.bpp()) / 8];
+                    let desc = SurfaceDesc::from_tuple(
+                        ($width, $height, $depth),
+                        $image_format,
+                        TextureKind::Texture2D,
+                        1,
+                        $align_resolution,
+                    )
+                    .unwrap();
+
+                    let mut dest = match $operation {
+                        Swizzle => vec![0u8; <$swizzle_type as TextureLayout>::swizzled_size(&desc).unwrap()],
+                        Deswizzle => vec![0u8; <$swizzle_type as TextureLayout>::linear_size(&desc).unwrap()],
+                    };
 
                     let result = match $operation {
                         Swizzle => {
-                            if !$width.is_power_of_two() || !$height.is_power_of_two() {
-                                dest = vec![0u8;(dest.len() as f32*1.25).floor() as usize];
-                            }
                             <$swizzle_type as Swizzler>::swizzle(
                                 unswizzled_data,
                                 &mut dest,
@@ -62,14 +78,19 @@ mod tests {
                     };
 
                     assert!(
-                        result.is_err(),
+                        result.is_ok(),
                         "{} operation failed with error: {:?}",
                         stringify!($operation),
                         result.err()
                     );
 
                     match $operation {
-                        Swizzle => assert!(*swizzled_data == dest, "Swizzled data did not match reference"),
+                        // Some references were saved with trailing zeroes past the swizzled surface
+                        Swizzle => {
+                            assert!(dest.len() <= swizzled_data.len(), "Swizzled size is larger than the reference");
+                            assert!(swizzled_data[..dest.len()] == dest, "Swizzled data did not match reference");
+                            assert!(swizzled_data[dest.len()..].iter().all(|&b| b == 0), "Swizzled size is smaller than the reference");
+                        }
                         Deswizzle => assert!(*unswizzled_data == dest, "Deswizzled data did not match reference")
                     }
                 }
@@ -152,6 +173,64 @@ mod tests {
         false
     );
 
+    #[test]
+    fn ps4_tile_mode_1d_thin_matches_reference() {
+        use crate::swizzle::{gcn::TileMode, ps::Ps4};
+
+        let swizzled = &mut include_bytes!("../testdata/ps4-bc5-512x512.bin").to_vec();
+        let unswizzled = include_bytes!("../testdata/ps4-bc5-512x512-unswizzled.bin");
+        let mut dest = vec![0u8; unswizzled.len()];
+
+        Ps4::deswizzle_with_tile_mode(
+            swizzled,
+            &mut dest,
+            (512, 512, 1),
+            BC5,
+            TileMode::Thin_1dThin,
+            Ps4::PIPE_CONFIG,
+        )
+        .unwrap();
+        assert!(
+            dest == unswizzled,
+            "Deswizzled data did not match reference"
+        );
+
+        let mut reswizzled = vec![0u8; swizzled.len()];
+        Ps4::swizzle_with_tile_mode(
+            &dest,
+            &mut reswizzled,
+            (512, 512, 1),
+            BC5,
+            TileMode::Thin_1dThin,
+            Ps4::PIPE_CONFIG,
+        )
+        .unwrap();
+        assert!(
+            *swizzled == reswizzled,
+            "Swizzled data did not match reference"
+        );
+    }
+
+    #[test]
+    fn ps4_2d_thin_is_pipe_bank_swizzled() {
+        use crate::swizzle::gcn::{PipeConfig, SurfaceLayout, TileMode};
+
+        let layout = SurfaceLayout::new(
+            (256, 256, 1),
+            4,
+            TileMode::Thin_2dThin,
+            PipeConfig::P8_32x32_16x16,
+        );
+
+        // Within a micro tile elements stay in Morton order, neighbouring micro tiles are spread
+        // across pipes (bits 8..11) and banks (bits 11..15). This only pins down the current
+        // output, there is no 2D thin reference dump to check it against.
+        assert_eq!(layout.element_offset(1, 1, 0), 12);
+        assert_eq!(layout.element_offset(8, 0, 0), 0x100);
+        assert_eq!(layout.element_offset(0, 8, 0), 0x4100);
+        assert_eq!(layout.element_offset(8, 8, 0), 0x4000);
+    }
+
     // PS3 64 x 64 RGBA8
 
     test_impl!(
@@ -201,4 +280,2163 @@ mod tests {
         COMPRESSED_DXT45,
         true
     );
+
+    // Switch
+
+    fn round_trip<S: crate::swizzle::Swizzler + crate::swizzle::Deswizzler>(
+        dimensions: (usize, usize, usize),
+        format: impl crate::swizzle::Format + Copy,
+        swizzled_len: usize,
+    ) {
+        let (width, height, depth) = dimensions;
+        let linear: Vec<u8> = (0..(width * height * depth * format.bpp().unwrap()) / 8)
+            .map(|i| (i * 7 + i / 251) as u8)
+            .collect();
+        let mut swizzled = vec![0u8; swizzled_len];
+        let mut result = vec![0u8; linear.len()];
+
+        S::swizzle(&linear, &mut swizzled, dimensions, format, false).unwrap();
+        assert_ne!(
+            swizzled[..linear.len()],
+            linear[..],
+            "swizzling was a no-op"
+        );
+        S::deswizzle(&swizzled, &mut result, dimensions, format, false).unwrap();
+        assert!(
+            result == linear,
+            "Round trip did not match the original data"
+        );
+    }
+
+    #[test]
+    fn switch_gob_address() {
+        use crate::swizzle::switch::tegra::gob_address;
+
+        assert_eq!(gob_address(0, 0, 0, 1, 1, 1), 0);
+        assert_eq!(gob_address(0, 1, 0, 1, 1, 1), 16);
+        assert_eq!(gob_address(16, 0, 0, 1, 1, 1), 32);
+        assert_eq!(gob_address(0, 2, 0, 1, 1, 1), 64);
+        assert_eq!(gob_address(32, 0, 0, 1, 1, 1), 256);
+        assert_eq!(gob_address(64, 0, 0, 2, 1, 1), 512);
+        assert_eq!(gob_address(0, 8, 0, 2, 2, 1), 512);
+        assert_eq!(gob_address(64, 0, 0, 2, 2, 1), 1024);
+        assert_eq!(gob_address(0, 0, 1, 1, 1, 2), 512);
+        assert_eq!(gob_address(0, 8, 1, 1, 2, 2), 1536);
+        assert_eq!(gob_address(64, 0, 0, 2, 1, 2), 1024);
+    }
+
+    #[test]
+    fn switch_round_trip_rgba8() {
+        round_trip::<crate::swizzle::switch::Switch>((256, 256, 1), Format8_8_8_8, 256 * 256 * 4);
+    }
+
+    #[test]
+    fn switch_round_trip_bc7_npot() {
+        round_trip::<crate::swizzle::switch::Switch>((100, 60, 1), BC7, 32 * 1024);
+    }
+
+    #[test]
+    fn switch_block_height() {
+        use crate::swizzle::switch::Switch;
+
+        assert_eq!(Switch::block_height_mip0(8), 1);
+        assert_eq!(Switch::block_height_mip0(128), 2);
+        assert_eq!(Switch::block_height_mip0(1024), 16);
+
+        let source = vec![0u8; 64 * 64 * 4];
+        let mut dest = vec![0u8; 64 * 64 * 4];
+        let result = Switch::deswizzle_with_block_height(
+            &source,
+            &mut dest,
+            (64, 64, 1),
+            Format8_8_8_8,
+            false,
+            3,
+        );
+        assert!(result.is_err(), "Block height of 3 GOBs should be rejected");
+    }
+
+    // Block-linear references from tegra_swizzle, see testdata/README.md
+    fn switch_reference(
+        swizzled: &[u8],
+        linear: &[u8],
+        dimensions: (usize, usize, usize),
+        format: impl crate::swizzle::Format + Copy,
+        block_height: usize,
+    ) {
+        use crate::swizzle::switch::Switch;
+
+        let mut dest = vec![0u8; linear.len()];
+        Switch::deswizzle_with_block_height(
+            swizzled,
+            &mut dest,
+            dimensions,
+            format,
+            false,
+            block_height,
+        )
+        .unwrap();
+        assert!(dest == linear, "Deswizzled data did not match reference");
+
+        let mut dest = vec![0u8; swizzled.len()];
+        Switch::swizzle_with_block_height(
+            linear,
+            &mut dest,
+            dimensions,
+            format,
+            false,
+            block_height,
+        )
+        .unwrap();
+        assert!(dest == swizzled, "Swizzled data did not match reference");
+    }
+
+    #[test]
+    fn switch_reference_2d() {
+        switch_reference(
+            include_bytes!("../testdata/switch-bc7-64x64.bin"),
+            include_bytes!("../testdata/switch-bc7-64x64-unswizzled.bin"),
+            (64, 64, 1),
+            BC7,
+            2,
+        );
+        switch_reference(
+            include_bytes!("../testdata/switch-bc1-128x128.bin"),
+            include_bytes!("../testdata/switch-bc1-128x128-unswizzled.bin"),
+            (128, 128, 1),
+            BC1,
+            4,
+        );
+    }
+
+    #[test]
+    fn switch_reference_volume() {
+        use crate::swizzle::{
+            Deswizzler, SurfaceDesc, Swizzler, TextureKind, TextureLayout, switch::Switch,
+        };
+
+        switch_reference(
+            include_bytes!("../testdata/switch-rgba8-16x16x16.bin"),
+            include_bytes!("../testdata/switch-rgba8-16x16x16-unswizzled.bin"),
+            (16, 16, 16),
+            Format8_8_8_8,
+            1,
+        );
+
+        // 33 slices are padded out to a whole block of 16 slices
+        let swizzled = include_bytes!("../testdata/switch-rgba8-33x33x33.bin");
+        let linear = include_bytes!("../testdata/switch-rgba8-33x33x33-unswizzled.bin");
+        let desc = SurfaceDesc::builder(Format8_8_8_8)
+            .width(33)
+            .height(33)
+            .depth(33)
+            .kind(TextureKind::Volume)
+            .build()
+            .unwrap();
+        assert_eq!(Switch::swizzled_size(&desc).unwrap(), swizzled.len());
+
+        let mut dest = vec![0u8; linear.len()];
+        Switch::deswizzle_surface(swizzled, &mut dest, &desc).unwrap();
+        assert!(
+            dest[..] == linear[..],
+            "Deswizzled volume did not match reference"
+        );
+
+        let mut dest = vec![0u8; swizzled.len()];
+        Switch::swizzle_surface(linear, &mut dest, &desc).unwrap();
+        assert!(
+            dest[..] == swizzled[..],
+            "Swizzled volume did not match reference"
+        );
+    }
+
+    // Xbox One
+
+    #[test]
+    fn xbox_one_1d_thin_matches_ps4_micro_tiles() {
+        use crate::swizzle::{Deswizzler, gcn::TileMode, xbox::XboxOne};
+
+        let swizzled = &mut include_bytes!("../testdata/ps4-bc7-900x1080.bin").to_vec();
+        let unswizzled = include_bytes!("../testdata/ps4-bc7-900x1080-unswizzled.bin");
+        let mut dest = vec![0u8; unswizzled.len()];
+
+        XboxOne::deswizzle_with_tile_mode(
+            swizzled,
+            &mut dest,
+            (900, 1080, 1),
+            BC7,
+            TileMode::Thin_1dThin,
+        )
+        .unwrap();
+        assert!(
+            dest == unswizzled,
+            "Deswizzled data did not match reference"
+        );
+
+        // Without a tile mode, the tuple API falls back to the same verified mode
+        let mut dest = vec![0u8; unswizzled.len()];
+        XboxOne::deswizzle(swizzled, &mut dest, (900, 1080, 1), BC7, false).unwrap();
+        assert!(
+            dest == unswizzled,
+            "Deswizzled data did not match reference"
+        );
+    }
+
+    #[test]
+    fn xbox_one_round_trip_tile_modes() {
+        use crate::swizzle::{
+            Format,
+            gcn::{SurfaceLayout, TileMode},
+            xbox::XboxOne,
+        };
+
+        for (tile_mode, dimensions) in [
+            (TileMode::Display_LinearAligned, (200, 120, 1)),
+            (TileMode::Display_2dThin, (512, 512, 1)),
+            (TileMode::Thin_2dThin, (640, 360, 1)),
+            (TileMode::Thin_ThinPrt, (256, 256, 1)),
+            (TileMode::Depth_2dThin_256, (256, 256, 1)),
+            (TileMode::Thick_2dThick, (256, 256, 8)),
+        ] {
+            let (width, height, depth) = dimensions;
+            let linear: Vec<u8> = (0..width * height * depth * 4)
+                .map(|i| (i * 7 + i / 251) as u8)
+                .collect();
+            let layout = SurfaceLayout::new(
+                dimensions,
+                Format8_8_8_8.block_size().unwrap(),
+                tile_mode,
+                XboxOne::PIPE_CONFIG,
+            );
+            let mut swizzled = vec![0u8; layout.size()];
+            let mut result = vec![0u8; linear.len()];
+
+            XboxOne::swizzle_with_tile_mode(
+                &linear,
+                &mut swizzled,
+                dimensions,
+                Format8_8_8_8,
+                tile_mode,
+            )
+            .unwrap();
+            XboxOne::deswizzle_with_tile_mode(
+                &swizzled,
+                &mut result,
+                dimensions,
+                Format8_8_8_8,
+                tile_mode,
+            )
+            .unwrap();
+            assert!(result == linear, "{tile_mode:?} round trip did not match");
+        }
+    }
+
+    // Mipmaps
+
+    fn round_trip_mips<S: crate::swizzle::Swizzler + crate::swizzle::Deswizzler>(
+        dimensions: (usize, usize, usize),
+        format: impl crate::swizzle::Format + Copy,
+        mip_count: usize,
+    ) -> crate::swizzle::MipChain {
+        use crate::swizzle::TextureKind;
+
+        let kind = if dimensions.2 > 1 {
+            TextureKind::Volume
+        } else {
+            TextureKind::Texture2D
+        };
+        round_trip_texture::<S>(dimensions, format, kind, mip_count)
+    }
+
+    fn round_trip_texture<S: crate::swizzle::Swizzler + crate::swizzle::Deswizzler>(
+        dimensions: (usize, usize, usize),
+        format: impl crate::swizzle::Format + Copy,
+        kind: crate::swizzle::TextureKind,
+        mip_count: usize,
+    ) -> crate::swizzle::MipChain {
+        let (width, height, depth) = dimensions;
+        let linear_len = (width * height * depth * format.bpp().unwrap()) / 8 * 2 * kind.layers();
+        let linear: Vec<u8> = (0..linear_len).map(|i| (i * 7 + i / 251) as u8).collect();
+        let desc =
+            crate::swizzle::SurfaceDesc::from_tuple(dimensions, format, kind, mip_count, false)
+                .unwrap();
+        let mut swizzled = vec![0u8; S::swizzled_size(&desc).unwrap()];
+
+        let chain = S::swizzle_texture(
+            &linear,
+            &mut swizzled,
+            dimensions,
+            format,
+            kind,
+            mip_count,
+            false,
+        )
+        .unwrap();
+        assert_eq!(chain.len(), mip_count);
+        assert_eq!(chain.layers(), kind.layers());
+        assert_eq!(chain.swizzled_size(), swizzled.len());
+
+        let mut result = vec![0u8; chain.linear_size()];
+        let chain2 = S::deswizzle_texture(
+            &swizzled,
+            &mut result,
+            dimensions,
+            format,
+            kind,
+            mip_count,
+            false,
+        )
+        .unwrap();
+        assert_eq!(chain, chain2);
+        assert!(
+            result == linear[..chain.linear_size()],
+            "Texture round trip did not match the original data"
+        );
+        chain
+    }
+
+    #[test]
+    fn ps3_mip_chain_is_packed() {
+        let chain = round_trip_mips::<crate::swizzle::ps::Ps3>((64, 64, 1), A8R8G8B8, 7);
+        let offsets: Vec<usize> = chain.iter().map(|l| l.linear_offset).collect();
+        assert_eq!(offsets, [0, 16384, 20480, 21504, 21760, 21824, 21840]);
+        assert_eq!(chain.linear_size(), 21844);
+        assert_eq!(chain.swizzled_size(), 21844);
+        assert_eq!(chain[6].dimensions, (1, 1, 1));
+    }
+
+    #[test]
+    fn ps3_mip_chain_dxt_min_block() {
+        let chain = round_trip_mips::<crate::swizzle::ps::Ps3>((16, 16, 1), COMPRESSED_DXT45, 5);
+        let sizes: Vec<usize> = chain.iter().map(|l| l.linear_size).collect();
+        assert_eq!(sizes, [256, 64, 16, 16, 16]);
+    }
+
+    #[test]
+    fn ps4_mip_chain_round_trip() {
+        let chain = round_trip_mips::<crate::swizzle::ps::Ps4>((171, 171, 1), Format8_8_8_8, 4);
+        assert_eq!(chain[1].dimensions, (85, 85, 1));
+        assert_eq!(chain[1].padded_dimensions, (128, 128, 1));
+        assert!(chain.iter().all(|l| l.swizzled_offset % 256 == 0));
+    }
+
+    #[test]
+    fn ps4_mip_chain_base_level_matches_reference() {
+        use crate::swizzle::{Deswizzler, ps::Ps4};
+
+        let swizzled = &mut include_bytes!("../testdata/ps4-bc5-512x512.bin").to_vec();
+        let unswizzled = include_bytes!("../testdata/ps4-bc5-512x512-unswizzled.bin");
+        let mut dest = vec![0u8; unswizzled.len()];
+
+        Ps4::deswizzle_mips(swizzled, &mut dest, (512, 512, 1), BC5, 1, false).unwrap();
+        assert!(
+            dest == unswizzled,
+            "Deswizzled data did not match reference"
+        );
+    }
+
+    #[test]
+    fn xbox360_mip_chain_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+
+        let chain = round_trip_mips::<crate::swizzle::xbox::Xbox360>(
+            (200, 100, 1),
+            XenosSurfaceFormat::k_8_8_8_8,
+            6,
+        );
+        assert!(chain.iter().all(|l| l.swizzled_offset % 4096 == 0));
+        assert_eq!(chain[1].padded_dimensions, (128, 64, 1));
+    }
+
+    #[test]
+    fn xbox360_packed_mip_tail_offsets() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{Deswizzler, xbox::Xbox360};
+
+        let format = XenosSurfaceFormat::k_8_8_8_8;
+        let source = vec![0u8; 0x100000];
+        let mut dest = vec![0u8; 0x100000];
+        let chain =
+            Xbox360::deswizzle_mips(&source, &mut dest, (256, 256, 1), format, 9, false).unwrap();
+
+        // 16x16 and smaller levels all live in the tile allocated for the 16x16 level
+        let tail = chain[4].swizzled_offset;
+        assert!(chain[..4].iter().all(|l| l.swizzled_offset < tail));
+        assert!(chain[4..].iter().all(|l| l.swizzled_offset == tail));
+
+        let offsets: Vec<(usize, usize, usize)> = chain.iter().map(|l| l.packed_offset).collect();
+        assert_eq!(
+            offsets,
+            [
+                (0, 0, 0),
+                (0, 0, 0),
+                (0, 0, 0),
+                (0, 0, 0),
+                (16, 0, 0),
+                (8, 0, 0),
+                (4, 0, 0),
+                (0, 8, 0),
+                (0, 4, 0)
+            ]
+        );
+
+        let unpacked = Xbox360::deswizzle_mips_packed(
+            &source,
+            &mut dest,
+            (256, 256, 1),
+            format,
+            9,
+            false,
+            false,
+        )
+        .unwrap();
+        assert!(unpacked.iter().all(|l| l.packed_offset == (0, 0, 0)));
+        assert!(unpacked.swizzled_size() > chain.swizzled_size());
+
+        // A lone base level small enough for the tail is only packed by the surface API, the
+        // single image API keeps reading it from the start of the tile
+        let desc = crate::swizzle::SurfaceDesc::builder(format)
+            .width(64)
+            .height(16)
+            .build()
+            .unwrap();
+        let source = (0..0x2000)
+            .map(|i| (i * 3 + i / 256) as u8)
+            .collect::<Vec<u8>>();
+        let mut single = vec![0u8; 64 * 16 * 4];
+        let mut unpacked = vec![0u8; 64 * 16 * 4];
+        let mut packed = vec![0u8; 64 * 16 * 4];
+        Xbox360::deswizzle(&source, &mut single, (64, 16, 1), format, false).unwrap();
+        Xbox360::deswizzle_surface_packed(&source, &mut unpacked, &desc, false).unwrap();
+        Xbox360::deswizzle_surface(&source, &mut packed, &desc).unwrap();
+        assert!(single == unpacked);
+        assert!(single != packed);
+    }
+
+    #[test]
+    fn xbox360_packed_mip_tail_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+
+        // The base level of a 64x16 texture is already part of the mip tail
+        let chain = round_trip_mips::<crate::swizzle::xbox::Xbox360>(
+            (64, 16, 1),
+            XenosSurfaceFormat::k_16_16,
+            7,
+        );
+        assert_eq!(chain[0].packed_offset, (0, 16, 0));
+        assert_eq!(chain.swizzled_size(), 64 * 32 * 4);
+    }
+
+    #[test]
+    fn xbox360_3d_tiled_offset() {
+        use crate::swizzle::xbox::x360::xg_address_3d_tiled_offset;
+
+        // Every block of a 32x32x8 volume maps to its own offset inside the volume
+        for texel_byte_pitch in [1, 2, 4, 8, 16] {
+            let mut seen = vec![false; 32 * 32 * 8];
+            for z in 0..8 {
+                for y in 0..32 {
+                    for x in 0..32 {
+                        let offset = xg_address_3d_tiled_offset(x, y, z, 32, 32, texel_byte_pitch);
+                        assert!(!seen[offset], "({x}, {y}, {z}) overlaps another block");
+                        seen[offset] = true;
+                    }
+                }
+            }
+        }
+
+        // Slices are interleaved in groups of 4. These values come from this function itself, as
+        // there is no reference volume to take them from yet.
+        assert_eq!(xg_address_3d_tiled_offset(0, 0, 1, 32, 32, 4), 272);
+        assert_eq!(xg_address_3d_tiled_offset(0, 0, 4, 32, 32, 4), 4096);
+    }
+
+    #[test]
+    fn xbox360_volume_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+
+        round_trip::<crate::swizzle::xbox::Xbox360>(
+            (64, 32, 6),
+            XenosSurfaceFormat::k_8_8_8_8,
+            64 * 32 * 8 * 4,
+        );
+        round_trip_mips::<crate::swizzle::xbox::Xbox360>(
+            (64, 64, 8),
+            XenosSurfaceFormat::k_16_16,
+            7,
+        );
+    }
+
+    #[test]
+    fn switch_mip_chain_round_trip() {
+        round_trip_mips::<crate::swizzle::switch::Switch>((100, 60, 1), BC7, 5);
+        round_trip_mips::<crate::swizzle::switch::Switch>((512, 512, 1), Format8_8_8_8, 10);
+    }
+
+    #[test]
+    fn xbox_one_mip_chain_round_trip() {
+        round_trip_mips::<crate::swizzle::xbox::XboxOne>((256, 256, 1), Format8_8_8_8, 9);
+    }
+
+    #[test]
+    fn morton_3d_interleaves_all_axes() {
+        use crate::swizzle::morton_3d;
+
+        assert_eq!(morton_3d(1, 4, 4, 4), 1);
+        assert_eq!(morton_3d(2, 4, 4, 4), 4);
+        assert_eq!(morton_3d(4, 4, 4, 4), 16);
+        assert_eq!(morton_3d(8, 4, 4, 4), 2);
+        // Once Z runs out of bits, X and Y keep interleaving on their own
+        assert_eq!(morton_3d(8, 4, 4, 2), 2);
+        assert_eq!(morton_3d(16, 4, 4, 2), 8);
+    }
+
+    #[test]
+    fn ps3_volume_round_trip() {
+        round_trip::<crate::swizzle::ps::Ps3>((16, 16, 16), A8R8G8B8, 16 * 16 * 16 * 4);
+        round_trip::<crate::swizzle::ps::Ps3>((32, 8, 4), B8, 32 * 8 * 4);
+    }
+
+    #[test]
+    fn ps3_out_of_bounds() {
+        use crate::swizzle::{Deswizzler, SwizzleError, TextureSlice, ps::Ps3};
+
+        for depth in [1, 4] {
+            let size = 16 * 16 * depth * 4;
+            let source = vec![0u8; size];
+            let mut dest = vec![0u8; size];
+            assert!(matches!(
+                Ps3::deswizzle(
+                    &source[..size - 4],
+                    &mut dest,
+                    (16, 16, depth),
+                    A8R8G8B8,
+                    false
+                ),
+                Err(SwizzleError::OutOfBounds(TextureSlice::Source))
+            ));
+            assert!(matches!(
+                Ps3::deswizzle(
+                    &source,
+                    &mut dest[..size - 4],
+                    (16, 16, depth),
+                    A8R8G8B8,
+                    false
+                ),
+                Err(SwizzleError::OutOfBounds(TextureSlice::Dest))
+            ));
+        }
+    }
+
+    #[test]
+    fn cubemap_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{TextureKind, ps, switch, xbox};
+
+        // RSX faces each hold their whole mip chain, aligned to 128 bytes
+        let chain = round_trip_texture::<ps::Ps3>((64, 64, 1), A8R8G8B8, TextureKind::Cube, 7);
+        assert_eq!(chain.linear_layer_stride(), chain.linear_size() / 6);
+        assert!(chain.iter().all(|l| l.swizzled_layer_stride % 128 == 0));
+        assert!(chain[0].swizzled_layer_stride >= chain[6].swizzled_offset);
+
+        // GCN faces are the slices of each level
+        let chain = round_trip_texture::<ps::Ps4>((128, 128, 1), BC7, TextureKind::Cube, 6);
+        assert_eq!(chain[0].swizzled_layer_stride, chain[0].swizzled_size);
+        assert!(chain[1].swizzled_offset >= 6 * chain[0].swizzled_size);
+        round_trip_texture::<xbox::XboxOne>((100, 60, 1), Format8_8_8_8, TextureKind::Cube, 4);
+
+        // Xenos faces are 4KB aligned within each level, with a packed mip tail per face
+        let chain = round_trip_texture::<xbox::Xbox360>(
+            (64, 64, 1),
+            XenosSurfaceFormat::k_8,
+            TextureKind::Cube,
+            7,
+        );
+        assert!(chain.iter().all(|l| l.swizzled_layer_stride % 4096 == 0));
+        assert_eq!(chain[2].swizzled_offset, chain[6].swizzled_offset);
+
+        round_trip_texture::<switch::Switch>((256, 128, 1), BC7, TextureKind::Cube, 8);
+    }
+
+    #[test]
+    fn texture_array_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{TextureKind, ps, switch, xbox};
+
+        let kind = TextureKind::Array(3);
+        round_trip_texture::<ps::Ps3>((32, 16, 1), COMPRESSED_DXT45, kind, 4);
+        round_trip_texture::<ps::Ps4>((64, 64, 1), Format8_8_8_8, kind, 7);
+        round_trip_texture::<xbox::Xbox360>((128, 32, 1), XenosSurfaceFormat::k_16_16, kind, 5);
+
+        // Unlike a volume, the layers of an array don't shrink along with its mip levels
+        let chain = round_trip_texture::<switch::Switch>((64, 64, 1), Format8_8_8_8, kind, 7);
+        assert!(chain.iter().all(|l| l.dimensions.2 == 1));
+    }
+
+    #[test]
+    fn surface_desc_validation() {
+        use crate::swizzle::{SurfaceDesc, SwizzleError, TextureKind};
+
+        let builder = SurfaceDesc::builder(BC7).width(64).height(32);
+        assert!(builder.build().is_ok());
+        assert!(matches!(
+            builder.width(0).build(),
+            Err(SwizzleError::InvalidDimensions(0, 32, 1))
+        ));
+        assert!(matches!(
+            builder.depth(4).build(),
+            Err(SwizzleError::InvalidDimensions(64, 32, 4))
+        ));
+        assert!(matches!(
+            builder.depth(4).kind(TextureKind::Cube).build(),
+            Err(SwizzleError::InvalidDimensions(64, 32, 4))
+        ));
+        assert!(matches!(
+            builder.kind(TextureKind::Cube).depth(4).build(),
+            Err(SwizzleError::InvalidDimensions(64, 32, 4))
+        ));
+        let volume = builder.kind(TextureKind::Volume).depth(4).build().unwrap();
+        assert_eq!(
+            volume,
+            builder.depth(4).kind(TextureKind::Volume).build().unwrap()
+        );
+        assert_eq!(
+            builder
+                .kind(TextureKind::Volume)
+                .depth(1)
+                .build()
+                .unwrap()
+                .kind(),
+            TextureKind::Volume
+        );
+        assert!(matches!(
+            builder.mip_count(17).build(),
+            Err(SwizzleError::InvalidMipCount(17))
+        ));
+        assert!(matches!(
+            builder.array_size(0).build(),
+            Err(SwizzleError::InvalidArraySize(0))
+        ));
+        assert!(matches!(
+            builder.pitch(48).build(),
+            Err(SwizzleError::InvalidPitch(48))
+        ));
+    }
+
+    #[test]
+    fn surface_round_trip() {
+        use crate::swizzle::{Deswizzler, SurfaceDesc, Swizzler, gcn::TileMode, xbox::XboxOne};
+
+        let desc = SurfaceDesc::builder(Format8_8_8_8)
+            .width(100)
+            .height(60)
+            .mip_count(3)
+            .pitch(128)
+            .tile_mode(TileMode::Thin_1dThin)
+            .build()
+            .unwrap();
+
+        let linear: Vec<u8> = (0..100 * 60 * 4 * 2).map(|i| (i * 7) as u8).collect();
+        let mut swizzled = vec![0u8; 0x40000];
+        let chain = XboxOne::swizzle_surface(&linear, &mut swizzled, &desc).unwrap();
+        assert_eq!(chain[0].padded_dimensions, (128, 60, 1));
+
+        let mut result = vec![0u8; chain.linear_size()];
+        XboxOne::deswizzle_surface(&swizzled, &mut result, &desc).unwrap();
+        assert!(result == linear[..chain.linear_size()]);
+    }
+
+    #[test]
+    fn surface_sizes() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{SurfaceDesc, TextureLayout, ps, switch, xbox};
+
+        // 1D thin pads to whole 8x8 micro tiles
+        let desc = SurfaceDesc::builder(BC7)
+            .width(900)
+            .height(1080)
+            .build()
+            .unwrap();
+        assert_eq!(ps::Ps4::linear_size(&desc).unwrap(), 225 * 270 * 16);
+        assert_eq!(ps::Ps4::swizzled_size(&desc).unwrap(), 232 * 272 * 16);
+
+        let desc = SurfaceDesc::builder(A8R8G8B8)
+            .width(64)
+            .height(64)
+            .mip_count(7)
+            .build()
+            .unwrap();
+        assert_eq!(ps::Ps3::swizzled_size(&desc).unwrap(), 21844);
+        assert_eq!(ps::Ps3::linear_size(&desc).unwrap(), 21844);
+
+        // The 32x32 level is padded to a 4KB tile, the rest share the packed mip tail
+        let desc = SurfaceDesc::builder(XenosSurfaceFormat::k_8_8_8_8)
+            .width(64)
+            .height(64)
+            .mip_count(7)
+            .build()
+            .unwrap();
+        assert_eq!(
+            xbox::Xbox360::swizzled_size(&desc).unwrap(),
+            0x4000 + 0x1000 * 2
+        );
+
+        let desc = SurfaceDesc::builder(Format8_8_8_8)
+            .width(256)
+            .height(256)
+            .build()
+            .unwrap();
+        assert_eq!(switch::Switch::swizzled_size(&desc).unwrap(), 256 * 256 * 4);
+    }
+
+    #[test]
+    fn xbox360_source_is_not_modified() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{Deswizzler, xbox::Xbox360};
+
+        let format = XenosSurfaceFormat::k_DXT4_5;
+        let source: Vec<u8> = (0..128 * 128).map(|i| (i * 7 + i / 251) as u8).collect();
+        let mut first = vec![0u8; 128 * 128];
+        let mut second = vec![0u8; 128 * 128];
+
+        Xbox360::deswizzle(&source, &mut first, (128, 128, 1), format, false).unwrap();
+        Xbox360::deswizzle(&source, &mut second, (128, 128, 1), format, false).unwrap();
+        assert!(first == second, "Deswizzling twice gave different results");
+
+        // Swapping twice restores the data, and a trailing odd byte stays where it is
+        let mut swapped = source.clone();
+        Xbox360::swap_byte_order_in_place(&mut swapped);
+        assert_eq!(swapped[..2], [source[1], source[0]]);
+        Xbox360::swap_byte_order_in_place(&mut swapped);
+        assert!(swapped == source);
+        let mut odd = [1, 2, 3];
+        Xbox360::swap_byte_order_in_place(&mut odd);
+        assert_eq!(odd, [2, 1, 3]);
+    }
+
+    #[test]
+    fn platform_dispatch() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{Deswizzler, Format, Platform, SurfaceDesc, xbox::Xbox360};
+
+        let source: Vec<u8> = (0..64 * 64 * 8).map(|i| (i * 7 + i / 251) as u8).collect();
+        let format = XenosSurfaceFormat::k_8_8_8_8;
+        let mut expected = vec![0u8; 64 * 64 * 4];
+        let mut dest = vec![0u8; 64 * 64 * 4];
+
+        Xbox360::deswizzle(&source, &mut expected, (64, 64, 1), format, false).unwrap();
+        Platform::Xbox360
+            .deswizzle(&source, &mut dest, (64, 64, 1), &format, false)
+            .unwrap();
+        assert!(dest == expected);
+
+        let formats: [(Platform, &dyn Format); 5] = [
+            (Platform::Ps3, &A8R8G8B8),
+            (Platform::Ps4, &Format8_8_8_8),
+            (Platform::Xbox360, &format),
+            (Platform::XboxOne, &Format8_8_8_8),
+            (Platform::Switch, &Format8_8_8_8),
+        ];
+        for (platform, format) in formats {
+            let desc = SurfaceDesc::builder(format)
+                .width(64)
+                .height(64)
+                .mip_count(4)
+                .build()
+                .unwrap();
+            let linear = &source[..platform.linear_size(&desc).unwrap()];
+            let mut swizzled = vec![0u8; platform.swizzled_size(&desc).unwrap()];
+            let mut result = vec![0u8; linear.len()];
+
+            platform
+                .swizzle_surface(linear, &mut swizzled, &desc)
+                .unwrap();
+            platform
+                .deswizzle_surface(&swizzled, &mut result, &desc)
+                .unwrap();
+            assert!(result == linear, "{platform:?} round trip did not match");
+        }
+    }
+
+    #[test]
+    fn invalid_mip_count() {
+        use crate::swizzle::{Deswizzler, ps::Ps3};
+
+        let result = Ps3::deswizzle_mips(&[], &mut [], (64, 64, 1), A8R8G8B8, 0, false);
+        assert!(result.is_err(), "A mip count of 0 should be rejected");
+    }
+
+    /// Checks that every value of `values` either converts to a format that converts back to it,
+    /// or is rejected, returning the number of formats
+    fn check_format_values<T, R>(values: impl Iterator<Item = R>) -> usize
+    where
+        T: TryFrom<R, Error = crate::swizzle::SwizzleError> + Copy,
+        R: From<T> + Into<u32> + Copy + PartialEq + core::fmt::Debug,
+    {
+        let mut count = 0;
+        for value in values {
+            match T::try_from(value) {
+                Ok(format) => {
+                    assert_eq!(R::from(format), value);
+                    count += 1;
+                }
+                Err(crate::swizzle::SwizzleError::FormatOutOfRange(e)) => {
+                    assert_eq!(e, value.into())
+                }
+                Err(e) => panic!("{e:?}"),
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn format_integer_round_trip() {
+        use crate::format::{DxgiFormat, GcmSurfaceFormat, GcnSurfaceFormat, XenosSurfaceFormat};
+
+        let values = (0..=0xFFFF).chain([0x8000_0000, u32::MAX - 1, u32::MAX]);
+        assert_eq!(
+            check_format_values::<DxgiFormat, u32>(values),
+            DxgiFormat::ALL.len()
+        );
+        assert_eq!(
+            check_format_values::<GcnSurfaceFormat, u16>(0..=u16::MAX),
+            GcnSurfaceFormat::ALL.len()
+        );
+        assert_eq!(
+            check_format_values::<XenosSurfaceFormat, u8>(0..=u8::MAX),
+            XenosSurfaceFormat::ALL.len()
+        );
+        assert_eq!(
+            check_format_values::<GcmSurfaceFormat, u8>(0..=u8::MAX),
+            GcmSurfaceFormat::ALL.len()
+        );
+
+        // Gaps between the values of the enums
+        for value in [0x89, 0x8A, 0x8C, 0x96] {
+            assert!(GcmSurfaceFormat::try_from(value).is_err());
+        }
+        for value in [0x0F, 0x17, 0x1F, 0x2A] {
+            assert!(GcnSurfaceFormat::try_from(value).is_err());
+        }
+        assert!(DxgiFormat::try_from(116).is_err());
+        assert_eq!(
+            DxgiFormat::try_from(189).unwrap(),
+            DxgiFormat::SAMPLER_FEEDBACK_MIN_MIP_OPAQUE
+        );
+        assert_eq!(
+            DxgiFormat::try_from(190).unwrap(),
+            DxgiFormat::SAMPLER_FEEDBACK_MIP_REGION_USED_OPAQUE
+        );
+        assert_eq!(XenosSurfaceFormat::ALL.len(), 64);
+    }
+
+    #[test]
+    fn unsupported_formats_are_errors() {
+        use crate::format::{DxgiFormat, GcmSurfaceFormat, XenosSurfaceFormat};
+        use crate::swizzle::{
+            Deswizzler, Format, SurfaceDesc, SwizzleError, Swizzler, ps::Ps4, switch::Switch,
+        };
+
+        assert!(matches!(
+            DxgiFormat::Unknown.bpp(),
+            Err(SwizzleError::UnsupportedFormat(0))
+        ));
+        assert!(matches!(
+            DxgiFormat::NV12.block_size(),
+            Err(SwizzleError::UnsupportedFormat(103))
+        ));
+        assert!(matches!(
+            DxgiFormat::R1_UNORM.block_size(),
+            Err(SwizzleError::UnsupportedFormat(66))
+        ));
+        assert!(matches!(
+            XenosSurfaceFormat::k_1.block_size(),
+            Err(SwizzleError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            Invalid.bpp(),
+            Err(SwizzleError::UnsupportedFormat(_))
+        ));
+
+        // Every GCM format has a size
+        for format in GcmSurfaceFormat::ALL {
+            assert!(format.block_size().is_ok(), "{format:?}");
+        }
+        assert_eq!(COMPRESSED_HILO8.bpp().unwrap(), 16);
+        assert_eq!(W32_Z32_Y32_X32_FLOAT.block_size().unwrap(), 16);
+
+        let source = [0u8; 256];
+        let mut dest = [0u8; 256];
+        assert!(matches!(
+            Ps4::swizzle(&source, &mut dest, (8, 8, 1), DxgiFormat::Unknown, false),
+            Err(SwizzleError::UnsupportedFormat(0))
+        ));
+
+        let desc = SurfaceDesc::builder(DxgiFormat::NV12)
+            .width(8)
+            .height(8)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            Switch::deswizzle_surface(&source, &mut dest, &desc),
+            Err(SwizzleError::UnsupportedFormat(103))
+        ));
+    }
+
+    #[test]
+    fn dxgi_format_conversions() {
+        use crate::format::{DxgiFormat, GcmSurfaceFormat, GcnSurfaceFormat, XenosSurfaceFormat};
+
+        assert_eq!(
+            DxgiFormat::try_from(COMPRESSED_DXT45).unwrap(),
+            DxgiFormat::BC3_UNORM
+        );
+        assert_eq!(
+            DxgiFormat::try_from(XenosSurfaceFormat::k_DXN).unwrap(),
+            DxgiFormat::BC5_UNORM
+        );
+        assert_eq!(DxgiFormat::try_from(BC6).unwrap(), DxgiFormat::BC6H_UF16);
+        assert_eq!(
+            DxgiFormat::try_from(A8R8G8B8).unwrap(),
+            DxgiFormat::B8G8R8A8_UNORM
+        );
+        assert!(DxgiFormat::try_from(XenosSurfaceFormat::k_CTX1).is_err());
+        assert!(DxgiFormat::try_from(Format11_11_10).is_err());
+
+        // sRGB and typeless variants map to the same console format as their UNORM variant
+        for format in [
+            DxgiFormat::BC1_TYPELESS,
+            DxgiFormat::BC1_UNORM,
+            DxgiFormat::BC1_UNORM_SRGB,
+        ] {
+            assert_eq!(GcnSurfaceFormat::try_from(format).unwrap(), BC1);
+            assert_eq!(
+                XenosSurfaceFormat::try_from(format).unwrap(),
+                XenosSurfaceFormat::k_DXT1
+            );
+            assert_eq!(GcmSurfaceFormat::try_from(format).unwrap(), COMPRESSED_DXT1);
+        }
+        assert!(GcmSurfaceFormat::try_from(DxgiFormat::BC7_UNORM).is_err());
+
+        assert_eq!(DxgiFormat::BC7_UNORM.to_srgb(), DxgiFormat::BC7_UNORM_SRGB);
+        assert_eq!(DxgiFormat::R16_FLOAT.to_srgb(), DxgiFormat::R16_FLOAT);
+        assert_eq!(
+            DxgiFormat::B8G8R8A8_UNORM_SRGB.to_typeless(),
+            DxgiFormat::B8G8R8A8_TYPELESS
+        );
+
+        // Every Xenos format with a DXGI equivalent converts back to the same layout, although the
+        // gamma EDRAM format loses its sRGB flag
+        for value in 0..=u8::MAX {
+            if let Ok(format) = XenosSurfaceFormat::try_from(value)
+                && let Ok(dxgi) = DxgiFormat::try_from(format)
+            {
+                let back = XenosSurfaceFormat::try_from(dxgi).unwrap();
+                assert_eq!(
+                    DxgiFormat::try_from(back).unwrap().to_typeless(),
+                    dxgi.to_typeless(),
+                    "{format:?} did not round trip"
+                );
+            }
+        }
+        for format in [
+            Format8,
+            Format16_16,
+            Format10_11_11,
+            Format2_10_10_10,
+            Format8_8_8_8,
+            Format5_9_9_9,
+            BC1,
+            BC5,
+            BC7,
+        ] {
+            let dxgi = DxgiFormat::try_from(format).unwrap();
+            assert_eq!(GcnSurfaceFormat::try_from(dxgi).unwrap(), format);
+        }
+    }
+
+    #[test]
+    fn dds_round_trip() {
+        use crate::dds::{self, Dds, HeaderVersion};
+        use crate::format::DxgiFormat;
+        use crate::swizzle::{SurfaceDesc, TextureKind};
+
+        let descs = [
+            (
+                HeaderVersion::Legacy,
+                DxgiFormat::BC1_UNORM,
+                TextureKind::Cube,
+            ),
+            (
+                HeaderVersion::Legacy,
+                DxgiFormat::B8G8R8A8_UNORM,
+                TextureKind::Volume,
+            ),
+            (
+                HeaderVersion::Legacy,
+                DxgiFormat::R8G8B8A8_UNORM,
+                TextureKind::Texture2D,
+            ),
+            (
+                HeaderVersion::Dx10,
+                DxgiFormat::BC7_UNORM_SRGB,
+                TextureKind::Array(3),
+            ),
+            (
+                HeaderVersion::Dx10,
+                DxgiFormat::BC6H_UF16,
+                TextureKind::Cube,
+            ),
+            (
+                HeaderVersion::Dx10,
+                DxgiFormat::R16G16_FLOAT,
+                TextureKind::Volume,
+            ),
+        ];
+        for (version, format, kind) in descs {
+            let depth = if kind == TextureKind::Volume { 8 } else { 1 };
+            let desc = SurfaceDesc::builder(format)
+                .width(32)
+                .height(16)
+                .depth(depth)
+                .kind(kind)
+                .mip_count(5)
+                .build()
+                .unwrap();
+            let data = (0..dds::data_size(&desc).unwrap())
+                .map(|i| i as u8)
+                .collect::<Vec<u8>>();
+
+            let mut file = vec![0u8; dds::header_size(version) + data.len()];
+            let written = dds::write(&mut file, &desc, &data, version).unwrap();
+            assert_eq!(written, file.len());
+
+            let dds = Dds::read(&file).unwrap();
+            assert_eq!(dds.desc(), &desc, "{format:?} {kind:?}");
+            assert!(dds.data() == data);
+            assert!(dds.xbox().is_none());
+        }
+
+        assert_eq!(
+            dds::data_size(
+                &SurfaceDesc::builder(DxgiFormat::BC1_UNORM)
+                    .width(8)
+                    .height(8)
+                    .mip_count(4)
+                    .kind(TextureKind::Cube)
+                    .build()
+                    .unwrap()
+            )
+            .unwrap(),
+            (32 + 8 + 8 + 8) * 6
+        );
+
+        let array = SurfaceDesc::builder(DxgiFormat::BC1_UNORM)
+            .array_size(2)
+            .build()
+            .unwrap();
+        let bc7 = SurfaceDesc::builder(DxgiFormat::BC7_UNORM).build().unwrap();
+        let mut file = vec![0u8; 256];
+        assert!(dds::write(&mut file, &array, &[0; 16], HeaderVersion::Legacy).is_err());
+        assert!(dds::write(&mut file, &bc7, &[0; 16], HeaderVersion::Legacy).is_err());
+        assert!(dds::write(&mut file, &bc7, &[0; 16], HeaderVersion::Dx10).is_ok());
+
+        file[0] = b'X';
+        assert!(Dds::read(&file).is_err());
+        assert!(Dds::read(&file[..64]).is_err());
+    }
+
+    #[test]
+    fn dds_xbox_header() {
+        use crate::dds::{self, Dds, HeaderVersion};
+        use crate::format::DxgiFormat;
+        use crate::swizzle::{
+            Deswizzler, SurfaceDesc, Swizzler, TextureLayout, gcn::TileMode, xbox::XboxOne,
+        };
+
+        let desc = SurfaceDesc::builder(DxgiFormat::R8G8B8A8_UNORM)
+            .width(64)
+            .height(64)
+            .tile_mode(TileMode::Thin_1dThin)
+            .build()
+            .unwrap();
+        let linear = (0..dds::data_size(&desc).unwrap())
+            .map(|i| (i * 7) as u8)
+            .collect::<Vec<u8>>();
+        let mut swizzled = vec![0u8; XboxOne::swizzled_size(&desc).unwrap()];
+        XboxOne::swizzle_surface(&linear, &mut swizzled, &desc).unwrap();
+
+        // Xbox files replace the DX10 header with a longer one that has the same first fields
+        let header_size = dds::header_size(HeaderVersion::Dx10);
+        let mut file = vec![0u8; header_size + linear.len()];
+        dds::write(&mut file, &desc, &linear, HeaderVersion::Dx10).unwrap();
+        file.truncate(header_size);
+        file[84..88].copy_from_slice(b"XBOX");
+        for field in [TileMode::Thin_1dThin as u32, 256, swizzled.len() as u32, 0] {
+            file.extend_from_slice(&field.to_le_bytes());
+        }
+        file.extend_from_slice(&swizzled);
+
+        let dds = Dds::read(&file).unwrap();
+        let xbox = dds.xbox().unwrap();
+        assert_eq!(xbox.tile_mode, TileMode::Thin_1dThin);
+        assert_eq!(xbox.base_alignment, 256);
+        assert_eq!(dds.desc().tile_mode(), Some(TileMode::Thin_1dThin));
+        assert!(dds.data() == swizzled);
+
+        let mut result = vec![0u8; linear.len()];
+        XboxOne::deswizzle_surface(dds.data(), &mut result, dds.desc()).unwrap();
+        assert!(result == linear);
+    }
+
+    #[test]
+    fn gnf_round_trip() {
+        use crate::format::GcnSurfaceFormat;
+        use crate::gnf::{self, Gnf, TextureDescriptor, TextureType};
+        use crate::swizzle::{
+            Deswizzler, SurfaceDesc, Swizzler, TextureKind, TextureLayout, gcn::TileMode, ps::Ps4,
+        };
+
+        let descs = [
+            SurfaceDesc::builder(GcnSurfaceFormat::Format8_8_8_8)
+                .width(100)
+                .height(64)
+                .pitch(128)
+                .mip_count(3)
+                .tile_mode(TileMode::Thin_2dThin)
+                .build()
+                .unwrap(),
+            SurfaceDesc::builder(BC3)
+                .width(64)
+                .height(64)
+                .kind(TextureKind::Cube)
+                .mip_count(4)
+                .pitch(64)
+                .tile_mode(Ps4::DEFAULT_TILE_MODE)
+                .build()
+                .unwrap(),
+        ];
+
+        let mut linear = Vec::new();
+        let mut swizzled = Vec::new();
+        for desc in &descs {
+            let source = (0..Ps4::linear_size(desc).unwrap())
+                .map(|i| (i * 13) as u8)
+                .collect::<Vec<u8>>();
+            let mut dest = vec![0u8; Ps4::swizzled_size(desc).unwrap()];
+            Ps4::swizzle_surface(&source, &mut dest, desc).unwrap();
+            linear.push(source);
+            swizzled.push(dest);
+        }
+
+        let textures = [
+            (
+                TextureDescriptor::from_desc(&descs[0]).unwrap(),
+                swizzled[0].as_slice(),
+            ),
+            (
+                TextureDescriptor::from_desc(&descs[1]).unwrap(),
+                swizzled[1].as_slice(),
+            ),
+        ];
+        let mut file = vec![0u8; gnf::file_size(&textures)];
+        gnf::write(&mut file, &textures).unwrap();
+
+        let gnf = Gnf::read(&file).unwrap();
+        assert_eq!(gnf.len(), 2);
+        for (i, desc) in descs.iter().enumerate() {
+            let texture = gnf.texture(i).unwrap();
+            assert_eq!(&texture.descriptor().surface_desc().unwrap(), desc);
+            assert!(texture.data() == swizzled[i]);
+
+            let mut result = vec![0u8; linear[i].len()];
+            Ps4::deswizzle_surface(texture.data(), &mut result, desc).unwrap();
+            assert!(result == linear[i]);
+        }
+        assert_eq!(
+            gnf.texture(1).unwrap().descriptor().texture_type,
+            TextureType::Cube
+        );
+        assert!(gnf.texture(2).is_err());
+
+        file[8] = 1;
+        assert!(Gnf::read(&file).is_err());
+        file[0] = 0;
+        assert!(Gnf::read(&file).is_err());
+    }
+
+    #[test]
+    fn gnf_pow2_pad() {
+        use crate::format::GcnSurfaceFormat;
+        use crate::gnf::TextureDescriptor;
+        use crate::swizzle::{AlignmentPolicy, SurfaceDesc, TextureLayout, ps::Ps4};
+
+        for (format, padded) in [(GcnSurfaceFormat::Format8_8_8_8, false), (BC3, true)] {
+            let builder = SurfaceDesc::builder(format)
+                .width(132)
+                .height(60)
+                .mip_count(3);
+            let exact = builder.build().unwrap();
+            let desc = builder
+                .alignment(AlignmentPolicy::PowerOfTwo)
+                .build()
+                .unwrap();
+
+            // The bit survives a trip through the descriptor words
+            let descriptor = TextureDescriptor::from_desc(&desc).unwrap();
+            assert!(descriptor.pow2_pad);
+            let descriptor = TextureDescriptor::from_words(descriptor.to_words()).unwrap();
+            assert!(descriptor.pow2_pad);
+            assert_eq!(
+                descriptor.surface_desc().unwrap().alignment(),
+                AlignmentPolicy::PowerOfTwo
+            );
+
+            // Only the compressed base level is padded
+            let size = Ps4::swizzled_size(&desc).unwrap();
+            assert_eq!(size != Ps4::swizzled_size(&exact).unwrap(), padded);
+            assert_eq!(descriptor.size as usize, size);
+        }
+    }
+
+    #[test]
+    fn gnf_texture_descriptor_words() {
+        use crate::gnf::{TextureDescriptor, TextureType};
+        use crate::swizzle::gcn::TileMode;
+
+        // BC7 SRGB, 512x256, pitch 512, 10 levels, 2D thin
+        let words = [
+            0x0000_0010,
+            0x2690_0000,
+            0x003F_C1FF,
+            0x90E9_0FAC,
+            0x003F_E000,
+            0x0000_0000,
+            0x0000_0000,
+            0x0002_AB00,
+        ];
+        let descriptor = TextureDescriptor::from_words(words).unwrap();
+        assert_eq!(descriptor.base_address, 0x1000);
+        assert_eq!(descriptor.data_format, BC7);
+        assert_eq!(descriptor.num_format, 9);
+        assert_eq!((descriptor.width, descriptor.height), (512, 256));
+        assert_eq!(descriptor.pitch, 512);
+        assert_eq!(descriptor.dst_sel, [4, 5, 6, 7]);
+        assert_eq!((descriptor.base_level, descriptor.last_level), (0, 9));
+        assert_eq!(descriptor.tile_mode, TileMode::Thin_2dThin);
+        assert_eq!(descriptor.texture_type, TextureType::Texture2D);
+        assert_eq!(descriptor.size, 0x2AB00);
+        assert_eq!(descriptor.to_words(), words);
+
+        let mut invalid = words;
+        invalid[3] &= 0x0FFF_FFFF;
+        assert!(TextureDescriptor::from_words(invalid).is_err());
+    }
+
+    #[test]
+    fn gtf_round_trip() {
+        use crate::gtf::{self, GcmTexture, Gtf};
+        use crate::swizzle::{
+            SurfaceDesc, SwizzleError, Swizzler, TextureKind, TextureLayout, ps::Ps3,
+        };
+
+        let desc = SurfaceDesc::builder(A8R8G8B8)
+            .width(32)
+            .height(32)
+            .kind(TextureKind::Cube)
+            .mip_count(3)
+            .build()
+            .unwrap();
+        let linear = (0..Ps3::linear_size(&desc).unwrap())
+            .map(|i| (i * 3) as u8)
+            .collect::<Vec<u8>>();
+        let mut swizzled = vec![0u8; Ps3::swizzled_size(&desc).unwrap()];
+        Ps3::swizzle_surface(&linear, &mut swizzled, &desc).unwrap();
+
+        // A linear 30x4 texture with rows padded to 128 bytes
+        let padded_desc = SurfaceDesc::builder(A8R8G8B8)
+            .width(30)
+            .height(4)
+            .build()
+            .unwrap();
+        let padded_texture = GcmTexture {
+            pitch: 128,
+            ..GcmTexture::from_desc(&padded_desc, true).unwrap()
+        };
+
+        // Cubemaps are the only arrays a GCM texture can describe
+        let array_desc = SurfaceDesc::builder(A8R8G8B8)
+            .width(32)
+            .height(32)
+            .array_size(4)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            GcmTexture::from_desc(&array_desc, false),
+            Err(SwizzleError::InvalidTextureType(2))
+        ));
+        let mut padded = vec![0xFFu8; 128 * 4];
+        for row in padded.chunks_exact_mut(128) {
+            row[..120].copy_from_slice(&linear[..120]);
+        }
+
+        let textures = [
+            (
+                GcmTexture::from_desc(&desc, false).unwrap(),
+                swizzled.as_slice(),
+            ),
+            (padded_texture, padded.as_slice()),
+        ];
+        let mut file = vec![0u8; gtf::file_size(&textures)];
+        gtf::write(&mut file, &textures).unwrap();
+
+        let gtf = Gtf::read(&file).unwrap();
+        assert_eq!(gtf.len(), 2);
+
+        let texture = gtf.texture(0).unwrap();
+        assert!(texture.texture().is_swizzled());
+        assert_eq!(texture.texture().surface_desc().unwrap(), desc);
+        let mut result = vec![0u8; linear.len()];
+        texture.deswizzle(&mut result).unwrap();
+        assert!(result == linear);
+
+        let texture = gtf.texture(1).unwrap();
+        assert_eq!(texture.id(), 1);
+        assert_eq!(texture.texture().format_byte(), 0xA5);
+        assert!(!texture.texture().is_swizzled());
+        let mut result = vec![0u8; 120 * 4];
+        texture.deswizzle(&mut result).unwrap();
+        for row in result.chunks_exact(120) {
+            assert!(row == &linear[..120]);
+        }
+        assert!(gtf.texture(2).is_err());
+    }
+
+    #[test]
+    fn gtf_ps3_reference() {
+        use crate::gtf::{self, GcmTexture, Gtf};
+        use crate::swizzle::SurfaceDesc;
+
+        let swizzled = include_bytes!("../testdata/ps3-bc3-128x128.bin");
+        let unswizzled = include_bytes!("../testdata/ps3-bc3-128x128-unswizzled.bin");
+
+        let desc = SurfaceDesc::builder(COMPRESSED_DXT45)
+            .width(128)
+            .height(128)
+            .build()
+            .unwrap();
+        let texture = GcmTexture::from_desc(&desc, false).unwrap();
+        let textures = [(texture, &swizzled[..])];
+        let mut file = vec![0u8; gtf::file_size(&textures)];
+        gtf::write(&mut file, &textures).unwrap();
+
+        let gtf = Gtf::read(&file).unwrap();
+        let texture = gtf.texture(0).unwrap();
+        assert!(texture.texture().is_swizzled());
+        let mut result = vec![0u8; unswizzled.len()];
+        texture.deswizzle(&mut result).unwrap();
+        assert!(
+            result[..] == unswizzled[..],
+            "Deswizzled GTF did not match reference"
+        );
+    }
+
+    #[test]
+    fn gtf_format_flags() {
+        use crate::gtf::GcmTexture;
+
+        let mut state = [0u8; 24];
+        state[..4].copy_from_slice(&[0xC6, 1, 2, 0]);
+        state[8..14].copy_from_slice(&[0, 16, 0, 16, 0, 1]);
+
+        let texture = GcmTexture::read(&state).unwrap();
+        assert_eq!(texture.format, COMPRESSED_DXT1);
+        assert!(texture.unnormalized);
+        assert!(!texture.linear);
+        // Without LN, compressed textures are swizzled too
+        assert!(texture.is_swizzled());
+
+        let mut written = [0u8; 24];
+        texture.write(&mut written);
+        assert_eq!(written, state);
+
+        state[0] = 0x80;
+        assert!(GcmTexture::read(&state).is_err());
+    }
+
+    #[test]
+    fn fetch_constant_words() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{
+            SurfaceDesc, TextureKind,
+            xbox::{XenosComponent, XenosEndian, XenosSwizzle},
+        };
+        use crate::xpr::{FetchConstant, TextureDimension};
+
+        // A tiled array of 4 100x50 k_8_8_8_8 layers with 128 texel rows
+        let fetch = FetchConstant::from_words([
+            0x8100_0002,
+            0x0000_1586,
+            0x0C06_2063,
+            0x0000_0D18,
+            0x0000_0000,
+            0x0000_0200,
+        ])
+        .unwrap();
+        assert!(fetch.tiled);
+        assert!(fetch.stacked);
+        assert_eq!(fetch.format, XenosSurfaceFormat::k_8_8_8_8);
+        assert_eq!(fetch.endian, XenosEndian::Swap8In32);
+        assert_eq!(fetch.base_address, 0x1000);
+        assert_eq!((fetch.width, fetch.height, fetch.depth), (100, 50, 4));
+        assert_eq!(fetch.pitch, 128);
+        assert_eq!(
+            fetch.swizzle,
+            XenosSwizzle([
+                XenosComponent::Zero,
+                XenosComponent::Y,
+                XenosComponent::Z,
+                XenosComponent::W
+            ])
+        );
+        assert_eq!(fetch.dimension, TextureDimension::Texture2D);
+        assert_eq!(fetch.mip_address, 0);
+
+        let desc = SurfaceDesc::builder(XenosSurfaceFormat::k_8_8_8_8)
+            .width(100)
+            .height(50)
+            .pitch(128)
+            .kind(TextureKind::Array(4))
+            .build()
+            .unwrap();
+        assert_eq!(fetch.surface_desc().unwrap(), desc);
+    }
+
+    #[test]
+    fn xpr2_round_trip() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{
+            SurfaceDesc, SwizzleError, TextureLayout,
+            xbox::{Xbox360, XenosEndian},
+        };
+        use crate::xpr::Xpr2;
+
+        let desc = SurfaceDesc::builder(XenosSurfaceFormat::k_DXT1)
+            .width(64)
+            .height(64)
+            .mip_count(7)
+            .build()
+            .unwrap();
+        let linear = (0..Xbox360::linear_size(&desc).unwrap())
+            .map(|i| (i * 7) as u8)
+            .collect::<Vec<u8>>();
+        let mut swizzled = vec![0u8; Xbox360::swizzled_size(&desc).unwrap()];
+        Xbox360::swizzle_surface_packed(&linear, &mut swizzled, &desc, true).unwrap();
+
+        // An untiled 16x4 k_8 texture at 0x6000, rows padded to 32 bytes
+        let mut untiled = [0u8; 32 * 4];
+        for (row, bytes) in untiled.chunks_exact_mut(32).enumerate() {
+            bytes[..16].fill(row as u8 + 1);
+        }
+
+        let fetch_words: [[u32; 6]; 2] = [
+            [0x8000_0002, 0x0000_0052, 0x0007_E03F, 0, 0x180, 0x0000_2A00],
+            [0x0040_0002, 0x0000_6002, 0x0000_600F, 0, 0, 0x0000_0200],
+        ];
+        let header_size = 0x100;
+        let data_size = 0x6000 + untiled.len();
+        let mut file = vec![0u8; 12 + header_size + data_size];
+        file[..4].copy_from_slice(b"XPR2");
+        file[4..8].copy_from_slice(&(header_size as u32).to_be_bytes());
+        file[8..12].copy_from_slice(&(data_size as u32).to_be_bytes());
+        file[12..16].copy_from_slice(&3u32.to_be_bytes());
+        let resources: [(&[u8; 4], u32, &[u8]); 3] = [
+            (b"TX2D", 0x40, b"base\0"),
+            (b"TX2D", 0x80, b"flat\0"),
+            (b"USER", 0, b"\0"),
+        ];
+        for (i, (kind, offset, name)) in resources.iter().enumerate() {
+            let entry = 16 + i * 16;
+            let name_offset = 0xC0 + i as u32 * 8;
+            file[entry..entry + 4].copy_from_slice(*kind);
+            file[entry + 4..entry + 8].copy_from_slice(&offset.to_be_bytes());
+            file[entry + 8..entry + 12].copy_from_slice(&52u32.to_be_bytes());
+            file[entry + 12..entry + 16].copy_from_slice(&name_offset.to_be_bytes());
+            let name_start = 12 + name_offset as usize;
+            file[name_start..name_start + name.len()].copy_from_slice(name);
+        }
+        for (words, offset) in fetch_words.iter().zip([0x40, 0x80]) {
+            for (i, word) in words.iter().enumerate() {
+                let start = 12 + offset + 28 + i * 4;
+                file[start..start + 4].copy_from_slice(&word.to_be_bytes());
+            }
+        }
+        let data_start = 12 + header_size;
+        file[data_start..data_start + swizzled.len()].copy_from_slice(&swizzled);
+        file[data_start + 0x6000..].copy_from_slice(&untiled);
+
+        let xpr = Xpr2::read(&file).unwrap();
+        assert_eq!(xpr.len(), 3);
+
+        let texture = xpr.texture(0).unwrap();
+        assert_eq!(texture.name(), b"base");
+        assert_eq!(texture.fetch_constant().surface_desc().unwrap(), desc);
+        let mut result = vec![0u8; linear.len()];
+        texture.deswizzle(&mut result).unwrap();
+        assert!(result == linear);
+
+        let texture = xpr.texture(1).unwrap();
+        assert_eq!(texture.name(), b"flat");
+        let mut result = [0u8; 16 * 4];
+        texture.deswizzle(&mut result).unwrap();
+        for (row, bytes) in result.chunks_exact(16).enumerate() {
+            assert!(bytes.iter().all(|&b| b == row as u8 + 1));
+        }
+
+        assert_eq!(xpr.resource_type(2).unwrap(), *b"USER");
+        assert!(matches!(
+            xpr.texture(2),
+            Err(SwizzleError::InvalidTextureType(0x5553_4552))
+        ));
+        assert!(xpr.texture(3).is_err());
+
+        // A mip address that doesn't follow the base level
+        let mut words = fetch_words[0];
+        words[5] |= 0x1000;
+        let fetch = crate::xpr::FetchConstant::from_words(words).unwrap();
+        assert!(matches!(
+            fetch.deswizzle(&swizzled, &mut result),
+            Err(SwizzleError::InvalidMipAddress(0x3000))
+        ));
+
+        // Words of an untiled texture are swapped within its padded rows, even when a row ends
+        // in the middle of one
+        let untiled_ramp: [u8; 32 * 4] = core::array::from_fn(|i| i as u8);
+        let mut fetch = crate::xpr::FetchConstant::from_words(fetch_words[1]).unwrap();
+        fetch.width = 15;
+        fetch.endian = XenosEndian::Swap8In16;
+        let mut result = [0u8; 15 * 4];
+        fetch.deswizzle(&untiled_ramp, &mut result).unwrap();
+        for (row, bytes) in result.chunks_exact(15).enumerate() {
+            for (x, &byte) in bytes.iter().enumerate() {
+                assert_eq!(byte, untiled_ramp[row * 32 + (x ^ 1)]);
+            }
+        }
+    }
+
+    #[test]
+    fn xenos_endian() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{
+            Deswizzler, SurfaceDesc, TextureLayout, xbox::Xbox360, xbox::XenosEndian,
+        };
+
+        let mut words = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        XenosEndian::Swap8In16.swap_in_place(&mut words);
+        assert_eq!(words, [2, 1, 4, 3, 6, 5, 8, 7]);
+        XenosEndian::Swap8In16.swap_in_place(&mut words);
+        XenosEndian::Swap8In32.swap_in_place(&mut words);
+        assert_eq!(words, [4, 3, 2, 1, 8, 7, 6, 5]);
+        XenosEndian::Swap8In32.swap_in_place(&mut words);
+        XenosEndian::Swap16In32.swap_in_place(&mut words);
+        assert_eq!(words, [3, 4, 1, 2, 7, 8, 5, 6]);
+
+        // 32-bit texels are swapped on their own, 16-bit ones in pairs
+        for format in [
+            XenosSurfaceFormat::k_32_FLOAT,
+            XenosSurfaceFormat::k_16_FLOAT,
+        ] {
+            let desc = SurfaceDesc::builder(format)
+                .width(64)
+                .height(64)
+                .mip_count(4)
+                .build()
+                .unwrap();
+            let linear = (0..Xbox360::linear_size(&desc).unwrap())
+                .map(|i| (i * 5 + i / 256) as u8)
+                .collect::<Vec<u8>>();
+            let size = Xbox360::swizzled_size(&desc).unwrap();
+
+            let mut unswapped = vec![0u8; size];
+            Xbox360::swizzle_surface_with_endian(
+                &linear,
+                &mut unswapped,
+                &desc,
+                true,
+                XenosEndian::None,
+            )
+            .unwrap();
+
+            for endian in [XenosEndian::Swap8In32, XenosEndian::Swap16In32] {
+                let mut swizzled = vec![0u8; size];
+                Xbox360::swizzle_surface_with_endian(&linear, &mut swizzled, &desc, true, endian)
+                    .unwrap();
+                let mut expected = unswapped.clone();
+                endian.swap_in_place(&mut expected);
+                assert!(swizzled == expected);
+
+                let mut result = vec![0u8; linear.len()];
+                Xbox360::deswizzle_surface_with_endian(&swizzled, &mut result, &desc, true, endian)
+                    .unwrap();
+                assert!(result == linear);
+            }
+        }
+
+        // Texels smaller than a word are swapped with their neighbours in the tile, which aren't
+        // their neighbours in the rows of an odd width, nor in a 1x1 level
+        for (format, (width, height), mip_count, endian) in [
+            (XenosSurfaceFormat::k_8, (33, 32), 1, XenosEndian::Swap8In16),
+            (
+                XenosSurfaceFormat::k_16,
+                (33, 32),
+                1,
+                XenosEndian::Swap8In32,
+            ),
+            (XenosSurfaceFormat::k_16, (4, 4), 3, XenosEndian::Swap8In32),
+            (XenosSurfaceFormat::k_8, (64, 64), 7, XenosEndian::Swap8In16),
+        ] {
+            let desc = SurfaceDesc::builder(format)
+                .width(width)
+                .height(height)
+                .mip_count(mip_count)
+                .build()
+                .unwrap();
+            let linear = (0..Xbox360::linear_size(&desc).unwrap())
+                .map(|i| (i * 7 + i / 256) as u8)
+                .collect::<Vec<u8>>();
+
+            let mut swapped = vec![0u8; Xbox360::swizzled_size(&desc).unwrap()];
+            Xbox360::swizzle_surface_with_endian(
+                &linear,
+                &mut swapped,
+                &desc,
+                true,
+                XenosEndian::None,
+            )
+            .unwrap();
+            endian.swap_in_place(&mut swapped);
+
+            let mut swizzled = vec![0u8; swapped.len()];
+            Xbox360::swizzle_surface_with_endian(&linear, &mut swizzled, &desc, true, endian)
+                .unwrap();
+            assert!(swizzled == swapped, "{format:?} {width}x{height}");
+
+            let mut result = vec![0u8; linear.len()];
+            Xbox360::deswizzle_surface_with_endian(&swapped, &mut result, &desc, true, endian)
+                .unwrap();
+            assert!(result == linear, "{format:?} {width}x{height}");
+        }
+
+        // Only the image is swapped, not the rest of the buffer
+        let desc = SurfaceDesc::builder(XenosSurfaceFormat::k_DXT1)
+            .width(64)
+            .height(64)
+            .build()
+            .unwrap();
+        let size = Xbox360::linear_size(&desc).unwrap();
+        let swizzled = vec![0u8; Xbox360::swizzled_size(&desc).unwrap()];
+        let mut dest = (0..size + 8).map(|i| i as u8).collect::<Vec<u8>>();
+        Xbox360::deswizzle(&swizzled, &mut dest, (64, 64, 1), desc.format(), false).unwrap();
+        assert_eq!(
+            dest[size..],
+            [0, 1, 2, 3, 4, 5, 6, 7].map(|i| (size + i) as u8)
+        );
+    }
+
+    #[test]
+    fn xenos_component_swizzle() {
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{
+            Deswizzler, SurfaceDesc,
+            xbox::{Xbox360, XenosComponent::*, XenosEndian, XenosSwizzle},
+        };
+
+        let swizzle = XenosSwizzle([Z, Y, X, One]);
+        assert_eq!(XenosSwizzle::from_bits(swizzle.to_bits()), swizzle);
+        assert_eq!(XenosSwizzle::from_bits(0x688), XenosSwizzle::IDENTITY);
+
+        let mut texels = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        swizzle.apply(&mut texels, XenosSurfaceFormat::k_8_8_8_8);
+        assert_eq!(texels, [3, 2, 1, 0xFF, 7, 6, 5, 0xFF]);
+
+        // Only the components of the format are written
+        let mut texels = 0x001Fu16.to_le_bytes();
+        swizzle.apply(&mut texels, XenosSurfaceFormat::k_5_6_5);
+        assert_eq!(u16::from_le_bytes(texels), 0xF800);
+
+        // Components are rescaled to the width they are moved to
+        let mut texels = 0xFC00u16.to_le_bytes();
+        swizzle.apply(&mut texels, XenosSurfaceFormat::k_6_5_5);
+        assert_eq!(u16::from_le_bytes(texels), 0x001F);
+        let mut texels = 0x000Fu16.to_le_bytes();
+        XenosSwizzle([Keep, X, X, Zero]).apply(&mut texels, XenosSurfaceFormat::k_1_5_5_5);
+        assert_eq!(u16::from_le_bytes(texels), 0x3DEF);
+
+        let mut texels = [0x00, 0x40, 0x12, 0x34];
+        XenosSwizzle([X, One, Keep, Keep]).apply(&mut texels, XenosSurfaceFormat::k_16_16_FLOAT);
+        assert_eq!(texels, [0x00, 0x40, 0x00, 0x3C]);
+
+        // Compressed blocks are left alone
+        let mut blocks = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        swizzle.apply(&mut blocks, XenosSurfaceFormat::k_DXT1);
+        assert_eq!(blocks, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Deswizzling DXT blocks only swaps their byte order
+        let format = XenosSurfaceFormat::k_DXT1;
+        let desc = SurfaceDesc::builder(format)
+            .width(128)
+            .height(128)
+            .build()
+            .unwrap();
+        let source: Vec<u8> = (0..128 * 128 / 2)
+            .map(|i| (i * 7 + i / 251) as u8)
+            .collect();
+        let mut expected = vec![0u8; source.len()];
+        Xbox360::deswizzle_surface_with_endian(
+            &source,
+            &mut expected,
+            &desc,
+            true,
+            XenosEndian::None,
+        )
+        .unwrap();
+        Xbox360::swap_byte_order_in_place(&mut expected);
+        let mut dest = vec![0u8; source.len()];
+        Xbox360::deswizzle_surface(&source, &mut dest, &desc).unwrap();
+        assert!(dest == expected);
+    }
+
+    #[test]
+    fn gcm_remap() {
+        use crate::gtf::{self, GcmTexture, Gtf};
+        use crate::swizzle::{
+            SurfaceDesc,
+            ps::{GcmComponent::*, GcmRemap},
+        };
+
+        assert_eq!(GcmRemap::from_bits(0xAAE4), GcmRemap::IDENTITY);
+        assert_eq!(GcmRemap::IDENTITY.to_bits(), 0xAAE4);
+        let opaque = GcmRemap::from_bits(0xA9E4);
+        assert_eq!(opaque, GcmRemap([One, R, G, B]));
+        let bgr = GcmRemap([A, B, G, R]);
+        assert_eq!(GcmRemap::from_bits(bgr.to_bits()), bgr);
+
+        let mut texels = [0x10, 0x20, 0x30, 0x40];
+        opaque.apply(&mut texels, A8R8G8B8);
+        assert_eq!(texels, [0xFF, 0x20, 0x30, 0x40]);
+        bgr.apply(&mut texels, A8R8G8B8);
+        assert_eq!(texels, [0xFF, 0x40, 0x30, 0x20]);
+
+        let mut texels = [0x12, 0x34];
+        GcmRemap([A, R, B, G]).apply(&mut texels, G8B8);
+        assert_eq!(texels, [0x34, 0x12]);
+
+        // R5G6B5 has no alpha, which reads as one
+        let mut texels = [0x00, 0x00];
+        GcmRemap([Zero, A, Zero, Zero]).apply(&mut texels, R5G6B5);
+        assert_eq!(texels, [0xF8, 0x00]);
+
+        // Compressed blocks are left alone
+        let mut blocks = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        bgr.apply(&mut blocks, COMPRESSED_DXT1);
+        assert_eq!(blocks, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // GTF textures are remapped as they are deswizzled
+        let desc = SurfaceDesc::builder(A8R8G8B8)
+            .width(8)
+            .height(8)
+            .build()
+            .unwrap();
+        let texture = GcmTexture {
+            remap: 0xA9E4,
+            ..GcmTexture::from_desc(&desc, true).unwrap()
+        };
+        let data = [0x10u8; 8 * 8 * 4];
+        let mut file = vec![0u8; gtf::file_size(&[(texture, &data)])];
+        gtf::write(&mut file, &[(texture, &data)]).unwrap();
+
+        let mut result = [0u8; 8 * 8 * 4];
+        Gtf::read(&file)
+            .unwrap()
+            .texture(0)
+            .unwrap()
+            .deswizzle(&mut result)
+            .unwrap();
+        for texel in result.chunks_exact(4) {
+            assert_eq!(texel, [0xFF, 0x10, 0x10, 0x10]);
+        }
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_bc1_to_bc5() {
+        use crate::decode::{self, BcFormat};
+        use crate::swizzle::{SwizzleError, TextureSlice};
+
+        // Red and blue, so 4 colors
+        let bc1 = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode::decode_block_rgba8(&bc1, BcFormat::Bc1).unwrap();
+        assert_eq!(
+            texels[..4],
+            [
+                [255, 0, 0, 255],
+                [0, 0, 255, 255],
+                [170, 0, 85, 255],
+                [85, 0, 170, 255]
+            ]
+        );
+
+        // Blue and red, so 3 colors and transparent black
+        let bc1 = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode::decode_block_rgba8(&bc1, BcFormat::Bc1).unwrap();
+        assert_eq!(
+            texels[..4],
+            [
+                [0, 0, 255, 255],
+                [255, 0, 0, 255],
+                [128, 0, 128, 255],
+                [0, 0, 0, 0]
+            ]
+        );
+
+        let mut bc2 = [0xFF; 16];
+        bc2[..8].copy_from_slice(&0x0000_0000_0000_F730u64.to_le_bytes());
+        bc2[12..].fill(0);
+        let texels = decode::decode_block_rgba8(&bc2, BcFormat::Bc2).unwrap();
+        assert_eq!(
+            texels[..5].iter().map(|t| t[3]).collect::<Vec<_>>(),
+            [0, 51, 119, 255, 0]
+        );
+
+        // Interpolating 8 alpha values, then 6 and the extremes
+        let mut bc3 = [
+            0xFF, 0x00, 0x88, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0,
+        ];
+        let texels = decode::decode_block_rgba8(&bc3, BcFormat::Bc3).unwrap();
+        assert_eq!(
+            texels[..3],
+            [[255; 4], [255, 255, 255, 0], [255, 255, 255, 219]]
+        );
+        bc3[..8].copy_from_slice(&[0x00, 0xFF, 0x88, 0x7C, 0, 0, 0, 0]);
+        let texels = decode::decode_block_rgba8(&bc3, BcFormat::Bc3).unwrap();
+        assert_eq!(
+            texels[..5].iter().map(|t| t[3]).collect::<Vec<_>>(),
+            [0, 255, 51, 0, 255]
+        );
+
+        let bc4 = [0x81, 0x7F, 0x08, 0, 0, 0, 0, 0];
+        let texels = decode::decode_block_rgba8(&bc4, BcFormat::Bc4Signed).unwrap();
+        assert_eq!(texels[..2], [[0, 128, 128, 255], [255, 128, 128, 255]]);
+        let texels = decode::decode_block_rgba32f(&bc4, BcFormat::Bc4Signed).unwrap();
+        assert_eq!(texels[..2], [[-1.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0]]);
+        let texels = decode::decode_block_rgba8(&bc4, BcFormat::Bc4).unwrap();
+        assert_eq!(texels[..2], [[0x81, 0, 0, 255], [0x7F, 0, 0, 255]]);
+
+        let bc5 = [0x40, 0x40, 0, 0, 0, 0, 0, 0, 0x80, 0x80, 0, 0, 0, 0, 0, 0];
+        let texels = decode::decode_block_rgba8(&bc5, BcFormat::Bc5).unwrap();
+        assert_eq!(texels, [[0x40, 0x80, 0, 255]; 16]);
+        let texels = decode::decode_block_rgba16f(&bc5[..8], BcFormat::Bc5);
+        assert!(matches!(
+            texels,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Source))
+        ));
+
+        assert_eq!(BcFormat::try_from(COMPRESSED_DXT45).unwrap(), BcFormat::Bc3);
+        assert_eq!(BcFormat::try_from(BC6).unwrap(), BcFormat::Bc6h);
+        assert!(BcFormat::try_from(A8R8G8B8).is_err());
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_bc7() {
+        use crate::decode::{self, BcFormat};
+        use crate::swizzle::{SwizzleError, TextureSlice};
+
+        // Mode 6: endpoints (127, 0, 0, 127) and 0 with P-bits 1 and 0
+        let block = (1u128 << 6) | (127 << 7) | (127 << 49) | (1 << 63) | (8 << 68) | (15 << 124);
+        let texels = decode::decode_block_rgba8(&block.to_le_bytes(), BcFormat::Bc7).unwrap();
+        assert_eq!(texels[0], [255, 1, 1, 255]);
+        assert_eq!(texels[1], [120, 0, 0, 120]);
+        assert_eq!(texels[2], [255, 1, 1, 255]);
+        assert_eq!(texels[15], [0, 0, 0, 0]);
+
+        // Reserved mode
+        let texels = decode::decode_block_rgba8(&[0; 16], BcFormat::Bc7).unwrap();
+        assert_eq!(texels, [[0; 4]; 16]);
+
+        // The top left of the reference image is empty space
+        let source = include_bytes!("../testdata/ps4-bc7-900x1080-unswizzled.bin");
+        let mut dest = vec![0u8; 900 * 1080 * 4];
+        let size = decode::decode_rgba8(source, &mut dest, (900, 1080, 1), BcFormat::Bc7).unwrap();
+        assert_eq!(size, dest.len());
+        assert_eq!(dest[..4], [0, 0, 0, 0]);
+        let result = decode::decode_rgba8(source, &mut dest[1..], (900, 1080, 1), BcFormat::Bc7);
+        assert!(matches!(
+            result,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Dest))
+        ));
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_bc6h() {
+        use crate::decode::{self, BcFormat};
+
+        // Mode 11, from 0 to the largest endpoint
+        let mut block = 0b00011u128 | (1023 << 35) | (1023 << 45) | (1023 << 55);
+        for texel in 1..16 {
+            block |= 15 << (68 + (texel - 1) * 4);
+        }
+        let texels = decode::decode_block_rgba16f(&block.to_le_bytes(), BcFormat::Bc6h).unwrap();
+        assert_eq!(texels[0], [0, 0, 0, 0x3C00]);
+        assert_eq!(texels[1], [0x7BFF, 0x7BFF, 0x7BFF, 0x3C00]);
+        let texels = decode::decode_block_rgba32f(&block.to_le_bytes(), BcFormat::Bc6h).unwrap();
+        assert_eq!(texels[1], [65504.0, 65504.0, 65504.0, 1.0]);
+        let texels = decode::decode_block_rgba8(&block.to_le_bytes(), BcFormat::Bc6h).unwrap();
+        assert_eq!(texels[1], [255; 4]);
+
+        // The second endpoints are -512, the smallest 10 bit value
+        let block = 0b00011u128 | (0x200 << 35) | (0x200 << 45) | (0x200 << 55) | (15 << 68);
+        let texels = decode::decode_block_rgba16f(&block.to_le_bytes(), BcFormat::Bc6hSigned);
+        assert_eq!(texels.unwrap()[1], [0xFBFF, 0xFBFF, 0xFBFF, 0x3C00]);
+
+        // Mode 1, with the second endpoint of the first region 1 less in red
+        let block = (1023u128 << 5) | (0x1F << 35) | (7 << 84);
+        let texels = decode::decode_block_rgba16f(&block.to_le_bytes(), BcFormat::Bc6h).unwrap();
+        assert_eq!(texels[0], [0x7BFF, 0, 0, 0x3C00]);
+        assert_eq!(texels[1], [0x7BD1, 0, 0, 0x3C00]);
+
+        // Reserved mode
+        let texels = decode::decode_block_rgba16f(&[0x13; 16], BcFormat::Bc6h).unwrap();
+        assert_eq!(texels, [[0, 0, 0, 0x3C00]; 16]);
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_image() {
+        use crate::decode::{self, BcFormat};
+        use crate::swizzle::{SwizzleError, TextureSlice};
+
+        // 6x6 texels in 2x2 blocks of red, green, blue and white
+        let blocks = [0xF800u16, 0x07E0, 0x001F, 0xFFFF];
+        let mut source = [0u8; 4 * 8];
+        for (block, color) in source.chunks_exact_mut(8).zip(blocks) {
+            block[..2].copy_from_slice(&color.to_le_bytes());
+            block[2..4].copy_from_slice(&color.to_le_bytes());
+        }
+        let mut dest = [0u8; 6 * 6 * 4];
+        let size = decode::decode_rgba8(&source, &mut dest, (6, 6, 1), BcFormat::Bc1).unwrap();
+        assert_eq!(size, dest.len());
+        let texel = |x: usize, y: usize| &dest[(y * 6 + x) * 4..][..4];
+        assert_eq!(texel(3, 3), [255, 0, 0, 255]);
+        assert_eq!(texel(4, 3), [0, 255, 0, 255]);
+        assert_eq!(texel(3, 4), [0, 0, 255, 255]);
+        assert_eq!(texel(5, 5), [255; 4]);
+
+        let mut dest = [0u8; 6 * 6 * 8];
+        decode::decode_rgba16f(&source, &mut dest, (6, 6, 1), BcFormat::Bc1).unwrap();
+        assert_eq!(dest[..8], [0x00, 0x3C, 0, 0, 0, 0, 0x00, 0x3C]);
+
+        let result = decode::decode_rgba8(&source[..24], &mut dest, (6, 6, 1), BcFormat::Bc1);
+        assert!(matches!(
+            result,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Source))
+        ));
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn gcm_dxgi_conversion_matches_decode() {
+        use crate::decode;
+        use crate::format::{DxgiFormat, GcmSurfaceFormat};
+        use crate::swizzle::Format;
+
+        let mut texel = [0x11, 0x22, 0x33, 0x44];
+        assert_eq!(
+            decode::decode_texel_rgba8(&texel, A8R8G8B8).unwrap(),
+            [0x22, 0x33, 0x44, 0x11]
+        );
+        A8R8G8B8.swap_byte_order_in_place(&mut texel).unwrap();
+        assert_eq!(
+            decode::decode_texel_rgba8(&texel, DxgiFormat::B8G8R8A8_UNORM).unwrap(),
+            [0x22, 0x33, 0x44, 0x11]
+        );
+
+        let mut checked = 0;
+        for &format in GcmSurfaceFormat::ALL {
+            let Ok(dxgi) = DxgiFormat::try_from(format) else {
+                continue;
+            };
+            if format.is_compressed() {
+                continue;
+            }
+            // Small values, so no float is a NaN
+            let mut texel: [u8; 16] = core::array::from_fn(|i| (0x11 + i as u8 * 0x0D) & 0x3F);
+            let texel = &mut texel[..format.block_size().unwrap()];
+            let Ok(expected) = decode::decode_texel_rgba32f(texel, format) else {
+                continue;
+            };
+            format.swap_byte_order_in_place(texel).unwrap();
+            let rgba = decode::decode_texel_rgba32f(texel, dxgi).unwrap();
+
+            // GCM repeats the components of formats with fewer than four
+            let channels = match dxgi {
+                DxgiFormat::R8_UNORM | DxgiFormat::R16_UNORM | DxgiFormat::R32_FLOAT => 1,
+                DxgiFormat::R8G8_UNORM | DxgiFormat::R16G16_UNORM | DxgiFormat::R16G16_FLOAT => 2,
+                _ => 4,
+            };
+            assert_eq!(rgba[..channels], expected[..channels], "{format:?}");
+            assert_eq!(GcmSurfaceFormat::try_from(dxgi).unwrap(), format);
+            checked += 1;
+        }
+        assert_eq!(checked, 13);
+
+        assert!(DxgiFormat::try_from(DEPTH24_D8).is_err());
+        assert!(GcmSurfaceFormat::try_from(DxgiFormat::R8G8B8A8_UNORM).is_err());
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_texels() {
+        use crate::decode;
+        use crate::format::{DxgiFormat, XenosSurfaceFormat};
+        use crate::gnf::TextureDescriptor;
+        use crate::swizzle::{SurfaceDesc, SwizzleError, TextureSlice};
+
+        let one_11_11_10 = 0x3C0u32 | (0x3C0 << 11) | (0x1E0 << 22);
+        let texel = decode::decode_texel_rgba32f(&one_11_11_10.to_le_bytes(), Format10_11_11);
+        assert_eq!(texel.unwrap(), [1.0; 4]);
+
+        let shared_exponent = (256u32 | (128 << 9) | (16 << 27)).to_le_bytes();
+        let texel = decode::decode_texel_rgba32f(&shared_exponent, Format5_9_9_9);
+        assert_eq!(texel.unwrap(), [1.0, 0.5, 0.0, 1.0]);
+        let texel = decode::decode_texel_rgba32f(&shared_exponent, DxgiFormat::R9G9B9E5_SHAREDEXP);
+        assert_eq!(texel.unwrap(), [1.0, 0.5, 0.0, 1.0]);
+
+        // X is the 2-bit channel
+        let texel = (3u32 | (1023 << 2)).to_le_bytes();
+        let texel = decode::decode_texel_rgba8(&texel, Format10_10_10_2).unwrap();
+        assert_eq!(texel, [255, 255, 0, 0]);
+
+        let texel = [0x00, 0x3C, 0x00, 0x38, 0x00, 0x00, 0x00, 0xBC];
+        let format = XenosSurfaceFormat::k_16_16_16_16_FLOAT;
+        let rgba = decode::decode_texel_rgba32f(&texel, format).unwrap();
+        assert_eq!(rgba, [1.0, 0.5, 0.0, -1.0]);
+        assert_eq!(
+            decode::decode_texel_rgba8(&texel, format).unwrap(),
+            [255, 128, 0, 0]
+        );
+
+        let texel = decode::decode_texel_rgba8(&[0xF8, 0x42], A4R4G4B4).unwrap();
+        assert_eq!(texel, [136, 68, 34, 255]);
+        assert_eq!(decode::texel_size(W32_Z32_Y32_X32_FLOAT).unwrap(), 16);
+
+        let texel = decode::decode_texel_rgba8(&[0x00, 0xF8], DxgiFormat::B5G6R5_UNORM).unwrap();
+        assert_eq!(texel, [255, 0, 0, 255]);
+        let texel = 0x05FF_FFFFu32.to_le_bytes();
+        let texel = decode::decode_texel_rgba32f(&texel, DxgiFormat::D24_UNORM_S8_UINT).unwrap();
+        assert_eq!(texel, [1.0, 5.0, 0.0, 1.0]);
+        let texel = [0x81, 0x7F];
+        let rgba = decode::decode_texel_rgba32f(&texel, DxgiFormat::R8G8_SNORM).unwrap();
+        assert_eq!(rgba, [-1.0, 1.0, 0.0, 1.0]);
+        let rgba = decode::decode_texel_rgba8(&texel, DxgiFormat::R8G8_SNORM).unwrap();
+        assert_eq!(rgba, [0, 255, 0, 255]);
+        let texel = (0x180u32 | (0x180 << 10) | (0x180 << 20) | (3 << 30)).to_le_bytes();
+        let format = DxgiFormat::R10G10B10_XR_BIAS_A2_UNORM;
+        let rgba = decode::decode_texel_rgba32f(&texel, format).unwrap();
+        assert_eq!(rgba, [0.0, 0.0, 0.0, 1.0]);
+
+        // GNF descriptors carry the numeric format
+        let desc = SurfaceDesc::builder(Format8_8_8_8)
+            .width(4)
+            .height(4)
+            .build()
+            .unwrap();
+        let snorm = TextureDescriptor {
+            num_format: 1,
+            ..TextureDescriptor::from_desc(&desc).unwrap()
+        };
+        let rgba = decode::decode_texel_rgba32f(&[0x81, 0x00, 0x7F, 0x7F], snorm).unwrap();
+        assert_eq!(rgba, [-1.0, 0.0, 1.0, 1.0]);
+
+        let result = decode::decode_texel_rgba8(&[0; 8], BC1);
+        assert!(matches!(result, Err(SwizzleError::UnsupportedFormat(0x23))));
+        let result = decode::decode_texel_rgba8(&[0; 3], Format8_8_8_8);
+        assert!(matches!(
+            result,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Source))
+        ));
+
+        let source = include_bytes!("../testdata/ps3-rgba8-64x64-unswizzled.bin");
+        let mut dest = vec![0u8; 64 * 64 * 4];
+        let size = decode::decode_texels_rgba8(source, &mut dest, (64, 64, 1), A8R8G8B8).unwrap();
+        assert_eq!(size, dest.len());
+        assert_eq!(dest[..4], [source[1], source[2], source[3], source[0]]);
+        let result = decode::decode_texels_rgba32f(source, &mut dest, (64, 64, 1), A8R8G8B8);
+        assert!(matches!(
+            result,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Dest))
+        ));
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn decode_xenos_blocks() {
+        use crate::decode::{self, BcFormat};
+        use crate::format::XenosSurfaceFormat;
+        use crate::swizzle::{Format, SwizzleError, TextureSlice};
+
+        let ctx1 = XenosSurfaceFormat::k_CTX1;
+        assert_eq!(ctx1.block_size().unwrap(), 8);
+        assert_eq!(ctx1.pixel_block_size(), 4);
+        assert!(ctx1.is_compressed());
+        assert_eq!(XenosSurfaceFormat::k_DXT5A.block_size().unwrap(), 8);
+        assert_eq!(XenosSurfaceFormat::k_DXN.pixel_block_size(), 4);
+        assert_eq!(BcFormat::try_from(ctx1).unwrap(), BcFormat::Ctx1);
+        assert_eq!(
+            BcFormat::try_from(XenosSurfaceFormat::k_DXT5A).unwrap(),
+            BcFormat::Bc4
+        );
+
+        // X from 255 to 0, and Y from 0 to 255
+        let block = [0xFF, 0x00, 0x00, 0xFF, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode::decode_block_rgba8(&block, BcFormat::Ctx1).unwrap();
+        assert_eq!(
+            texels[..4],
+            [
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [170, 85, 0, 255],
+                [85, 170, 0, 255]
+            ]
+        );
+
+        let mut bc5 = [0u8; 16];
+        assert_eq!(decode::ctx1_to_bc5(&block, &mut bc5).unwrap(), 16);
+        let converted = decode::decode_block_rgba8(&bc5, BcFormat::Bc5).unwrap();
+        for (texel, converted) in texels.iter().zip(converted) {
+            for (&channel, converted) in texel.iter().zip(converted) {
+                assert!(channel.abs_diff(converted) < 13);
+            }
+        }
+        let result = decode::ctx1_to_bc5(&block, &mut bc5[..15]);
+        assert!(matches!(
+            result,
+            Err(SwizzleError::OutOfBounds(TextureSlice::Dest))
+        ));
+
+        let block = 0xA5F730u64.to_le_bytes();
+        let texels = decode::decode_block_rgba8(&block, BcFormat::Dxt3a).unwrap();
+        assert_eq!(
+            texels[..4],
+            [
+                [0, 0, 0, 255],
+                [51, 0, 0, 255],
+                [119, 0, 0, 255],
+                [255, 0, 0, 255]
+            ]
+        );
+        let texels = decode::decode_block_rgba8(&block, BcFormat::Dxt3aAs1111).unwrap();
+        assert_eq!(texels[4..6], [[255, 0, 255, 0], [0, 255, 0, 255]]);
+    }
 }
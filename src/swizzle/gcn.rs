@@ -0,0 +1,727 @@
+// GCN (Southern/Sea Islands) surface addressing, adapted from AMD's addrlib
+// https://github.com/GPUOpen-Drivers/pal/tree/dev/src/core/imported/addrlib
+//
+// Both Durango (Xbox One) and Liverpool (PS4) use the Sea Islands tile mode table, so the tile mode
+// index stored in an Xbox One DDS header and in a GNF T# descriptor mean the same thing.
+
+use super::{Format, LayerLayout, MipChain, SurfaceDesc, SwizzleError};
+
+const MICRO_TILE_WIDTH: usize = 8;
+const MICRO_TILE_HEIGHT: usize = 8;
+const MICRO_TILE_PIXELS: usize = MICRO_TILE_WIDTH * MICRO_TILE_HEIGHT;
+const PIPE_INTERLEAVE_BYTES: usize = 256;
+const ROW_SIZE: usize = 1024;
+
+/// Index into the Sea Islands tile mode table (`GB_TILE_MODE0..31`).
+#[allow(non_camel_case_types)]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileMode {
+    Depth_2dThin_64 = 0x00,
+    Depth_2dThin_128 = 0x01,
+    Depth_2dThin_256 = 0x02,
+    Depth_2dThin_512 = 0x03,
+    Depth_2dThin_1K = 0x04,
+    Depth_1dThin = 0x05,
+    Depth_2dThinPrt_256 = 0x06,
+    Depth_2dThinPrt_1K = 0x07,
+    Display_LinearAligned = 0x08,
+    Display_1dThin = 0x09,
+    Display_2dThin = 0x0A,
+    Display_ThinPrt = 0x0B,
+    Display_2dThinPrt = 0x0C,
+    Thin_1dThin = 0x0D,
+    Thin_2dThin = 0x0E,
+    Thin_3dThin = 0x0F,
+    Thin_ThinPrt = 0x10,
+    Thin_2dThinPrt = 0x11,
+    Thin_3dThinPrt = 0x12,
+    Thick_1dThick = 0x13,
+    Thick_2dThick = 0x14,
+    Thick_3dThick = 0x15,
+    Thick_ThickPrt = 0x16,
+    Thick_2dThickPrt = 0x17,
+    Thick_3dThickPrt = 0x18,
+    Thick_2dXThick = 0x19,
+    Thick_3dXThick = 0x1A,
+    Display_LinearGeneral = 0x1F,
+}
+
+impl TryFrom<u32> for TileMode {
+    type Error = SwizzleError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x00 => TileMode::Depth_2dThin_64,
+            0x01 => TileMode::Depth_2dThin_128,
+            0x02 => TileMode::Depth_2dThin_256,
+            0x03 => TileMode::Depth_2dThin_512,
+            0x04 => TileMode::Depth_2dThin_1K,
+            0x05 => TileMode::Depth_1dThin,
+            0x06 => TileMode::Depth_2dThinPrt_256,
+            0x07 => TileMode::Depth_2dThinPrt_1K,
+            0x08 => TileMode::Display_LinearAligned,
+            0x09 => TileMode::Display_1dThin,
+            0x0A => TileMode::Display_2dThin,
+            0x0B => TileMode::Display_ThinPrt,
+            0x0C => TileMode::Display_2dThinPrt,
+            0x0D => TileMode::Thin_1dThin,
+            0x0E => TileMode::Thin_2dThin,
+            0x0F => TileMode::Thin_3dThin,
+            0x10 => TileMode::Thin_ThinPrt,
+            0x11 => TileMode::Thin_2dThinPrt,
+            0x12 => TileMode::Thin_3dThinPrt,
+            0x13 => TileMode::Thick_1dThick,
+            0x14 => TileMode::Thick_2dThick,
+            0x15 => TileMode::Thick_3dThick,
+            0x16 => TileMode::Thick_ThickPrt,
+            0x17 => TileMode::Thick_2dThickPrt,
+            0x18 => TileMode::Thick_3dThickPrt,
+            0x19 => TileMode::Thick_2dXThick,
+            0x1A => TileMode::Thick_3dXThick,
+            0x1F => TileMode::Display_LinearGeneral,
+            e => return Err(SwizzleError::InvalidTileMode(e)),
+        })
+    }
+}
+
+/// Hardware array mode (`ADDR_TM_*`) a tile mode resolves to.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrayMode {
+    LinearGeneral,
+    LinearAligned,
+    Tiled1dThin1,
+    Tiled1dThick,
+    Tiled2dThin1,
+    Tiled2dThick,
+    Tiled2dXThick,
+    Tiled3dThin1,
+    Tiled3dThick,
+    Tiled3dXThick,
+    PrtTiledThin1,
+    PrtTiledThick,
+    PrtTiled2dThin1,
+    PrtTiled2dThick,
+    PrtTiled3dThin1,
+    PrtTiled3dThick,
+}
+
+impl ArrayMode {
+    pub fn thickness(&self) -> usize {
+        match self {
+            ArrayMode::Tiled1dThick
+            | ArrayMode::Tiled2dThick
+            | ArrayMode::Tiled3dThick
+            | ArrayMode::PrtTiledThick
+            | ArrayMode::PrtTiled2dThick
+            | ArrayMode::PrtTiled3dThick => 4,
+            ArrayMode::Tiled2dXThick | ArrayMode::Tiled3dXThick => 8,
+            _ => 1,
+        }
+    }
+
+    pub fn is_linear(&self) -> bool {
+        matches!(self, ArrayMode::LinearGeneral | ArrayMode::LinearAligned)
+    }
+
+    pub fn is_micro_tiled(&self) -> bool {
+        matches!(self, ArrayMode::Tiled1dThin1 | ArrayMode::Tiled1dThick)
+    }
+
+    pub fn is_macro_tiled(&self) -> bool {
+        !self.is_linear() && !self.is_micro_tiled()
+    }
+
+    /// PRT modes that don't rotate pipes/banks between macro tiles
+    pub fn is_prt_no_rotation(&self) -> bool {
+        matches!(self, ArrayMode::PrtTiledThin1 | ArrayMode::PrtTiledThick)
+    }
+
+    fn degrade_to_1d(&self) -> ArrayMode {
+        if self.thickness() > 1 {
+            ArrayMode::Tiled1dThick
+        } else {
+            ArrayMode::Tiled1dThin1
+        }
+    }
+}
+
+/// Ordering of elements within a single 8x8 micro tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MicroTileMode {
+    Display,
+    Thin,
+    Depth,
+    Thick,
+}
+
+impl TileMode {
+    pub fn array_mode(&self) -> ArrayMode {
+        match self {
+            TileMode::Depth_2dThin_64
+            | TileMode::Depth_2dThin_128
+            | TileMode::Depth_2dThin_256
+            | TileMode::Depth_2dThin_512
+            | TileMode::Depth_2dThin_1K
+            | TileMode::Display_2dThin
+            | TileMode::Thin_2dThin => ArrayMode::Tiled2dThin1,
+            TileMode::Depth_1dThin | TileMode::Display_1dThin | TileMode::Thin_1dThin => {
+                ArrayMode::Tiled1dThin1
+            }
+            TileMode::Depth_2dThinPrt_256
+            | TileMode::Depth_2dThinPrt_1K
+            | TileMode::Display_2dThinPrt
+            | TileMode::Thin_2dThinPrt => ArrayMode::PrtTiled2dThin1,
+            TileMode::Display_LinearAligned => ArrayMode::LinearAligned,
+            TileMode::Display_LinearGeneral => ArrayMode::LinearGeneral,
+            TileMode::Display_ThinPrt | TileMode::Thin_ThinPrt => ArrayMode::PrtTiledThin1,
+            TileMode::Thin_3dThin => ArrayMode::Tiled3dThin1,
+            TileMode::Thin_3dThinPrt => ArrayMode::PrtTiled3dThin1,
+            TileMode::Thick_1dThick => ArrayMode::Tiled1dThick,
+            TileMode::Thick_2dThick => ArrayMode::Tiled2dThick,
+            TileMode::Thick_3dThick => ArrayMode::Tiled3dThick,
+            TileMode::Thick_ThickPrt => ArrayMode::PrtTiledThick,
+            TileMode::Thick_2dThickPrt => ArrayMode::PrtTiled2dThick,
+            TileMode::Thick_3dThickPrt => ArrayMode::PrtTiled3dThick,
+            TileMode::Thick_2dXThick => ArrayMode::Tiled2dXThick,
+            TileMode::Thick_3dXThick => ArrayMode::Tiled3dXThick,
+        }
+    }
+
+    pub fn micro_tile_mode(&self) -> MicroTileMode {
+        match self {
+            TileMode::Depth_2dThin_64
+            | TileMode::Depth_2dThin_128
+            | TileMode::Depth_2dThin_256
+            | TileMode::Depth_2dThin_512
+            | TileMode::Depth_2dThin_1K
+            | TileMode::Depth_1dThin
+            | TileMode::Depth_2dThinPrt_256
+            | TileMode::Depth_2dThinPrt_1K => MicroTileMode::Depth,
+            TileMode::Display_LinearAligned
+            | TileMode::Display_1dThin
+            | TileMode::Display_2dThin
+            | TileMode::Display_ThinPrt
+            | TileMode::Display_2dThinPrt
+            | TileMode::Display_LinearGeneral => MicroTileMode::Display,
+            TileMode::Thin_1dThin
+            | TileMode::Thin_2dThin
+            | TileMode::Thin_3dThin
+            | TileMode::Thin_ThinPrt
+            | TileMode::Thin_2dThinPrt
+            | TileMode::Thin_3dThinPrt => MicroTileMode::Thin,
+            TileMode::Thick_1dThick
+            | TileMode::Thick_2dThick
+            | TileMode::Thick_3dThick
+            | TileMode::Thick_ThickPrt
+            | TileMode::Thick_2dThickPrt
+            | TileMode::Thick_3dThickPrt
+            | TileMode::Thick_2dXThick
+            | TileMode::Thick_3dXThick => MicroTileMode::Thick,
+        }
+    }
+
+    /// Tile split size in bytes, only meaningful for depth tile modes
+    pub fn tile_split_bytes(&self) -> Option<usize> {
+        match self {
+            TileMode::Depth_2dThin_64 => Some(64),
+            TileMode::Depth_2dThin_128 => Some(128),
+            TileMode::Depth_2dThin_256 | TileMode::Depth_2dThinPrt_256 => Some(256),
+            TileMode::Depth_2dThin_512 => Some(512),
+            TileMode::Depth_2dThin_1K | TileMode::Depth_2dThinPrt_1K => Some(1024),
+            _ => None,
+        }
+    }
+}
+
+/// Pipe configuration (`ADDR_PIPECFG_*`) of the GPU the surface was tiled for.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipeConfig {
+    P2,
+    P4_8x16,
+    P4_16x16,
+    P4_16x32,
+    P4_32x32,
+    P8_16x32_8x16,
+    P8_32x32_8x16,
+    P8_16x32_16x16,
+    P8_32x32_16x16,
+    P8_32x64_32x32,
+}
+
+impl PipeConfig {
+    pub fn num_pipes(&self) -> usize {
+        match self {
+            PipeConfig::P2 => 2,
+            PipeConfig::P4_8x16
+            | PipeConfig::P4_16x16
+            | PipeConfig::P4_16x32
+            | PipeConfig::P4_32x32 => 4,
+            PipeConfig::P8_16x32_8x16
+            | PipeConfig::P8_32x32_8x16
+            | PipeConfig::P8_16x32_16x16
+            | PipeConfig::P8_32x32_16x16
+            | PipeConfig::P8_32x64_32x32 => 8,
+        }
+    }
+}
+
+/// Macro tile parameters for a given tile mode and element size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileInfo {
+    pub pipe_config: PipeConfig,
+    pub banks: usize,
+    pub bank_width: usize,
+    pub bank_height: usize,
+    pub macro_aspect_ratio: usize,
+    pub tile_split_bytes: usize,
+}
+
+impl TileInfo {
+    // (bank width, bank height, macro aspect ratio, banks), indexed by log2(tile bytes / 64).
+    // This is the generic CI table, used for every pipe config. It hasn't been checked against
+    // textures from a PS4 or an Xbox One.
+    const MACRO_TILE_MODES: [(usize, usize, usize, usize); 7] = [
+        (1, 4, 4, 16),
+        (1, 2, 2, 16),
+        (1, 1, 2, 16),
+        (1, 1, 1, 16),
+        (1, 1, 1, 8),
+        (1, 1, 1, 4),
+        (1, 1, 1, 2),
+    ];
+
+    pub fn new(tile_mode: TileMode, bytes_per_element: usize, pipe_config: PipeConfig) -> Self {
+        let thickness = tile_mode.array_mode().thickness();
+        let tile_bytes = MICRO_TILE_PIXELS * bytes_per_element * thickness;
+        let tile_split_bytes = tile_mode
+            .tile_split_bytes()
+            .unwrap_or(tile_bytes)
+            .min(ROW_SIZE);
+
+        let index = (tile_bytes.min(tile_split_bytes) / 64)
+            .max(1)
+            .ilog2()
+            .min(Self::MACRO_TILE_MODES.len() as u32 - 1) as usize;
+        let (bank_width, bank_height, macro_aspect_ratio, banks) = Self::MACRO_TILE_MODES[index];
+
+        Self {
+            pipe_config,
+            banks,
+            bank_width,
+            bank_height,
+            macro_aspect_ratio,
+            tile_split_bytes,
+        }
+    }
+
+    pub fn macro_tile_pitch(&self) -> usize {
+        MICRO_TILE_WIDTH * self.bank_width * self.pipe_config.num_pipes() * self.macro_aspect_ratio
+    }
+
+    pub fn macro_tile_height(&self) -> usize {
+        MICRO_TILE_HEIGHT * self.bank_height * self.banks / self.macro_aspect_ratio
+    }
+}
+
+/// Padded layout of a single GCN surface level, in elements (blocks for compressed formats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SurfaceLayout {
+    pub array_mode: ArrayMode,
+    pub micro_tile_mode: MicroTileMode,
+    pub tile_info: TileInfo,
+    pub bytes_per_element: usize,
+    pub pitch: usize,
+    pub height: usize,
+    pub depth: usize,
+}
+
+impl SurfaceLayout {
+    pub fn new(
+        dimensions: (usize, usize, usize),
+        bytes_per_element: usize,
+        tile_mode: TileMode,
+        pipe_config: PipeConfig,
+    ) -> Self {
+        let (width, height, depth) = dimensions;
+        let tile_info = TileInfo::new(tile_mode, bytes_per_element, pipe_config);
+        let mut array_mode = tile_mode.array_mode();
+
+        // addrlib falls back to micro tiling when a level is smaller than a macro tile
+        if array_mode.is_macro_tiled()
+            && !matches!(
+                array_mode,
+                ArrayMode::PrtTiledThin1 | ArrayMode::PrtTiledThick
+            )
+            && (width < tile_info.macro_tile_pitch() || height < tile_info.macro_tile_height())
+        {
+            array_mode = array_mode.degrade_to_1d();
+        }
+
+        let thickness = array_mode.thickness();
+        let (pitch_align, height_align) = match array_mode {
+            ArrayMode::LinearGeneral => (1, 1),
+            ArrayMode::LinearAligned => ((PIPE_INTERLEAVE_BYTES / bytes_per_element).max(64), 1),
+            ArrayMode::Tiled1dThin1 | ArrayMode::Tiled1dThick => {
+                (MICRO_TILE_WIDTH, MICRO_TILE_HEIGHT)
+            }
+            _ => (tile_info.macro_tile_pitch(), tile_info.macro_tile_height()),
+        };
+
+        Self {
+            array_mode,
+            micro_tile_mode: tile_mode.micro_tile_mode(),
+            tile_info,
+            bytes_per_element,
+            pitch: width.max(1).next_multiple_of(pitch_align),
+            height: height.max(1).next_multiple_of(height_align),
+            depth: depth.max(1).next_multiple_of(thickness),
+        }
+    }
+
+    /// Layout of an image with the given dimensions in pixels
+    pub fn for_format<T: Format>(
+        dimensions: (usize, usize, usize),
+        format: &T,
+        tile_mode: TileMode,
+        pipe_config: PipeConfig,
+    ) -> Result<Self, SwizzleError> {
+        let (width, height, depth) = dimensions;
+        let pixel_block_size = format.pixel_block_size();
+
+        Ok(Self::new(
+            (
+                width.div_ceil(pixel_block_size),
+                height.div_ceil(pixel_block_size),
+                depth,
+            ),
+            format.block_size()?,
+            tile_mode,
+            pipe_config,
+        ))
+    }
+
+    /// Alignment in bytes of the start of the surface
+    pub fn base_alignment(&self) -> usize {
+        match self.array_mode {
+            ArrayMode::LinearGeneral | ArrayMode::LinearAligned => PIPE_INTERLEAVE_BYTES,
+            ArrayMode::Tiled1dThin1 | ArrayMode::Tiled1dThick => PIPE_INTERLEAVE_BYTES
+                .max(MICRO_TILE_PIXELS * self.array_mode.thickness() * self.bytes_per_element),
+            _ => {
+                self.tile_info.macro_tile_pitch()
+                    * self.tile_info.macro_tile_height()
+                    * self.array_mode.thickness()
+                    * self.bytes_per_element
+            }
+        }
+    }
+
+    pub fn slice_size(&self) -> usize {
+        self.pitch * self.height * self.bytes_per_element
+    }
+
+    /// Total size of the tiled surface in bytes
+    pub fn size(&self) -> usize {
+        self.slice_size() * self.depth
+    }
+
+    /// Byte offset of the element at (`x`, `y`, `z`) in the tiled surface
+    pub fn element_offset(&self, x: usize, y: usize, z: usize) -> usize {
+        match self.array_mode {
+            ArrayMode::LinearGeneral | ArrayMode::LinearAligned => {
+                ((z * self.height + y) * self.pitch + x) * self.bytes_per_element
+            }
+            ArrayMode::Tiled1dThin1 | ArrayMode::Tiled1dThick => self.micro_tiled_offset(x, y, z),
+            _ => self.macro_tiled_offset(x, y, z),
+        }
+    }
+
+    fn pixel_index_within_micro_tile(&self, x: usize, y: usize, z: usize) -> usize {
+        let bit = |v: usize, b: u32| (v >> b) & 1;
+        let (x0, x1, x2) = (bit(x, 0), bit(x, 1), bit(x, 2));
+        let (y0, y1, y2) = (bit(y, 0), bit(y, 1), bit(y, 2));
+        let (z0, z1, z2) = (bit(z, 0), bit(z, 1), bit(z, 2));
+        let bpp = self.bytes_per_element * 8;
+        let thickness = self.array_mode.thickness();
+
+        let bits: [usize; 9] = if thickness > 1 {
+            let low = match bpp {
+                8 | 16 => [x0, y0, x1, y1, z0, z1],
+                32 => [x0, y0, x1, z0, y1, z1],
+                _ => [x0, y0, z0, x1, y1, z1],
+            };
+            let z2 = if thickness == 8 { z2 } else { 0 };
+            [low[0], low[1], low[2], low[3], low[4], low[5], x2, y2, z2]
+        } else {
+            let low = match self.micro_tile_mode {
+                MicroTileMode::Display => match bpp {
+                    8 => [x0, x1, x2, y1, y0, y2],
+                    16 => [x0, x1, x2, y0, y1, y2],
+                    64 => [x0, y0, x1, x2, y1, y2],
+                    128 => [y0, x0, x1, x2, y1, y2],
+                    _ => [x0, x1, y0, x2, y1, y2],
+                },
+                _ => [x0, y0, x1, y1, x2, y2],
+            };
+            [low[0], low[1], low[2], low[3], low[4], low[5], 0, 0, 0]
+        };
+
+        bits.iter()
+            .enumerate()
+            .fold(0, |acc, (i, b)| acc | (b << i))
+    }
+
+    fn micro_tiled_offset(&self, x: usize, y: usize, z: usize) -> usize {
+        let thickness = self.array_mode.thickness();
+        let micro_tile_bytes = MICRO_TILE_PIXELS * thickness * self.bytes_per_element;
+        let micro_tiles_per_row = self.pitch / MICRO_TILE_WIDTH;
+
+        let micro_tile_offset = micro_tile_bytes
+            * ((x / MICRO_TILE_WIDTH) + (y / MICRO_TILE_HEIGHT) * micro_tiles_per_row);
+        let slice_offset = (z / thickness) * self.slice_size() * thickness;
+        let pixel_offset = self.pixel_index_within_micro_tile(x, y, z) * self.bytes_per_element;
+
+        slice_offset + micro_tile_offset + pixel_offset
+    }
+
+    fn pipe_from_coord(&self, x: usize, y: usize, z: usize) -> usize {
+        let tx = x / MICRO_TILE_WIDTH;
+        let ty = y / MICRO_TILE_HEIGHT;
+        let bit = |v: usize, b: u32| (v >> b) & 1;
+        let (x3, x4, x5, x6) = (bit(tx, 0), bit(tx, 1), bit(tx, 2), bit(tx, 3));
+        let (y3, y4, y5) = (bit(ty, 0), bit(ty, 1), bit(ty, 2));
+
+        let (p0, p1, p2) = match self.tile_info.pipe_config {
+            PipeConfig::P2 => (x3 ^ y3, 0, 0),
+            PipeConfig::P4_8x16 => (x4 ^ y3, x3 ^ y4, 0),
+            PipeConfig::P4_16x16 => (x3 ^ y3 ^ x4, x4 ^ y4, 0),
+            PipeConfig::P4_16x32 => (x3 ^ y3 ^ x4, x4 ^ y5, 0),
+            PipeConfig::P4_32x32 => (x3 ^ y3 ^ x5, x5 ^ y5, 0),
+            PipeConfig::P8_16x32_8x16 => (x4 ^ y3 ^ x5, x3 ^ y4, x4 ^ y5),
+            PipeConfig::P8_32x32_8x16 => (x4 ^ y3 ^ x5, x3 ^ y4, x5 ^ y5),
+            PipeConfig::P8_16x32_16x16 => (x3 ^ y3 ^ x4, x5 ^ y4, x4 ^ y5),
+            PipeConfig::P8_32x32_16x16 => (x3 ^ y3 ^ x4, x4 ^ y4, x5 ^ y5),
+            PipeConfig::P8_32x64_32x32 => (x3 ^ y3 ^ x5, x6 ^ y4, x5 ^ y5),
+        };
+        let pipe = p0 | (p1 << 1) | (p2 << 2);
+
+        let num_pipes = self.tile_info.pipe_config.num_pipes();
+        let slice_rotation = match self.array_mode {
+            ArrayMode::Tiled3dThin1 | ArrayMode::Tiled3dThick | ArrayMode::Tiled3dXThick => {
+                (num_pipes / 2).saturating_sub(1).max(1) * (z / self.array_mode.thickness())
+            }
+            _ => 0,
+        };
+
+        pipe ^ (slice_rotation & (num_pipes - 1))
+    }
+
+    fn bank_from_coord(&self, x: usize, y: usize, z: usize, tile_split_slice: usize) -> usize {
+        let info = &self.tile_info;
+        let num_pipes = info.pipe_config.num_pipes();
+        let tx = x / MICRO_TILE_WIDTH / (info.bank_width * num_pipes);
+        let ty = y / MICRO_TILE_HEIGHT / info.bank_height;
+        let bit = |v: usize, b: u32| (v >> b) & 1;
+        let (x3, x4, x5, x6) = (bit(tx, 0), bit(tx, 1), bit(tx, 2), bit(tx, 3));
+        let (y3, y4, y5, y6) = (bit(ty, 0), bit(ty, 1), bit(ty, 2), bit(ty, 3));
+
+        let mut bank = match info.banks {
+            16 => (x3 ^ y6) | ((x4 ^ y5 ^ y6) << 1) | ((x5 ^ y4) << 2) | ((x6 ^ y3) << 3),
+            8 => (x3 ^ y5) | ((x4 ^ y4 ^ y5) << 1) | ((x5 ^ y3) << 2),
+            4 => (x3 ^ y4) | ((x4 ^ y3) << 1),
+            _ => x3 ^ y3,
+        };
+
+        if matches!(
+            info.pipe_config,
+            PipeConfig::P4_32x32 | PipeConfig::P8_32x64_32x32
+        ) && info.bank_width == 1
+        {
+            let tile_x = x / MICRO_TILE_WIDTH;
+            bank |= (bank & 1) ^ bit(tile_x, 1) ^ bit(tile_x, 2);
+        }
+
+        let thickness = self.array_mode.thickness();
+        let slice_rotation = match self.array_mode {
+            ArrayMode::Tiled2dThin1 | ArrayMode::Tiled2dThick | ArrayMode::Tiled2dXThick => {
+                (info.banks / 2 - 1) * (z / thickness)
+            }
+            ArrayMode::Tiled3dThin1 | ArrayMode::Tiled3dThick | ArrayMode::Tiled3dXThick => {
+                (num_pipes / 2).saturating_sub(1).max(1) * (z / thickness) / num_pipes
+            }
+            _ => 0,
+        };
+        let tile_split_rotation = match self.array_mode {
+            ArrayMode::Tiled2dThin1
+            | ArrayMode::Tiled3dThin1
+            | ArrayMode::PrtTiled2dThin1
+            | ArrayMode::PrtTiled3dThin1 => (info.banks / 2 + 1) * tile_split_slice,
+            _ => 0,
+        };
+
+        (bank ^ slice_rotation ^ tile_split_rotation) & (info.banks - 1)
+    }
+
+    fn macro_tiled_offset(&self, x: usize, y: usize, z: usize) -> usize {
+        let info = &self.tile_info;
+        let thickness = self.array_mode.thickness();
+        let num_pipes = info.pipe_config.num_pipes();
+        let num_pipe_bits = num_pipes.ilog2();
+        let num_bank_bits = info.banks.ilog2();
+        let num_pipe_interleave_bits = PIPE_INTERLEAVE_BYTES.ilog2();
+
+        let mut micro_tile_bytes = MICRO_TILE_PIXELS * thickness * self.bytes_per_element;
+        let mut element_offset =
+            self.pixel_index_within_micro_tile(x, y, z) * self.bytes_per_element;
+
+        let (slices_per_tile, tile_split_slice) =
+            if micro_tile_bytes > info.tile_split_bytes && thickness == 1 {
+                let split = element_offset / info.tile_split_bytes;
+                element_offset %= info.tile_split_bytes;
+                let slices = micro_tile_bytes / info.tile_split_bytes;
+                micro_tile_bytes = info.tile_split_bytes;
+                (slices, split)
+            } else {
+                (1, 0)
+            };
+
+        let macro_tile_pitch = info.macro_tile_pitch();
+        let macro_tile_height = info.macro_tile_height();
+        let macro_tile_bytes = micro_tile_bytes
+            * (macro_tile_pitch / MICRO_TILE_WIDTH)
+            * (macro_tile_height / MICRO_TILE_HEIGHT)
+            / (num_pipes * info.banks);
+
+        let macro_tiles_per_row = self.pitch / macro_tile_pitch;
+        let macro_tile_offset = ((y / macro_tile_height) * macro_tiles_per_row
+            + (x / macro_tile_pitch))
+            * macro_tile_bytes;
+        let macro_tiles_per_slice = macro_tiles_per_row * (self.height / macro_tile_height);
+        let slice_bytes = macro_tiles_per_slice * macro_tile_bytes;
+        let slice_offset = slice_bytes * (tile_split_slice + slices_per_tile * (z / thickness));
+
+        let tile_row_index = (y / MICRO_TILE_HEIGHT) % info.bank_height;
+        let tile_column_index = ((x / MICRO_TILE_WIDTH) / num_pipes) % info.bank_width;
+        let tile_offset = (tile_row_index * info.bank_width + tile_column_index) * micro_tile_bytes;
+
+        let total_offset = slice_offset + macro_tile_offset + element_offset + tile_offset;
+
+        let (x, y) = if self.array_mode.is_prt_no_rotation() {
+            (x % macro_tile_pitch, y % macro_tile_height)
+        } else {
+            (x, y)
+        };
+        let pipe = self.pipe_from_coord(x, y, z);
+        let bank = self.bank_from_coord(x, y, z, tile_split_slice);
+
+        let pipe_interleave_offset = total_offset & (PIPE_INTERLEAVE_BYTES - 1);
+        let offset = total_offset >> num_pipe_interleave_bits;
+
+        pipe_interleave_offset
+            | (pipe << num_pipe_interleave_bits)
+            | (bank << (num_pipe_interleave_bits + num_pipe_bits))
+            | (offset << (num_pipe_interleave_bits + num_pipe_bits + num_bank_bits))
+    }
+}
+
+pub fn do_swizzle<T: Format>(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    format: T,
+    unswizzle: bool,
+    tile_mode: TileMode,
+    pipe_config: PipeConfig,
+) -> Result<(), SwizzleError> {
+    let layout = SurfaceLayout::for_format(dimensions, &format, tile_mode, pipe_config)?;
+    swizzle_layout(source, dest, dimensions, &layout, format, unswizzle)
+}
+
+/// Swizzles an image into (or out of) an already computed surface layout
+pub fn swizzle_layout<T: Format>(
+    source: &[u8],
+    dest: &mut [u8],
+    dimensions: (usize, usize, usize),
+    layout: &SurfaceLayout,
+    format: T,
+    unswizzle: bool,
+) -> Result<(), SwizzleError> {
+    let (width, height, depth) = dimensions;
+    let pixel_block_size = format.pixel_block_size();
+    let block_size = format.block_size()?;
+
+    let width_texels = width.div_ceil(pixel_block_size);
+    let height_texels = height.div_ceil(pixel_block_size);
+
+    for z in 0..depth {
+        for y in 0..height_texels {
+            for x in 0..width_texels {
+                let tiled_index = layout.element_offset(x, y, z);
+                let linear_index = ((z * height_texels + y) * width_texels + x) * block_size;
+
+                let (src, dst) = if unswizzle {
+                    (tiled_index, linear_index)
+                } else {
+                    (linear_index, tiled_index)
+                };
+
+                if (src + block_size) <= source.len() && (dst + block_size) <= dest.len() {
+                    dest[dst..dst + block_size].copy_from_slice(&source[src..src + block_size]);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes the mip layout of a GCN texture.
+/// Levels after the first are padded to a power of two, and every level starts on its own
+/// base alignment. Cubemap faces and array layers are stored as the slices of each level.
+pub fn mip_chain<T: Format>(
+    desc: &SurfaceDesc<T>,
+    tile_mode: TileMode,
+    pipe_config: PipeConfig,
+) -> Result<MipChain, SwizzleError> {
+    let format = desc.format();
+    let align_resolution = desc.align_resolution();
+
+    MipChain::new(
+        desc,
+        LayerLayout::LevelMajor(1),
+        |level, (width, height, depth)| {
+            let padded_dimensions = if level > 0 || (align_resolution && format.is_compressed()) {
+                (
+                    width.next_power_of_two(),
+                    height.next_power_of_two(),
+                    depth.next_power_of_two(),
+                )
+            } else {
+                (width, height, depth)
+            };
+            let padded_dimensions = desc.apply_pitch(level, padded_dimensions);
+
+            let layout =
+                SurfaceLayout::for_format(padded_dimensions, format, tile_mode, pipe_config)?;
+            Ok((padded_dimensions, layout.size(), layout.base_alignment()))
+        },
+    )
+}
+
+pub fn do_swizzle_mips<T: Format>(
+    source: &[u8],
+    dest: &mut [u8],
+    chain: &MipChain,
+    format: T,
+    unswizzle: bool,
+    tile_mode: TileMode,
+    pipe_config: PipeConfig,
+) -> Result<(), SwizzleError> {
+    chain.for_each_level(source, dest, unswizzle, |source, dest, level| {
+        let layout =
+            SurfaceLayout::for_format(level.padded_dimensions, &format, tile_mode, pipe_config)?;
+        swizzle_layout(source, dest, level.dimensions, &layout, &format, unswizzle)
+    })
+}
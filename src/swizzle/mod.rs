@@ -1,7 +1,14 @@
+pub mod gcn;
+mod platform;
 pub mod ps;
+mod surface;
+pub mod switch;
 pub mod xbox;
 
-use core::{error::Error, fmt};
+pub use platform::Platform;
+pub use surface::{AlignmentPolicy, SurfaceDesc, SurfaceDescBuilder};
+
+use core::{error::Error, fmt, ops::Deref};
 
 #[derive(Debug, Clone, Copy)]
 pub enum TextureSlice {
@@ -13,6 +20,18 @@ pub enum TextureSlice {
 pub enum SwizzleError {
     FormatOutOfRange(u32),
     OutOfBounds(TextureSlice),
+    InvalidBlockHeight(usize),
+    InvalidTileMode(u32),
+    InvalidMipCount(usize),
+    InvalidDimensions(usize, usize, usize),
+    InvalidArraySize(usize),
+    InvalidPitch(usize),
+    InvalidMagic(u32),
+    UnsupportedVersion(u32),
+    InvalidTextureType(u32),
+    InvalidMipAddress(u32),
+    /// The format is valid, but can't be handled, such as the planar video formats
+    UnsupportedFormat(u32),
 }
 
 impl Error for SwizzleError {}
@@ -22,48 +41,447 @@ impl fmt::Display for SwizzleError {
         match *self {
             SwizzleError::OutOfBounds(s) => write!(f, "slice {s:?} out of bounds"),
             SwizzleError::FormatOutOfRange(e) => write!(f, "format is out of range ({e})"),
+            SwizzleError::InvalidBlockHeight(b) => write!(f, "invalid block height ({b} GOBs)"),
+            SwizzleError::InvalidTileMode(t) => write!(f, "invalid tile mode ({t})"),
+            SwizzleError::InvalidMipCount(c) => write!(f, "invalid mip count ({c})"),
+            SwizzleError::InvalidDimensions(w, h, d) => {
+                write!(f, "invalid dimensions ({w}x{h}x{d})")
+            }
+            SwizzleError::InvalidArraySize(a) => write!(f, "invalid array size ({a})"),
+            SwizzleError::InvalidPitch(p) => write!(f, "pitch is smaller than the width ({p})"),
+            SwizzleError::InvalidMagic(m) => write!(f, "invalid file magic ({m:#010x})"),
+            SwizzleError::UnsupportedVersion(v) => write!(f, "unsupported file version ({v})"),
+            SwizzleError::InvalidTextureType(t) => write!(f, "invalid texture type ({t})"),
+            SwizzleError::InvalidMipAddress(a) => write!(f, "invalid mip address ({a:#x})"),
+            SwizzleError::UnsupportedFormat(e) => write!(f, "unsupported format ({e})"),
+        }
+    }
+}
+
+/// Maximum number of mip levels in a [`MipChain`]
+pub const MAX_MIP_LEVELS: usize = 16;
+
+/// How the slices of a texture relate to each other
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TextureKind {
+    /// A single 2D image
+    #[default]
+    Texture2D,
+    /// Six 2D faces, in the +X, -X, +Y, -Y, +Z, -Z order
+    Cube,
+    /// The given number of independent 2D layers
+    Array(usize),
+    /// A 3D image, which also shrinks along the Z axis in its mip levels
+    Volume,
+}
+
+impl TextureKind {
+    /// Number of layers that each have their own mip chain
+    pub fn layers(self) -> usize {
+        match self {
+            TextureKind::Texture2D | TextureKind::Volume => 1,
+            TextureKind::Cube => 6,
+            TextureKind::Array(layers) => layers,
+        }
+    }
+}
+
+/// Where the layers of a texture are placed in the swizzled layout
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LayerLayout {
+    /// Each layer holds its whole mip chain, and starts on a multiple of the given alignment
+    LayerMajor(usize),
+    /// Each mip level holds that level of every layer, each one starting on a multiple of the
+    /// given alignment
+    LevelMajor(usize),
+}
+
+/// Location of a single mip level in both the swizzled and the linear layout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MipLevel {
+    /// Dimensions of the level: `(width, height, depth)`.
+    pub dimensions: (usize, usize, usize),
+    /// Dimensions the level is tiled with, before any platform-specific tile alignment.
+    pub padded_dimensions: (usize, usize, usize),
+    /// Offset of the level within the first layer of the linear layout.
+    pub linear_offset: usize,
+    pub linear_size: usize,
+    /// Offset of the level within the first layer of the swizzled layout.
+    pub swizzled_offset: usize,
+    pub swizzled_size: usize,
+    /// Distance in bytes between this level in one layer and the same level in the next one.
+    pub swizzled_layer_stride: usize,
+    /// Position of the level, in blocks, inside a tile shared with other levels (such as the
+    /// packed mip tail on Xbox 360). Zero for levels that have their own storage.
+    pub packed_offset: (usize, usize, usize),
+}
+
+/// The layout of every level of a mipmapped texture, largest level first.
+///
+/// Linearly, layers are stored one after the other, each with its whole mip chain, as in a DDS
+/// file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MipChain {
+    levels: [MipLevel; MAX_MIP_LEVELS],
+    len: usize,
+    layers: usize,
+    linear_layer_stride: usize,
+}
+
+impl MipChain {
+    /// Builds a mip chain from a per-level callback.
+    /// * `swizzled_layout` - Returns the padded dimensions, swizzled size and swizzled alignment
+    ///   of a level, given its index and dimensions.
+    pub(crate) fn new<T: Format>(
+        desc: &SurfaceDesc<T>,
+        layer_layout: LayerLayout,
+        mut swizzled_layout: impl FnMut(
+            usize,
+            (usize, usize, usize),
+        )
+            -> Result<((usize, usize, usize), usize, usize), SwizzleError>,
+    ) -> Result<Self, SwizzleError> {
+        let mip_count = desc.mip_count();
+        if mip_count == 0 || mip_count > MAX_MIP_LEVELS {
+            return Err(SwizzleError::InvalidMipCount(mip_count));
+        }
+
+        let (width, height, depth) = desc.dimensions();
+        let format = desc.format();
+        let kind = desc.kind();
+        let pixel_block_size = format.pixel_block_size();
+        let block_size = format.block_size()?;
+        let layers = kind.layers();
+
+        let mut chain = MipChain {
+            layers,
+            ..Default::default()
+        };
+        let mut linear_offset = 0;
+        let mut swizzled_offset: usize = 0;
+
+        for level in 0..mip_count {
+            let dimensions = (
+                (width >> level).max(1),
+                (height >> level).max(1),
+                if kind == TextureKind::Volume {
+                    (depth >> level).max(1)
+                } else {
+                    depth
+                },
+            );
+            let (padded_dimensions, swizzled_size, swizzled_alignment) =
+                swizzled_layout(level, dimensions)?;
+
+            let linear_size = dimensions.0.div_ceil(pixel_block_size)
+                * dimensions.1.div_ceil(pixel_block_size)
+                * dimensions.2
+                * block_size;
+
+            swizzled_offset = swizzled_offset.next_multiple_of(swizzled_alignment.max(1));
+
+            let swizzled_layer_stride = match layer_layout {
+                LayerLayout::LayerMajor(_) => 0,
+                LayerLayout::LevelMajor(alignment) => {
+                    swizzled_size.next_multiple_of(alignment.max(1))
+                }
+            };
+
+            chain.levels[level] = MipLevel {
+                dimensions,
+                padded_dimensions,
+                linear_offset,
+                linear_size,
+                swizzled_offset,
+                swizzled_size,
+                swizzled_layer_stride,
+                packed_offset: (0, 0, 0),
+            };
+            chain.len += 1;
+
+            linear_offset += linear_size;
+            swizzled_offset += swizzled_layer_stride * (layers - 1) + swizzled_size;
+        }
+
+        chain.linear_layer_stride = linear_offset;
+        if let LayerLayout::LayerMajor(alignment) = layer_layout {
+            let swizzled_layer_stride = swizzled_offset.next_multiple_of(alignment.max(1));
+            for level in chain.levels_mut() {
+                level.swizzled_layer_stride = swizzled_layer_stride;
+            }
+        }
+
+        Ok(chain)
+    }
+
+    pub(crate) fn levels_mut(&mut self) -> &mut [MipLevel] {
+        &mut self.levels[..self.len]
+    }
+
+    /// Number of layers, such as the six faces of a cubemap
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    /// Distance in bytes between two layers in the linear layout
+    pub fn linear_layer_stride(&self) -> usize {
+        self.linear_layer_stride
+    }
+
+    /// Total size of the linear texture in bytes, including every layer
+    pub fn linear_size(&self) -> usize {
+        self.linear_layer_stride * self.layers
+    }
+
+    /// Total size of the swizzled texture in bytes, including every layer
+    pub fn swizzled_size(&self) -> usize {
+        self.iter()
+            .map(|l| {
+                l.swizzled_offset
+                    + l.swizzled_layer_stride * self.layers.saturating_sub(1)
+                    + l.swizzled_size
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Calls `f` with the source and destination slices of every level of every layer in the chain
+    pub(crate) fn for_each_level(
+        &self,
+        source: &[u8],
+        dest: &mut [u8],
+        unswizzle: bool,
+        mut f: impl FnMut(&[u8], &mut [u8], &MipLevel) -> Result<(), SwizzleError>,
+    ) -> Result<(), SwizzleError> {
+        for layer in 0..self.layers {
+            for level in self.iter() {
+                let linear_offset = layer * self.linear_layer_stride + level.linear_offset;
+                let swizzled_offset = layer * level.swizzled_layer_stride + level.swizzled_offset;
+
+                let (src_offset, src_size, dst_offset, dst_size) = if unswizzle {
+                    (
+                        swizzled_offset,
+                        level.swizzled_size,
+                        linear_offset,
+                        level.linear_size,
+                    )
+                } else {
+                    (
+                        linear_offset,
+                        level.linear_size,
+                        swizzled_offset,
+                        level.swizzled_size,
+                    )
+                };
+
+                let src_end = (src_offset + src_size).min(source.len());
+                let Some(level_source) = source.get(src_offset..src_end) else {
+                    return Err(SwizzleError::OutOfBounds(TextureSlice::Source));
+                };
+
+                let Some(level_dest) = dest.get_mut(dst_offset..dst_offset + dst_size) else {
+                    return Err(SwizzleError::OutOfBounds(TextureSlice::Dest));
+                };
+
+                f(level_source, level_dest, level)?;
+            }
         }
+
+        Ok(())
+    }
+}
+
+impl Deref for MipChain {
+    type Target = [MipLevel];
+
+    fn deref(&self) -> &Self::Target {
+        &self.levels[..self.len]
+    }
+}
+
+/// The kind the `*_mips` methods assume: a 2D image, or a volume when it is deeper than 1
+pub(crate) fn legacy_kind(dimensions: (usize, usize, usize)) -> TextureKind {
+    if dimensions.2 > 1 {
+        TextureKind::Volume
+    } else {
+        TextureKind::Texture2D
+    }
+}
+
+/// Converts an unsigned normalized value between bit widths, replicating its high bits when
+/// widening
+pub(crate) fn rescale_unorm(value: u128, from: u32, to: u32) -> u128 {
+    if from >= to {
+        value >> (from - to)
+    } else {
+        let mut result = 0;
+        let mut filled = 0;
+        while filled < to {
+            result = (result << from) | value;
+            filled += from;
+        }
+        result >> (filled - to)
+    }
+}
+
+/// The trait that defines how a platform lays out the surfaces it swizzles
+pub trait TextureLayout {
+    /// Computes the location of every level of the surface described by `desc`, in both the
+    /// swizzled and the linear layout.
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError>;
+
+    /// Exact size in bytes of the swizzled surface, including every layer and mip level.
+    fn swizzled_size<T: Format>(desc: &SurfaceDesc<T>) -> Result<usize, SwizzleError> {
+        Ok(Self::mip_chain(desc)?.swizzled_size())
+    }
+
+    /// Exact size in bytes of the linear surface, including every layer and mip level.
+    fn linear_size<T: Format>(desc: &SurfaceDesc<T>) -> Result<usize, SwizzleError> {
+        Ok(Self::mip_chain(desc)?.linear_size())
     }
 }
 
 /// This trait defines the function used to swizzle/tile image data
 /// * `source` - Source image data.
-/// * `dest` - Destination slice. When swizzling an image that has dimensions that are not a power of two,
-///   it is recommended to make this larger than the size of the unswizzled texture, or else data could be lost.
+/// * `dest` - Destination slice, of at least [`TextureLayout::swizzled_size`] bytes. Any data that
+///   does not fit in a smaller slice is lost.
 /// * `dimensions` - Dimensions of the image: `(width, height, depth)`.
 /// * `format` - Expected image format.
 /// * `align_resolution` - Align the resolution of the image to the next power of two.
-pub trait Swizzler {
+pub trait Swizzler: TextureLayout {
     fn swizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError>;
+
+    /// Swizzles a full mip chain, laid out linearly in `source` with the largest level first.
+    /// Returns the location of every level in both layouts.
+    /// * `mip_count` - Number of mip levels, including the base level.
+    fn swizzle_mips<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        mip_count: usize,
+        align_resolution: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::swizzle_texture(
+            source,
+            dest,
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            mip_count,
+            align_resolution,
+        )
+    }
+
+    /// Swizzles every layer of a texture, such as the six faces of a cubemap, with all of their
+    /// mip levels.
+    /// `source` holds each layer with its full mip chain, one after the other, as in a DDS file.
+    /// Returns the location of every level in both layouts.
+    /// * `kind` - What the layers of the texture are.
+    /// * `mip_count` - Number of mip levels per layer, including the base level.
+    fn swizzle_texture<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        kind: TextureKind,
+        mip_count: usize,
+        align_resolution: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        let desc = SurfaceDesc::from_tuple(dimensions, format, kind, mip_count, align_resolution)?;
+        Self::swizzle_surface(source, dest, &desc)
+    }
+
+    /// Swizzles every layer and mip level of the surface described by `desc`.
+    /// Returns the location of every level in both layouts.
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError>;
 }
 
 /// The trait that defines an interface to deswizzle/detile image data
 /// * `source` - Source image data.
-/// * `dest` - Destination slice, usually with the size `(width * height * depth * format.bpp()) / 8`.
+/// * `dest` - Destination slice, of at least [`TextureLayout::linear_size`] bytes. A single level
+///   takes `format.block_size()` bytes for each of its blocks, counting partial blocks at the
+///   edges as whole ones.
 /// * `dimensions` - Dimensions of the image: `(width, height, depth)`.
 /// * `format` - Expected image format.
 /// * `align_resolution` - Align the resolution of the image to the next power of two.
-pub trait Deswizzler {
+pub trait Deswizzler: TextureLayout {
     fn deswizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError>;
+
+    /// Deswizzles a full mip chain into `dest`, largest level first.
+    /// Returns the location of every level in both layouts.
+    /// * `mip_count` - Number of mip levels, including the base level.
+    fn deswizzle_mips<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        mip_count: usize,
+        align_resolution: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::deswizzle_texture(
+            source,
+            dest,
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            mip_count,
+            align_resolution,
+        )
+    }
+
+    /// Deswizzles every layer of a texture, such as the six faces of a cubemap, with all of their
+    /// mip levels.
+    /// `dest` receives each layer with its full mip chain, one after the other, as in a DDS file.
+    /// Returns the location of every level in both layouts.
+    /// * `kind` - What the layers of the texture are.
+    /// * `mip_count` - Number of mip levels per layer, including the base level.
+    fn deswizzle_texture<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        kind: TextureKind,
+        mip_count: usize,
+        align_resolution: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        let desc = SurfaceDesc::from_tuple(dimensions, format, kind, mip_count, align_resolution)?;
+        Self::deswizzle_surface(source, dest, &desc)
+    }
+
+    /// Deswizzles every layer and mip level of the surface described by `desc`.
+    /// Returns the location of every level in both layouts.
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError>;
 }
 
 /// A trait that defines a given texture format
 pub trait Format {
-    fn bpp(&self) -> usize;
+    /// Bits per pixel, or [`SwizzleError::UnsupportedFormat`] for formats without a fixed size
+    fn bpp(&self) -> Result<usize, SwizzleError>;
 
-    fn block_size(&self) -> usize;
+    /// Size in bytes of a block of [`Format::pixel_block_size`] squared pixels, or
+    /// [`SwizzleError::UnsupportedFormat`] for formats whose pixels don't fill whole bytes
+    fn block_size(&self) -> Result<usize, SwizzleError>;
 
     fn pixel_block_size(&self) -> usize;
 
@@ -72,6 +490,28 @@ pub trait Format {
     fn x360_swap(&self) -> bool;
 }
 
+impl<T: Format + ?Sized> Format for &T {
+    fn bpp(&self) -> Result<usize, SwizzleError> {
+        (**self).bpp()
+    }
+
+    fn block_size(&self) -> Result<usize, SwizzleError> {
+        (**self).block_size()
+    }
+
+    fn pixel_block_size(&self) -> usize {
+        (**self).pixel_block_size()
+    }
+
+    fn is_compressed(&self) -> bool {
+        (**self).is_compressed()
+    }
+
+    fn x360_swap(&self) -> bool {
+        (**self).x360_swap()
+    }
+}
+
 pub fn morton(t: usize, x: usize, y: usize) -> usize {
     let mut bit_position_x = 1;
     let mut bit_position_y = 1;
@@ -98,3 +538,41 @@ pub fn morton(t: usize, x: usize, y: usize) -> usize {
 
     result_y * x + result_x
 }
+
+/// Like [`morton`], but interleaves the bits of all three axes, in X, Y, Z order.
+/// Once an axis runs out of bits the remaining ones are interleaved without it.
+pub fn morton_3d(t: usize, x: usize, y: usize, z: usize) -> usize {
+    let mut bit_position_x = 1;
+    let mut bit_position_y = 1;
+    let mut bit_position_z = 1;
+    let mut morton_code = t;
+    let mut mask_x = x;
+    let mut mask_y = y;
+    let mut mask_z = z;
+    let mut result_x = 0;
+    let mut result_y = 0;
+    let mut result_z = 0;
+
+    while mask_x > 1 || mask_y > 1 || mask_z > 1 {
+        if mask_x > 1 {
+            result_x += bit_position_x * (morton_code & 1);
+            morton_code >>= 1;
+            bit_position_x <<= 1;
+            mask_x >>= 1;
+        }
+        if mask_y > 1 {
+            result_y += bit_position_y * (morton_code & 1);
+            morton_code >>= 1;
+            bit_position_y <<= 1;
+            mask_y >>= 1;
+        }
+        if mask_z > 1 {
+            result_z += bit_position_z * (morton_code & 1);
+            morton_code >>= 1;
+            bit_position_z <<= 1;
+            mask_z >>= 1;
+        }
+    }
+
+    (result_z * y + result_y) * x + result_x
+}
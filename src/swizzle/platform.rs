@@ -0,0 +1,105 @@
+use super::{
+    Deswizzler, Format, MipChain, SurfaceDesc, SwizzleError, Swizzler, TextureLayout,
+    ps::{Ps3, Ps4},
+    switch::Switch,
+    xbox::{Xbox360, XboxOne},
+};
+
+/// A platform whose textures can be swizzled, for picking a backend at runtime.
+///
+/// ```
+/// # use swizzleinator::{format::GcnSurfaceFormat, swizzle::{Format, Platform}};
+/// let platform = Platform::Ps4;
+/// let format: &dyn Format = &GcnSurfaceFormat::Format8_8_8_8;
+///
+/// let source = vec![0u8; 64 * 64 * 4];
+/// let mut dest = vec![0u8; 64 * 64 * 4];
+/// platform.deswizzle(&source, &mut dest, (64, 64, 1), format, false).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    Ps3,
+    Ps4,
+    Xbox360,
+    XboxOne,
+    Switch,
+}
+
+macro_rules! dispatch {
+    ($platform:expr, $trait:ident::$function:ident($($arg:expr),*)) => {
+        match $platform {
+            Platform::Ps3 => <Ps3 as $trait>::$function($($arg),*),
+            Platform::Ps4 => <Ps4 as $trait>::$function($($arg),*),
+            Platform::Xbox360 => <Xbox360 as $trait>::$function($($arg),*),
+            Platform::XboxOne => <XboxOne as $trait>::$function($($arg),*),
+            Platform::Switch => <Switch as $trait>::$function($($arg),*),
+        }
+    };
+}
+
+impl Platform {
+    /// See [`Swizzler::swizzle`]
+    pub fn swizzle(
+        self,
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: &dyn Format,
+        align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        dispatch!(
+            self,
+            Swizzler::swizzle(source, dest, dimensions, format, align_resolution)
+        )
+    }
+
+    /// See [`Deswizzler::deswizzle`]
+    pub fn deswizzle(
+        self,
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: &dyn Format,
+        align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        dispatch!(
+            self,
+            Deswizzler::deswizzle(source, dest, dimensions, format, align_resolution)
+        )
+    }
+
+    /// See [`Swizzler::swizzle_surface`]
+    pub fn swizzle_surface<T: Format>(
+        self,
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        dispatch!(self, Swizzler::swizzle_surface(source, dest, desc))
+    }
+
+    /// See [`Deswizzler::deswizzle_surface`]
+    pub fn deswizzle_surface<T: Format>(
+        self,
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        dispatch!(self, Deswizzler::deswizzle_surface(source, dest, desc))
+    }
+
+    /// See [`TextureLayout::mip_chain`]
+    pub fn mip_chain<T: Format>(self, desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        dispatch!(self, TextureLayout::mip_chain(desc))
+    }
+
+    /// See [`TextureLayout::swizzled_size`]
+    pub fn swizzled_size<T: Format>(self, desc: &SurfaceDesc<T>) -> Result<usize, SwizzleError> {
+        dispatch!(self, TextureLayout::swizzled_size(desc))
+    }
+
+    /// See [`TextureLayout::linear_size`]
+    pub fn linear_size<T: Format>(self, desc: &SurfaceDesc<T>) -> Result<usize, SwizzleError> {
+        dispatch!(self, TextureLayout::linear_size(desc))
+    }
+}
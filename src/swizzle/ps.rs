@@ -1,47 +1,232 @@
-use super::{Deswizzler, Format, SwizzleError, Swizzler};
+use super::{
+    Deswizzler, Format, MipChain, SurfaceDesc, SwizzleError, Swizzler, TextureLayout,
+    gcn::{self, PipeConfig, SurfaceLayout, TileMode},
+    rescale_unorm,
+};
+use crate::format::GcmSurfaceFormat;
 
 pub struct Ps3;
 
+impl TextureLayout for Ps3 {
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        ps3::mip_chain(desc)
+    }
+}
+
 impl Swizzler for Ps3 {
     fn swizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        ps3::do_swizzle(source, dest, dimensions, format, false, align_resolution);
-        Ok(())
+        ps3::do_swizzle(source, dest, dimensions, format, false, align_resolution)
+    }
+
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        chain.for_each_level(source, dest, false, |source, dest, level| {
+            ps3::do_swizzle(
+                source,
+                dest,
+                level.dimensions,
+                desc.format(),
+                false,
+                desc.align_resolution(),
+            )
+        })?;
+        Ok(chain)
     }
 }
 
 impl Deswizzler for Ps3 {
     fn deswizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        ps3::do_swizzle(source, dest, dimensions, format, true, align_resolution);
-        Ok(())
+        ps3::do_swizzle(source, dest, dimensions, format, true, align_resolution)
+    }
+
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        chain.for_each_level(source, dest, true, |source, dest, level| {
+            ps3::do_swizzle(
+                source,
+                dest,
+                level.dimensions,
+                desc.format(),
+                true,
+                desc.align_resolution(),
+            )
+        })?;
+        Ok(chain)
+    }
+}
+
+/// Where a component of a sampled texel comes from, `CELL_GCM_TEXTURE_REMAP_FROM_*` or the
+/// `CELL_GCM_TEXTURE_REMAP_ZERO`/`ONE` overrides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GcmComponent {
+    A,
+    R,
+    G,
+    B,
+    Zero,
+    One,
+}
+
+/// The `remap` of a `CellGcmTexture`, which the RSX applies to the A, R, G and B components of
+/// each texel as it is sampled. [`Ps3`] doesn't apply it, call [`GcmRemap::apply`] on the
+/// deswizzled data to get the components a shader would see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GcmRemap(pub [GcmComponent; 4]);
+
+impl Default for GcmRemap {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl GcmRemap {
+    pub const IDENTITY: Self = GcmRemap([
+        GcmComponent::A,
+        GcmComponent::R,
+        GcmComponent::G,
+        GcmComponent::B,
+    ]);
+
+    /// Parses a `remap` value: the source of A, R, G and B in 2 bit fields from bit 0, then
+    /// whether each of them is zero, one or remapped in 2 bit fields from bit 8
+    pub fn from_bits(bits: u32) -> Self {
+        GcmRemap(core::array::from_fn(|i| {
+            match ((bits >> (8 + i * 2)) & 3, (bits >> (i * 2)) & 3) {
+                (0, _) => GcmComponent::Zero,
+                (1, _) => GcmComponent::One,
+                (_, 0) => GcmComponent::A,
+                (_, 1) => GcmComponent::R,
+                (_, 2) => GcmComponent::G,
+                _ => GcmComponent::B,
+            }
+        }))
+    }
+
+    pub fn to_bits(self) -> u32 {
+        self.0.iter().enumerate().fold(0, |bits, (i, &component)| {
+            let (output, source) = match component {
+                GcmComponent::Zero => (0, i as u32),
+                GcmComponent::One => (1, i as u32),
+                source => (2, source as u32),
+            };
+            bits | output << (8 + i * 2) | source << (i * 2)
+        })
+    }
+
+    /// Remaps the components of every texel of `data`, which holds big endian texels of
+    /// `format`. Components of different widths are rescaled.
+    /// Components that share their bits with another one, such as the A and R of `G8B8`, are
+    /// overwritten by it, so only G and B are kept for those formats.
+    /// Formats without [`GcmSurfaceFormat::components`] are left as they are.
+    pub fn apply(self, data: &mut [u8], format: GcmSurfaceFormat) {
+        let Some(components) = format.components() else {
+            return;
+        };
+        if self == Self::IDENTITY {
+            return;
+        }
+
+        let texel_bits = components
+            .iter()
+            .flatten()
+            .map(|(shift, width)| shift + width)
+            .max()
+            .unwrap_or_default();
+        let texel_size = texel_bits.div_ceil(8) as usize;
+        let mask = |width: u32| u128::MAX >> (128 - width);
+        let one = |width: u32| match (format.is_float(), width) {
+            (true, 16) => 0x3C00,
+            (true, 32) => 0x3F80_0000,
+            (_, width) => mask(width),
+        };
+
+        for texel in data.chunks_exact_mut(texel_size) {
+            let mut bytes = [0u8; 16];
+            bytes[16 - texel_size..].copy_from_slice(texel);
+            let value = u128::from_be_bytes(bytes);
+            let read = |source: usize, width: u32| match components[source] {
+                Some((shift, source_width)) => {
+                    rescale_unorm((value >> shift) & mask(source_width), source_width, width)
+                }
+                None => one(width),
+            };
+
+            let mut remapped = value;
+            for (i, component) in components.iter().enumerate() {
+                let Some((shift, width)) = *component else {
+                    continue;
+                };
+                let result = match self.0[i] {
+                    GcmComponent::Zero => 0,
+                    GcmComponent::One => one(width),
+                    source => read(source as usize, width),
+                };
+                remapped = (remapped & !(mask(width) << shift)) | result << shift;
+            }
+            texel.copy_from_slice(&remapped.to_be_bytes()[16 - texel_size..]);
+        }
     }
 }
 
 mod ps3 {
-    use crate::swizzle::{Format, SwizzleError};
+    use crate::swizzle::{Format, LayerLayout, MipChain, SurfaceDesc, SwizzleError, TextureSlice};
+
+    /// RSX mip levels are packed back to back, without any alignment between them.
+    /// Cubemap faces each hold their whole mip chain, and are aligned to 128 bytes.
+    pub fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        let format = desc.format();
+        let align_resolution = desc.align_resolution();
+
+        MipChain::new(
+            desc,
+            LayerLayout::LayerMajor(128),
+            |_, (width, height, depth)| {
+                let (width_src, height_src) = if align_resolution && format.is_compressed() {
+                    (width.next_power_of_two(), height.next_power_of_two())
+                } else {
+                    (width, height)
+                };
+
+                let size = width_src.div_ceil(format.pixel_block_size())
+                    * height_src.div_ceil(format.pixel_block_size())
+                    * depth
+                    * format.block_size()?;
+                Ok(((width_src, height_src, depth), size, 1))
+            },
+        )
+    }
 
     pub fn do_swizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         unswizzle: bool,
         align_resolution: bool,
-    ) {
+    ) -> Result<(), SwizzleError> {
         let (width, height, depth) = dimensions;
         let pixel_block_size = format.pixel_block_size();
-        let block_size = format.block_size();
+        let block_size = format.block_size()?;
 
         let (width_src, height_src) = if align_resolution && format.is_compressed() {
             (width.next_power_of_two(), height.next_power_of_two())
@@ -49,127 +234,182 @@ mod ps3 {
             (width, height)
         };
 
-        let width_texels = width_src / pixel_block_size;
-        let height_texels = height_src / pixel_block_size;
+        let width_texels = width_src.div_ceil(pixel_block_size);
+        let height_texels = height_src.div_ceil(pixel_block_size);
 
-        let mut data_index = 0;
+        for t in 0..width_texels * height_texels * depth {
+            // Volumes are swizzled as a whole, with the Z bits interleaved along with X and Y
+            let pixel_index = if depth > 1 {
+                crate::swizzle::morton_3d(t, width_texels, height_texels, depth)
+            } else {
+                crate::swizzle::morton(t, width_texels, height_texels)
+            };
+            let data_index = t * block_size;
+            let dest_index = pixel_index * block_size;
+            let (src, dst) = if unswizzle {
+                (data_index, dest_index)
+            } else {
+                (dest_index, data_index)
+            };
 
-        let texel_size = width_texels * height_texels;
+            let Some(block) = source.get(src..src + block_size) else {
+                return Err(SwizzleError::OutOfBounds(TextureSlice::Source));
+            };
+            let Some(out) = dest.get_mut(dst..dst + block_size) else {
+                return Err(SwizzleError::OutOfBounds(TextureSlice::Dest));
+            };
+            out.copy_from_slice(block);
+        }
 
-        for z in 0..depth {
-            let slice_dest = &mut dest[(z * width * height * format.bpp()) / 8..];
+        Ok(())
+    }
+}
 
-            for t in 0..texel_size {
-                let pixel_index = crate::swizzle::morton(t, width_texels, height_texels);
-                let dest_index = block_size * pixel_index;
-                let (src, dst) = if unswizzle {
-                    (data_index, dest_index)
-                } else {
-                    (dest_index, data_index)
-                };
+pub struct Ps4;
 
-                if (src + block_size) <= source.len() && (dst + block_size) <= slice_dest.len() {
-                    slice_dest[dst..dst + block_size]
-                        .copy_from_slice(&source[src..src + block_size]);
-                }
+impl Ps4 {
+    /// Pipe configuration of the Liverpool GPU
+    pub const PIPE_CONFIG: PipeConfig = PipeConfig::P8_32x32_16x16;
 
-                data_index += block_size;
-            }
-        }
+    /// Tile mode used by the `Swizzler`/`Deswizzler` implementations
+    pub const DEFAULT_TILE_MODE: TileMode = TileMode::Thin_1dThin;
+
+    /// Swizzles an image using the tile mode from its T# descriptor.
+    ///
+    /// The `Swizzler` implementation always lays textures out as [`Ps4::DEFAULT_TILE_MODE`].
+    pub fn swizzle_with_tile_mode<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        tile_mode: TileMode,
+        pipe_config: PipeConfig,
+    ) -> Result<(), SwizzleError> {
+        gcn::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            false,
+            tile_mode,
+            pipe_config,
+        )
     }
-}
 
-pub struct Ps4;
+    /// Deswizzles an image using the tile mode from its T# descriptor.
+    ///
+    /// The `Deswizzler` implementation always expects textures laid out as [`Ps4::DEFAULT_TILE_MODE`].
+    pub fn deswizzle_with_tile_mode<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        tile_mode: TileMode,
+        pipe_config: PipeConfig,
+    ) -> Result<(), SwizzleError> {
+        gcn::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            true,
+            tile_mode,
+            pipe_config,
+        )
+    }
 
-impl Swizzler for Ps4 {
-    fn swizzle<T: Format>(
-        source: &mut [u8],
+    /// Swizzles a single image laid out as [`Ps4::DEFAULT_TILE_MODE`]. With `align_resolution`,
+    /// compressed images are tiled as if their dimensions were powers of two.
+    fn do_swizzle<T: Format>(
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
+        unswizzle: bool,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        ps4::do_swizzle(source, dest, dimensions, format, false, align_resolution);
-        Ok(())
+        let (width, height, depth) = dimensions;
+        let tiled_dimensions = if align_resolution && format.is_compressed() {
+            (width.next_power_of_two(), height.next_power_of_two(), depth)
+        } else {
+            dimensions
+        };
+        let layout = SurfaceLayout::for_format(
+            tiled_dimensions,
+            &format,
+            Self::DEFAULT_TILE_MODE,
+            Self::PIPE_CONFIG,
+        )?;
+        gcn::swizzle_layout(source, dest, dimensions, &layout, format, unswizzle)
     }
 }
 
-impl Deswizzler for Ps4 {
-    fn deswizzle<T: Format>(
-        source: &mut [u8],
+impl TextureLayout for Ps4 {
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        gcn::mip_chain(
+            desc,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
+        )
+    }
+}
+
+impl Swizzler for Ps4 {
+    fn swizzle<T: Format>(
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        ps4::do_swizzle(source, dest, dimensions, format, true, align_resolution);
-        Ok(())
+        Self::do_swizzle(source, dest, dimensions, format, false, align_resolution)
     }
-}
 
-mod ps4 {
-    use crate::swizzle::Format;
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        gcn::do_swizzle_mips(
+            source,
+            dest,
+            &chain,
+            desc.format(),
+            false,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
+        )?;
+        Ok(chain)
+    }
+}
 
-    pub fn do_swizzle<T: Format>(
-        source: &mut [u8],
+impl Deswizzler for Ps4 {
+    fn deswizzle<T: Format>(
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
-        unswizzle: bool,
         align_resolution: bool,
-    ) {
-        let (width, height, depth) = dimensions;
-        let pixel_block_size = format.pixel_block_size();
-        let block_size = format.block_size();
-
-        let (width_src, height_src) = if align_resolution && format.is_compressed() {
-            (width.next_power_of_two(), height.next_power_of_two())
-        } else {
-            (width, height)
-        };
+    ) -> Result<(), SwizzleError> {
+        Self::do_swizzle(source, dest, dimensions, format, true, align_resolution)
+    }
 
-        let width_texels_dest = width / pixel_block_size;
-        let height_texels_dest = height / pixel_block_size;
-
-        let width_texels = width_src / pixel_block_size;
-        let width_texels_aligned = width_texels.div_ceil(8);
-        let height_texels = height_src / pixel_block_size;
-        let height_texels_aligned = height_texels.div_ceil(8);
-        let mut data_index = 0;
-
-        for z in 0..depth {
-            let slice_dest = &mut dest[(z * width * height * format.bpp()) / 8..];
-
-            for y in 0..height_texels_aligned {
-                for x in 0..width_texels_aligned {
-                    for t in 0..64 {
-                        let pixel_index = crate::swizzle::morton(t, 8, 8);
-                        let div = pixel_index / 8;
-                        let rem = pixel_index % 8;
-                        let x_offset = (x * 8) + rem;
-                        let y_offset = (y * 8) + div;
-
-                        if x_offset < width_texels_dest && y_offset < height_texels_dest {
-                            let dest_pixel_index = y_offset * width_texels_dest + x_offset;
-                            let dest_index = block_size * dest_pixel_index;
-                            let (src, dst) = if unswizzle {
-                                (data_index, dest_index)
-                            } else {
-                                (dest_index, data_index)
-                            };
-
-                            if (src + block_size) <= source.len()
-                                && (dst + block_size) <= slice_dest.len()
-                            {
-                                slice_dest[dst..dst + block_size]
-                                    .copy_from_slice(&source[src..src + block_size]);
-                            }
-                        }
-
-                        data_index += block_size;
-                    }
-                }
-            }
-        }
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        gcn::do_swizzle_mips(
+            source,
+            dest,
+            &chain,
+            desc.format(),
+            true,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
+        )?;
+        Ok(chain)
     }
 }
@@ -0,0 +1,235 @@
+use super::{Format, MAX_MIP_LEVELS, SwizzleError, TextureKind, gcn::TileMode};
+
+/// How the resolution of a surface is padded before it is swizzled, on top of the platform's own
+/// tiling requirements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AlignmentPolicy {
+    /// Only the padding required by the platform
+    #[default]
+    Exact,
+    /// Compressed surfaces are padded to the next power of two
+    PowerOfTwo,
+}
+
+/// Everything needed to locate the texels of a surface in both the swizzled and the linear layout.
+///
+/// Built with [`SurfaceDesc::builder`]:
+/// ```
+/// # use swizzleinator::{format::GcnSurfaceFormat, swizzle::SurfaceDesc};
+/// let desc = SurfaceDesc::builder(GcnSurfaceFormat::BC7)
+///     .width(512)
+///     .height(256)
+///     .mip_count(10)
+///     .build()
+///     .unwrap();
+/// assert_eq!(desc.dimensions(), (512, 256, 1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SurfaceDesc<T: Format> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    mip_count: usize,
+    kind: TextureKind,
+    format: T,
+    pitch: Option<usize>,
+    tile_mode: Option<TileMode>,
+    alignment: AlignmentPolicy,
+}
+
+impl<T: Format> SurfaceDesc<T> {
+    pub fn builder(format: T) -> SurfaceDescBuilder<T> {
+        SurfaceDescBuilder {
+            desc: SurfaceDesc {
+                width: 1,
+                height: 1,
+                depth: 1,
+                mip_count: 1,
+                kind: TextureKind::Texture2D,
+                format,
+                pitch: None,
+                tile_mode: None,
+                alignment: AlignmentPolicy::Exact,
+            },
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Depth of a volume, 1 for any other kind of texture
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// `(width, height, depth)`, as taken by the tuple based methods
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.depth)
+    }
+
+    /// Number of mip levels per layer, including the base level
+    pub fn mip_count(&self) -> usize {
+        self.mip_count
+    }
+
+    pub fn kind(&self) -> TextureKind {
+        self.kind
+    }
+
+    /// Number of array layers, 6 for a cubemap
+    pub fn array_size(&self) -> usize {
+        self.kind.layers()
+    }
+
+    pub fn format(&self) -> &T {
+        &self.format
+    }
+
+    /// Pitch of the swizzled base level in pixels, if it is padded wider than `width`
+    pub fn pitch(&self) -> Option<usize> {
+        self.pitch
+    }
+
+    /// GCN tile mode of the surface, if it differs from the platform's default
+    pub fn tile_mode(&self) -> Option<TileMode> {
+        self.tile_mode
+    }
+
+    pub fn alignment(&self) -> AlignmentPolicy {
+        self.alignment
+    }
+
+    /// Builds a description from the arguments of the tuple based methods
+    pub(crate) fn from_tuple(
+        dimensions: (usize, usize, usize),
+        format: T,
+        kind: TextureKind,
+        mip_count: usize,
+        align_resolution: bool,
+    ) -> Result<Self, SwizzleError> {
+        SurfaceDesc::builder(format)
+            .width(dimensions.0)
+            .height(dimensions.1)
+            .depth(dimensions.2)
+            .kind(kind)
+            .mip_count(mip_count)
+            .alignment(if align_resolution {
+                AlignmentPolicy::PowerOfTwo
+            } else {
+                AlignmentPolicy::Exact
+            })
+            .build()
+    }
+
+    pub(crate) fn align_resolution(&self) -> bool {
+        self.alignment == AlignmentPolicy::PowerOfTwo
+    }
+
+    /// Widens the padded dimensions of the base level to the pitch of the surface
+    pub(crate) fn apply_pitch(
+        &self,
+        level: usize,
+        padded_dimensions: (usize, usize, usize),
+    ) -> (usize, usize, usize) {
+        match self.pitch {
+            Some(pitch) if level == 0 => (
+                padded_dimensions.0.max(pitch),
+                padded_dimensions.1,
+                padded_dimensions.2,
+            ),
+            _ => padded_dimensions,
+        }
+    }
+}
+
+/// Builder for [`SurfaceDesc`]. Every field defaults to a single 1x1 2D texel.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceDescBuilder<T: Format> {
+    desc: SurfaceDesc<T>,
+}
+
+impl<T: Format> SurfaceDescBuilder<T> {
+    pub fn width(mut self, width: usize) -> Self {
+        self.desc.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: usize) -> Self {
+        self.desc.height = height;
+        self
+    }
+
+    /// Depth of a volume. Anything above 1 needs a [`TextureKind::Volume`] kind.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.desc.depth = depth;
+        self
+    }
+
+    pub fn mip_count(mut self, mip_count: usize) -> Self {
+        self.desc.mip_count = mip_count;
+        self
+    }
+
+    /// Makes the surface an array with the given number of layers
+    pub fn array_size(mut self, array_size: usize) -> Self {
+        self.desc.kind = TextureKind::Array(array_size);
+        self
+    }
+
+    pub fn kind(mut self, kind: TextureKind) -> Self {
+        self.desc.kind = kind;
+        self
+    }
+
+    /// Pitch of the swizzled base level in pixels, as found in Xbox 360 fetch constants and GCN
+    /// T# descriptors. Platforms that derive the pitch from the width ignore it.
+    pub fn pitch(mut self, pitch: usize) -> Self {
+        self.desc.pitch = Some(pitch);
+        self
+    }
+
+    /// Tile mode used by the GCN platforms
+    pub fn tile_mode(mut self, tile_mode: TileMode) -> Self {
+        self.desc.tile_mode = Some(tile_mode);
+        self
+    }
+
+    pub fn alignment(mut self, alignment: AlignmentPolicy) -> Self {
+        self.desc.alignment = alignment;
+        self
+    }
+
+    pub fn build(self) -> Result<SurfaceDesc<T>, SwizzleError> {
+        let desc = self.desc;
+
+        let volume = desc.kind == TextureKind::Volume;
+        if desc.width == 0 || desc.height == 0 || desc.depth == 0 || (!volume && desc.depth > 1) {
+            return Err(SwizzleError::InvalidDimensions(
+                desc.width,
+                desc.height,
+                desc.depth,
+            ));
+        }
+
+        if desc.mip_count == 0 || desc.mip_count > MAX_MIP_LEVELS {
+            return Err(SwizzleError::InvalidMipCount(desc.mip_count));
+        }
+
+        if desc.kind.layers() == 0 {
+            return Err(SwizzleError::InvalidArraySize(desc.kind.layers()));
+        }
+
+        if let Some(pitch) = desc.pitch
+            && pitch < desc.width
+        {
+            return Err(SwizzleError::InvalidPitch(pitch));
+        }
+
+        Ok(desc)
+    }
+}
@@ -0,0 +1,380 @@
+// Tegra X1 block-linear layout, as described in the Tegra X1 TRM ("Block Linear Memory Format")
+
+use super::{Deswizzler, Format, MipChain, SurfaceDesc, SwizzleError, Swizzler, TextureLayout};
+
+pub struct Switch;
+
+impl Switch {
+    /// Swizzles an image using an explicit block height, in GOBs (1, 2, 4, 8, 16 or 32).
+    pub fn swizzle_with_block_height<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        align_resolution: bool,
+        block_height: usize,
+    ) -> Result<(), SwizzleError> {
+        tegra::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            false,
+            align_resolution,
+            Some((block_height, tegra::block_depth(dimensions.2))),
+        )
+    }
+
+    /// Deswizzles an image using an explicit block height, in GOBs (1, 2, 4, 8, 16 or 32).
+    pub fn deswizzle_with_block_height<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        align_resolution: bool,
+        block_height: usize,
+    ) -> Result<(), SwizzleError> {
+        tegra::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            true,
+            align_resolution,
+            Some((block_height, tegra::block_depth(dimensions.2))),
+        )
+    }
+
+    /// The block height (in GOBs) the Switch graphics driver picks for the base level of a
+    /// surface with the given height in blocks.
+    pub fn block_height_mip0(height_in_blocks: usize) -> usize {
+        tegra::block_height_mip0(height_in_blocks)
+    }
+}
+
+impl TextureLayout for Switch {
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        tegra::mip_chain(desc)
+    }
+}
+
+impl Swizzler for Switch {
+    fn swizzle<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        tegra::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            false,
+            align_resolution,
+            None,
+        )
+    }
+
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let format = desc.format();
+        let chain = Self::mip_chain(desc)?;
+        let block_height_mip0 = tegra::block_height_mip0(
+            chain[0]
+                .padded_dimensions
+                .1
+                .div_ceil(format.pixel_block_size()),
+        );
+        let block_depth_mip0 = tegra::block_depth(chain[0].dimensions.2);
+
+        chain.for_each_level(source, dest, false, |source, dest, level| {
+            let block_height = tegra::mip_block_height(
+                level
+                    .padded_dimensions
+                    .1
+                    .div_ceil(format.pixel_block_size()),
+                block_height_mip0,
+            );
+            let block_depth = tegra::mip_block_depth(level.dimensions.2, block_depth_mip0);
+            tegra::do_swizzle(
+                source,
+                dest,
+                level.dimensions,
+                format,
+                false,
+                desc.align_resolution(),
+                Some((block_height, block_depth)),
+            )
+        })?;
+        Ok(chain)
+    }
+}
+
+impl Deswizzler for Switch {
+    fn deswizzle<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        tegra::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            true,
+            align_resolution,
+            None,
+        )
+    }
+
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let format = desc.format();
+        let chain = Self::mip_chain(desc)?;
+        let block_height_mip0 = tegra::block_height_mip0(
+            chain[0]
+                .padded_dimensions
+                .1
+                .div_ceil(format.pixel_block_size()),
+        );
+        let block_depth_mip0 = tegra::block_depth(chain[0].dimensions.2);
+
+        chain.for_each_level(source, dest, true, |source, dest, level| {
+            let block_height = tegra::mip_block_height(
+                level
+                    .padded_dimensions
+                    .1
+                    .div_ceil(format.pixel_block_size()),
+                block_height_mip0,
+            );
+            let block_depth = tegra::mip_block_depth(level.dimensions.2, block_depth_mip0);
+            tegra::do_swizzle(
+                source,
+                dest,
+                level.dimensions,
+                format,
+                true,
+                desc.align_resolution(),
+                Some((block_height, block_depth)),
+            )
+        })?;
+        Ok(chain)
+    }
+}
+
+pub(crate) mod tegra {
+    use crate::swizzle::{Format, LayerLayout, MipChain, SurfaceDesc, SwizzleError};
+
+    pub const GOB_WIDTH_IN_BYTES: usize = 64;
+    pub const GOB_HEIGHT_IN_ROWS: usize = 8;
+    pub const GOB_SIZE_IN_BYTES: usize = GOB_WIDTH_IN_BYTES * GOB_HEIGHT_IN_ROWS;
+
+    pub fn block_height_mip0(height_in_blocks: usize) -> usize {
+        let height_in_gobs = height_in_blocks.div_ceil(GOB_HEIGHT_IN_ROWS);
+        let height_and_half = height_in_gobs + (height_in_gobs / 2);
+
+        match height_and_half {
+            128.. => 16,
+            64.. => 8,
+            32.. => 4,
+            16.. => 2,
+            _ => 1,
+        }
+    }
+
+    /// Block heights shrink along with the mip levels, so small levels don't waste whole blocks
+    pub fn mip_block_height(height_in_blocks: usize, block_height_mip0: usize) -> usize {
+        let mut block_height = block_height_mip0;
+        while block_height > 1 && height_in_blocks <= (block_height / 2) * GOB_HEIGHT_IN_ROWS {
+            block_height /= 2;
+        }
+        block_height
+    }
+
+    /// The block depth (in GOBs) the Switch graphics driver picks for the base level of a volume
+    /// with the given depth in slices.
+    pub fn block_depth(depth: usize) -> usize {
+        match depth + (depth / 2) {
+            16.. => 16,
+            8.. => 8,
+            4.. => 4,
+            2.. => 2,
+            _ => 1,
+        }
+    }
+
+    /// Block depths shrink along with the mip levels, just like block heights
+    pub fn mip_block_depth(depth: usize, block_depth_mip0: usize) -> usize {
+        let mut block_depth = block_depth_mip0;
+        while block_depth > 1 && depth <= block_depth / 2 {
+            block_depth /= 2;
+        }
+        block_depth
+    }
+
+    /// Mip levels are stored back to back, each one with its own block height and depth.
+    /// Array layers each hold their whole mip chain, and are aligned to a block of the base level.
+    pub fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        let format = desc.format();
+        let align_resolution = desc.align_resolution();
+        let pixel_block_size = format.pixel_block_size();
+        let base_height = if align_resolution && format.is_compressed() {
+            desc.height().next_power_of_two()
+        } else {
+            desc.height()
+        };
+        let base_block_height = block_height_mip0(base_height.div_ceil(pixel_block_size));
+        let base_block_depth = block_depth(desc.depth());
+
+        MipChain::new(
+            desc,
+            LayerLayout::LayerMajor(base_block_height * GOB_SIZE_IN_BYTES),
+            |_, (width, height, depth)| {
+                let (width_src, height_src) = if align_resolution && format.is_compressed() {
+                    (width.next_power_of_two(), height.next_power_of_two())
+                } else {
+                    (width, height)
+                };
+
+                let height_texels = height_src.div_ceil(pixel_block_size);
+
+                let size = swizzled_slice_size(
+                    width_src.div_ceil(pixel_block_size) * format.block_size()?,
+                    height_texels,
+                    mip_block_height(height_texels, base_block_height),
+                ) * depth.next_multiple_of(mip_block_depth(depth, base_block_depth));
+                Ok(((width_src, height_src, depth), size, 1))
+            },
+        )
+    }
+
+    pub fn validate_block_height(block_height: usize) -> Result<usize, SwizzleError> {
+        if block_height.is_power_of_two() && block_height <= 32 {
+            Ok(block_height)
+        } else {
+            Err(SwizzleError::InvalidBlockHeight(block_height))
+        }
+    }
+
+    /// Size in bytes of one swizzled slice with the given dimensions (in bytes and rows).
+    pub fn swizzled_slice_size(width_in_bytes: usize, height: usize, block_height: usize) -> usize {
+        let width_in_gobs = width_in_bytes.div_ceil(GOB_WIDTH_IN_BYTES);
+        let height_in_blocks = height.div_ceil(GOB_HEIGHT_IN_ROWS * block_height);
+
+        width_in_gobs * height_in_blocks * block_height * GOB_SIZE_IN_BYTES
+    }
+
+    /// Returns the offset of the byte at (`x`, `y`, `z`) within a block-linear slab, which is
+    /// `block_depth` slices deep. `x` is given in bytes, `y` in rows and `z` in slices.
+    pub fn gob_address(
+        x: usize,
+        y: usize,
+        z: usize,
+        width_in_gobs: usize,
+        block_height: usize,
+        block_depth: usize,
+    ) -> usize {
+        let block_height_in_rows = GOB_HEIGHT_IN_ROWS * block_height;
+        let block_size = GOB_SIZE_IN_BYTES * block_height * block_depth;
+
+        // GOBs in a block go down the rows first, then back through the slices
+        let block_offset = (y / block_height_in_rows) * block_size * width_in_gobs
+            + (x / GOB_WIDTH_IN_BYTES) * block_size
+            + (z % block_depth) * GOB_SIZE_IN_BYTES * block_height
+            + ((y % block_height_in_rows) / GOB_HEIGHT_IN_ROWS) * GOB_SIZE_IN_BYTES;
+
+        let gob_offset = ((x % 64) / 32) * 256
+            + ((y % 8) / 2) * 64
+            + ((x % 32) / 16) * 32
+            + (y % 2) * 16
+            + (x % 16);
+
+        block_offset + gob_offset
+    }
+
+    pub fn do_swizzle<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        unswizzle: bool,
+        align_resolution: bool,
+        blocks: Option<(usize, usize)>,
+    ) -> Result<(), SwizzleError> {
+        let (width, height, depth) = dimensions;
+        let pixel_block_size = format.pixel_block_size();
+        let block_size = format.block_size()?;
+
+        let (width_src, height_src) = if align_resolution && format.is_compressed() {
+            (width.next_power_of_two(), height.next_power_of_two())
+        } else {
+            (width, height)
+        };
+
+        let width_texels_dest = width.div_ceil(pixel_block_size);
+        let height_texels_dest = height.div_ceil(pixel_block_size);
+        let width_texels = width_src.div_ceil(pixel_block_size);
+        let height_texels = height_src.div_ceil(pixel_block_size);
+
+        // Explicit block height and depth, in GOBs
+        let (block_height, block_depth) = match blocks {
+            Some((height, depth)) => (validate_block_height(height)?, depth),
+            None => (block_height_mip0(height_texels), block_depth(depth)),
+        };
+
+        let width_in_bytes = width_texels * block_size;
+        let width_in_gobs = width_in_bytes.div_ceil(GOB_WIDTH_IN_BYTES);
+        let swizzled_slab_size =
+            swizzled_slice_size(width_in_bytes, height_texels, block_height) * block_depth;
+        let linear_slice_size = width_texels_dest * height_texels_dest * block_size;
+
+        // Blocks that evenly divide a 16-byte GOB sector are never split, so they can be copied whole
+        let chunk_size = if 16 % block_size == 0 { block_size } else { 1 };
+
+        for z in 0..depth {
+            let swizzled_base = (z / block_depth) * swizzled_slab_size;
+            let linear_base = z * linear_slice_size;
+
+            for y in 0..height_texels_dest.min(height_texels) {
+                for x in 0..width_texels_dest.min(width_texels) {
+                    for c in (0..block_size).step_by(chunk_size) {
+                        let swizzled_index = swizzled_base
+                            + gob_address(
+                                x * block_size + c,
+                                y,
+                                z,
+                                width_in_gobs,
+                                block_height,
+                                block_depth,
+                            );
+                        let linear_index =
+                            linear_base + (y * width_texels_dest + x) * block_size + c;
+
+                        let (src, dst) = if unswizzle {
+                            (swizzled_index, linear_index)
+                        } else {
+                            (linear_index, swizzled_index)
+                        };
+
+                        if (src + chunk_size) <= source.len() && (dst + chunk_size) <= dest.len() {
+                            dest[dst..dst + chunk_size]
+                                .copy_from_slice(&source[src..src + chunk_size]);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
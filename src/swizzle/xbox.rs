@@ -1,73 +1,633 @@
 // Adapted from https://github.com/bartlomiejduda/ReverseBox/blob/main/reversebox/image/swizzling/swizzle_x360.py
 
-use super::{Deswizzler, Format, SwizzleError, Swizzler};
+use super::{
+    Deswizzler, Format, MipChain, SurfaceDesc, SwizzleError, Swizzler, TextureLayout,
+    gcn::{self, PipeConfig, TileMode},
+    legacy_kind, rescale_unorm,
+};
+use crate::format::XenosSurfaceFormat;
 
 pub struct Xbox360;
 
+/// `GPUENDIAN`, the byte swap the Xenos applies to each word of a texture as it is fetched.
+/// Xenos data is big endian, so most formats need one to come out little endian.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum XenosEndian {
+    #[default]
+    None = 0,
+    /// Swaps the bytes of each 16-bit word
+    Swap8In16 = 1,
+    /// Reverses the bytes of each 32-bit word
+    Swap8In32 = 2,
+    /// Swaps the 16-bit halves of each 32-bit word
+    Swap16In32 = 3,
+}
+
+impl XenosEndian {
+    /// The swap used by the [`Swizzler`] and [`Deswizzler`] implementations and by the
+    /// `_packed` methods, when the endian of the texture isn't known:
+    /// [`XenosEndian::Swap8In16`] for the formats whose `x360_swap` is set, none otherwise.
+    pub fn for_format<T: Format>(format: &T) -> Self {
+        if format.x360_swap() {
+            XenosEndian::Swap8In16
+        } else {
+            XenosEndian::None
+        }
+    }
+
+    /// Size in bytes of the words whose bytes are swapped
+    pub fn word_size(self) -> usize {
+        match self {
+            XenosEndian::None => 1,
+            XenosEndian::Swap8In16 => 2,
+            XenosEndian::Swap8In32 | XenosEndian::Swap16In32 => 4,
+        }
+    }
+
+    /// Address bits flipped by the swap: the byte at `a` moves to `a ^ address_mask`
+    fn address_mask(self) -> usize {
+        match self {
+            XenosEndian::None => 0,
+            XenosEndian::Swap8In16 => 1,
+            XenosEndian::Swap8In32 => 3,
+            XenosEndian::Swap16In32 => 2,
+        }
+    }
+
+    /// Swaps every word of `data` in place. Swapping twice restores the original data.
+    /// Trailing bytes that don't fill a whole word are left as they are.
+    pub fn swap_in_place(self, data: &mut [u8]) {
+        match self {
+            XenosEndian::None => {}
+            XenosEndian::Swap8In16 => data.chunks_exact_mut(2).for_each(|word| word.swap(0, 1)),
+            XenosEndian::Swap8In32 => data.chunks_exact_mut(4).for_each(|word| word.reverse()),
+            XenosEndian::Swap16In32 => data
+                .chunks_exact_mut(4)
+                .for_each(|word| word.rotate_left(2)),
+        }
+    }
+}
+
+impl Xbox360 {
+    /// Swizzles a full mip chain.
+    /// * `packed_mips` - See [`Xbox360::swizzle_surface_packed`].
+    pub fn swizzle_mips_packed<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        mip_count: usize,
+        align_resolution: bool,
+        packed_mips: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        let desc = SurfaceDesc::from_tuple(
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            mip_count,
+            align_resolution,
+        )?;
+        Self::swizzle_surface_packed(source, dest, &desc, packed_mips)
+    }
+
+    /// Deswizzles a full mip chain.
+    /// * `packed_mips` - See [`Xbox360::swizzle_surface_packed`].
+    pub fn deswizzle_mips_packed<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        mip_count: usize,
+        align_resolution: bool,
+        packed_mips: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        let desc = SurfaceDesc::from_tuple(
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            mip_count,
+            align_resolution,
+        )?;
+        Self::deswizzle_surface_packed(source, dest, &desc, packed_mips)
+    }
+
+    /// Swizzles the surface described by `desc`.
+    /// * `packed_mips` - Store every level whose shorter side is 16 texels or less in a single
+    ///   shared tail tile, as the `packed_mips` bit of the texture fetch constant requests. The
+    ///   surface methods of the [`Swizzler`] and [`Deswizzler`] implementations always pack them,
+    ///   their single image methods never do, so a lone base level stays at the start of its tile.
+    pub fn swizzle_surface_packed<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+        packed_mips: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::swizzle_surface_with_endian(
+            source,
+            dest,
+            desc,
+            packed_mips,
+            XenosEndian::for_format(&desc.format()),
+        )
+    }
+
+    /// Deswizzles the surface described by `desc`.
+    /// * `packed_mips` - See [`Xbox360::swizzle_surface_packed`].
+    pub fn deswizzle_surface_packed<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+        packed_mips: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::deswizzle_surface_with_endian(
+            source,
+            dest,
+            desc,
+            packed_mips,
+            XenosEndian::for_format(&desc.format()),
+        )
+    }
+
+    /// Swizzles the surface described by `desc`, swapping its byte order with `endian`, such as
+    /// the one stored in the texture fetch constant.
+    /// * `packed_mips` - See [`Xbox360::swizzle_surface_packed`].
+    pub fn swizzle_surface_with_endian<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+        packed_mips: bool,
+        endian: XenosEndian,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = x360::mip_chain(desc, packed_mips)?;
+        x360::do_swizzle_mips(source, dest, &chain, desc.format(), endian, false)?;
+        Ok(chain)
+    }
+
+    /// Deswizzles the surface described by `desc`, swapping its byte order with `endian`, such as
+    /// the one stored in the texture fetch constant.
+    /// * `packed_mips` - See [`Xbox360::swizzle_surface_packed`].
+    pub fn deswizzle_surface_with_endian<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+        packed_mips: bool,
+        endian: XenosEndian,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = x360::mip_chain(desc, packed_mips)?;
+        x360::do_swizzle_mips(source, dest, &chain, desc.format(), endian, true)?;
+        Ok(chain)
+    }
+
+    /// Swaps the byte order of every 16-bit word of `data` in place, converting it between the
+    /// big endian layout of Xenos textures and little endian. A trailing odd byte is left as it
+    /// is. The swizzle methods swap with [`XenosEndian::for_format`] on their own, this is for
+    /// data that wasn't tiled.
+    pub fn swap_byte_order_in_place(data: &mut [u8]) {
+        XenosEndian::Swap8In16.swap_in_place(data);
+    }
+}
+
+impl TextureLayout for Xbox360 {
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        x360::mip_chain(desc, true)
+    }
+}
+
 impl Swizzler for Xbox360 {
     fn swizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
-        dimentions: (usize, usize, usize),
+        dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        x360::do_swizzle(source, dest, dimentions, format, false, align_resolution)
+        let desc = SurfaceDesc::from_tuple(
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            1,
+            align_resolution,
+        )?;
+        Self::swizzle_surface_packed(source, dest, &desc, false)?;
+        Ok(())
+    }
+
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::swizzle_surface_packed(source, dest, desc, true)
     }
 }
 
 impl Deswizzler for Xbox360 {
     fn deswizzle<T: Format>(
-        source: &mut [u8],
+        source: &[u8],
         dest: &mut [u8],
-        dimentions: (usize, usize, usize),
+        dimensions: (usize, usize, usize),
         format: T,
         align_resolution: bool,
     ) -> Result<(), SwizzleError> {
-        x360::do_swizzle(source, dest, dimentions, format, true, align_resolution)
+        let desc = SurfaceDesc::from_tuple(
+            dimensions,
+            format,
+            legacy_kind(dimensions),
+            1,
+            align_resolution,
+        )?;
+        Self::deswizzle_surface_packed(source, dest, &desc, false)?;
+        Ok(())
+    }
+
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        Self::deswizzle_surface_packed(source, dest, desc, true)
+    }
+}
+
+/// `GPUSWIZZLE`, where a component of a fetched texel comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum XenosComponent {
+    X = 0,
+    Y = 1,
+    Z = 2,
+    W = 3,
+    Zero = 4,
+    One = 5,
+    /// Leaves the component as it is
+    Keep = 7,
+}
+
+/// The X, Y, Z and W component swizzle of a texture fetch constant, which the Xenos applies to
+/// each texel as it is fetched. The [`Xbox360`] swizzle methods don't apply it, call
+/// [`XenosSwizzle::apply`] on the deswizzled data to get the components a shader would see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XenosSwizzle(pub [XenosComponent; 4]);
+
+impl Default for XenosSwizzle {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl XenosSwizzle {
+    pub const IDENTITY: Self = XenosSwizzle([
+        XenosComponent::X,
+        XenosComponent::Y,
+        XenosComponent::Z,
+        XenosComponent::W,
+    ]);
+
+    /// Parses the 12 bit swizzle field of a fetch constant, 3 bits per component
+    pub fn from_bits(bits: u32) -> Self {
+        XenosSwizzle(core::array::from_fn(|i| match (bits >> (i * 3)) & 7 {
+            0 => XenosComponent::X,
+            1 => XenosComponent::Y,
+            2 => XenosComponent::Z,
+            3 => XenosComponent::W,
+            4 => XenosComponent::Zero,
+            5 => XenosComponent::One,
+            _ => XenosComponent::Keep,
+        }))
+    }
+
+    pub fn to_bits(self) -> u32 {
+        self.0.iter().enumerate().fold(0, |bits, (i, &component)| {
+            bits | (component as u32) << (i * 3)
+        })
+    }
+
+    /// Swizzles the components of every texel of `data`, which holds little endian texels of
+    /// `format`. Only the components the format has are written, and components it lacks read as
+    /// zero. Components of different widths are rescaled.
+    /// Compressed formats, and formats without [`XenosSurfaceFormat::component_bits`], are left
+    /// as they are.
+    pub fn apply(self, data: &mut [u8], format: XenosSurfaceFormat) {
+        let (Some(bits), Ok(texel_size)) = (format.component_bits(), format.block_size()) else {
+            return;
+        };
+        if self == Self::IDENTITY || format.is_compressed() {
+            return;
+        }
+
+        let shifts: [u32; 4] = core::array::from_fn(|i| bits.iter().take(i).sum());
+        let mask = |width: u32| u128::MAX >> (128 - width);
+        let one = |width: u32| match (format.is_float(), width) {
+            (true, 16) => 0x3C00,
+            (true, 32) => 0x3F80_0000,
+            (_, width) => mask(width),
+        };
+
+        for texel in data.chunks_exact_mut(texel_size) {
+            let mut bytes = [0u8; 16];
+            bytes[..texel_size].copy_from_slice(texel);
+            let value = u128::from_le_bytes(bytes);
+            let component = |i: usize| (value >> shifts[i]) & mask(bits[i]);
+
+            let mut swizzled = 0;
+            for (i, &width) in bits.iter().enumerate() {
+                let result = match self.0[i] {
+                    XenosComponent::Zero => 0,
+                    XenosComponent::One => one(width),
+                    XenosComponent::Keep => component(i),
+                    source => match bits.get(source as usize) {
+                        Some(&source_width) => {
+                            rescale_unorm(component(source as usize), source_width, width)
+                        }
+                        None => 0,
+                    },
+                };
+                swizzled |= result << shifts[i];
+            }
+            texel.copy_from_slice(&swizzled.to_le_bytes()[..texel_size]);
+        }
     }
 }
 
-mod x360 {
-    use crate::swizzle::{Format, SwizzleError, TextureSlice};
+pub struct XboxOne;
+
+impl XboxOne {
+    /// Pipe configuration of the Durango GPU
+    pub const PIPE_CONFIG: PipeConfig = PipeConfig::P8_32x32_16x16;
+
+    /// Tile mode used by the `Swizzler`/`Deswizzler` implementations when a surface doesn't give
+    /// one. Most Xbox One textures are 2D thin, but 1D thin is the only mode checked against real
+    /// data, so textures in any other mode need their tile mode passed explicitly.
+    pub const DEFAULT_TILE_MODE: TileMode = TileMode::Thin_1dThin;
 
-    pub fn do_swizzle<T: Format>(
-        source: &mut [u8],
+    /// Swizzles an image using the given tile mode, such as the one stored in a `DDS_HEADER_XBOX`.
+    pub fn swizzle_with_tile_mode<T: Format>(
+        source: &[u8],
         dest: &mut [u8],
         dimensions: (usize, usize, usize),
         format: T,
-        unswizzle: bool,
-        align_resolution: bool,
+        tile_mode: TileMode,
     ) -> Result<(), SwizzleError> {
-        if format.x360_swap() {
-            swap_byte_order_x360(source);
-        }
+        gcn::do_swizzle(
+            source,
+            dest,
+            dimensions,
+            format,
+            false,
+            tile_mode,
+            Self::PIPE_CONFIG,
+        )
+    }
 
-        untile_x360_image_data(
-            &source,
+    /// Deswizzles an image using the given tile mode, such as the one stored in a `DDS_HEADER_XBOX`.
+    pub fn deswizzle_with_tile_mode<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        tile_mode: TileMode,
+    ) -> Result<(), SwizzleError> {
+        gcn::do_swizzle(
+            source,
             dest,
             dimensions,
-            format.pixel_block_size(),
-            format.block_size(),
-            unswizzle,
+            format,
+            true,
+            tile_mode,
+            Self::PIPE_CONFIG,
+        )
+    }
+}
+
+impl TextureLayout for XboxOne {
+    fn mip_chain<T: Format>(desc: &SurfaceDesc<T>) -> Result<MipChain, SwizzleError> {
+        gcn::mip_chain(
+            desc,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
+        )
+    }
+}
+
+impl Swizzler for XboxOne {
+    fn swizzle<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        _align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        Self::swizzle_with_tile_mode(source, dest, dimensions, format, Self::DEFAULT_TILE_MODE)
+    }
+
+    fn swizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        gcn::do_swizzle_mips(
+            source,
+            dest,
+            &chain,
+            desc.format(),
+            false,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
         )?;
+        Ok(chain)
+    }
+}
 
-        if format.x360_swap() {
-            for chunk in dest.chunks_exact_mut(4) {
-                let (x, y, z, w) = (chunk[1], chunk[2], chunk[3], chunk[0]);
-                chunk[0] = x;
-                chunk[1] = y;
-                chunk[2] = z;
-                chunk[3] = w;
+impl Deswizzler for XboxOne {
+    fn deswizzle<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        dimensions: (usize, usize, usize),
+        format: T,
+        _align_resolution: bool,
+    ) -> Result<(), SwizzleError> {
+        Self::deswizzle_with_tile_mode(source, dest, dimensions, format, Self::DEFAULT_TILE_MODE)
+    }
+
+    fn deswizzle_surface<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        desc: &SurfaceDesc<T>,
+    ) -> Result<MipChain, SwizzleError> {
+        let chain = Self::mip_chain(desc)?;
+        gcn::do_swizzle_mips(
+            source,
+            dest,
+            &chain,
+            desc.format(),
+            true,
+            desc.tile_mode().unwrap_or(Self::DEFAULT_TILE_MODE),
+            Self::PIPE_CONFIG,
+        )?;
+        Ok(chain)
+    }
+}
+
+pub(crate) mod x360 {
+    use super::XenosEndian;
+    use crate::swizzle::{
+        Format, LayerLayout, MipChain, MipLevel, SurfaceDesc, SwizzleError, TextureKind,
+        TextureSlice,
+    };
+
+    /// Mip levels are padded to a power of two and to whole 32x32 block tiles, each one starting
+    /// on a 4KB boundary. Volume textures are also padded to groups of 4 slices.
+    /// Each level holds that level of every cubemap face or array layer, 4KB aligned.
+    /// The mip chain is expected to directly follow the base level.
+    pub fn mip_chain<T: Format>(
+        desc: &SurfaceDesc<T>,
+        packed_mips: bool,
+    ) -> Result<MipChain, SwizzleError> {
+        let format = desc.format();
+        let kind = desc.kind();
+        let dimensions = desc.dimensions();
+        let align_resolution = desc.align_resolution();
+
+        let tiled_depth = |depth: usize| {
+            if kind == TextureKind::Volume {
+                depth.next_multiple_of(4)
+            } else {
+                depth
+            }
+        };
+
+        let mut chain = MipChain::new(
+            desc,
+            LayerLayout::LevelMajor(4096),
+            |level, (width, height, depth)| {
+                let tiled_dimensions = if level > 0 || (align_resolution && format.is_compressed())
+                {
+                    (
+                        width.next_power_of_two(),
+                        height.next_power_of_two(),
+                        tiled_depth(depth),
+                    )
+                } else {
+                    (width, height, tiled_depth(depth))
+                };
+                let tiled_dimensions = desc.apply_pitch(level, tiled_dimensions);
+
+                let padded_width_in_blocks =
+                    (tiled_dimensions.0.div_ceil(format.pixel_block_size()) + 31) & !31;
+                let padded_height_in_blocks =
+                    (tiled_dimensions.1.div_ceil(format.pixel_block_size()) + 31) & !31;
+                let slice_size =
+                    padded_width_in_blocks * padded_height_in_blocks * format.block_size()?;
+                let size = if kind == TextureKind::Volume {
+                    slice_size * tiled_dimensions.2
+                } else {
+                    slice_size.next_multiple_of(4096) * tiled_dimensions.2
+                };
+
+                Ok((tiled_dimensions, size, 4096))
+            },
+        )?;
+
+        if packed_mips {
+            let levels = chain.levels_mut();
+            let tail = (0..levels.len())
+                .find(|&i| xg_get_mip_tail_level_offset_coords(dimensions, i, 1).is_some());
+
+            if let Some(tail) = tail {
+                let tail_level = levels[tail];
+                for (i, level) in levels.iter_mut().enumerate().skip(tail) {
+                    level.swizzled_offset = tail_level.swizzled_offset;
+                    level.swizzled_size = tail_level.swizzled_size;
+                    level.swizzled_layer_stride = tail_level.swizzled_layer_stride;
+                    level.padded_dimensions = tail_level.padded_dimensions;
+                    level.packed_offset = xg_get_mip_tail_level_offset_coords(
+                        dimensions,
+                        i,
+                        format.pixel_block_size(),
+                    )
+                    .unwrap_or_default();
+                }
             }
         }
-        Ok(())
+
+        Ok(chain)
     }
 
-    pub fn swap_byte_order_x360(image_data: &mut [u8]) {
-        for chunk in image_data.chunks_mut(2) {
-            chunk.swap(0, 1);
+    /// Returns the position (in blocks) of a mip level inside the packed mip tail, or `None` if
+    /// the level is large enough to be stored on its own.
+    /// Port of `XGGetMipTailLevelOffsetCoords`, based on Xenia's `GetPackedMipOffset`.
+    pub fn xg_get_mip_tail_level_offset_coords(
+        base_dimensions: (usize, usize, usize),
+        level: usize,
+        block_pixel_size: usize,
+    ) -> Option<(usize, usize, usize)> {
+        let (width, height, depth) = base_dimensions;
+        let log2_width = width.next_power_of_two().ilog2() as usize;
+        let log2_height = height.next_power_of_two().ilog2() as usize;
+        let log2_size = log2_width.min(log2_height);
+
+        // The shorter side is still larger than 16 texels
+        if log2_size > 4 + level {
+            return None;
         }
+
+        let packed_mip_base = log2_size.saturating_sub(4);
+        let packed_mip = level - packed_mip_base;
+
+        let (x, y, z) = if packed_mip < 3 {
+            if log2_width > log2_height {
+                // Wider than tall, laid out vertically
+                (0, 16 >> packed_mip, 0)
+            } else {
+                // Taller than wide, laid out horizontally
+                (16 >> packed_mip, 0, 0)
+            }
+        } else {
+            let (x, y, offset) = if log2_width > log2_height {
+                let offset = (1 << (log2_width - packed_mip_base)) >> (packed_mip - 2);
+                (offset, 0, offset)
+            } else {
+                let offset = (1 << (log2_height - packed_mip_base)) >> (packed_mip - 2);
+                (0, offset, offset)
+            };
+
+            // Volume textures pack their smallest levels along Z
+            let z = if offset < 4 && depth > 1 {
+                let log2_depth = depth.next_power_of_two().ilog2() as usize;
+                if log2_depth > 1 + packed_mip {
+                    (log2_depth - packed_mip) * 4
+                } else {
+                    4
+                }
+            } else {
+                0
+            };
+            (x, y, z)
+        };
+
+        Some((x / block_pixel_size, y / block_pixel_size, z))
+    }
+
+    /// Swizzles every level of a mip chain.
+    pub fn do_swizzle_mips<T: Format>(
+        source: &[u8],
+        dest: &mut [u8],
+        chain: &MipChain,
+        format: T,
+        endian: XenosEndian,
+        unswizzle: bool,
+    ) -> Result<(), SwizzleError> {
+        // Every level of a volume texture is 3D tiled, even once its depth has shrunk to 1
+        let untile = if chain[0].dimensions.2 > 1 {
+            untile_x360_volume_data
+        } else {
+            untile_x360_image_data
+        };
+        chain.for_each_level(source, dest, unswizzle, |source, dest, level| {
+            untile(source, dest, level, &format, endian, unswizzle)
+        })
     }
 
     fn xg_address_2d_tiled_x(
@@ -117,84 +677,202 @@ mod x360 {
         macro_ + micro + ((offset_tile & 0x10) >> 4)
     }
 
-    fn untile_x360_image_data(
+    /// Returns the offset (in blocks) of the block at (`x`, `y`, `z`) within a 3D tiled volume.
+    /// Volumes are tiled in 32x16x4 block macro tiles, with each group of 4 slices interleaved
+    /// inside of them.
+    /// Port of `XGAddress3DTiledOffset`, not yet checked against a real volume.
+    pub fn xg_address_3d_tiled_offset(
+        x: usize,
+        y: usize,
+        z: usize,
+        width_in_blocks: usize,
+        height_in_blocks: usize,
+        texel_byte_pitch: usize,
+    ) -> usize {
+        let aligned_width = (width_in_blocks + 31) & !31;
+        let aligned_height = (height_in_blocks + 31) & !31;
+        let log_bpp =
+            (texel_byte_pitch >> 2) + ((texel_byte_pitch >> 1) >> (texel_byte_pitch >> 2));
+
+        let macro_ = ((((z >> 2) * (aligned_height >> 4) + (y >> 4)) * (aligned_width >> 5))
+            + (x >> 5))
+            << (log_bpp + 8);
+        let micro = (((y & 6) << 2) + (x & 7)) << log_bpp;
+        let offset = macro_
+            + ((micro & !0xF) << 1)
+            + (micro & 0xF)
+            + ((z & 3) << (log_bpp + 6))
+            + ((y & 1) << 4);
+
+        (((offset & !0x1FF) << 3)
+            + ((offset & 0x1C0) << 2)
+            + (offset & 0x3F)
+            + ((y & 8) << 8)
+            + (((((y & 8) >> 2) + (x >> 3) + (z & 3)) & 3) << 6))
+            >> log_bpp
+    }
+
+    /// Copies a texel between the tiled and the linear data. The tiled bytes are read or written
+    /// where `endian` swaps them to, so texels smaller than a word are swapped with their
+    /// neighbours in the tile rather than in the linear rows.
+    fn copy_texel(
+        source: &[u8],
+        dest: &mut [u8],
+        (src, dst): (usize, usize),
+        size: usize,
+        endian: XenosEndian,
+        deswizzle: bool,
+    ) {
+        if src + size > source.len() || dst + size > dest.len() {
+            return;
+        }
+
+        let mask = endian.address_mask();
+        if mask == 0 {
+            dest[dst..dst + size].copy_from_slice(&source[src..src + size]);
+            return;
+        }
+
+        for i in 0..size {
+            let (src, dst) = if deswizzle {
+                ((src + i) ^ mask, dst + i)
+            } else {
+                (src + i, (dst + i) ^ mask)
+            };
+            if let (Some(&byte), Some(out)) = (source.get(src), dest.get_mut(dst)) {
+                *out = byte;
+            }
+        }
+    }
+
+    fn untile_x360_volume_data<T: Format>(
         image_data: &[u8],
         dest: &mut [u8],
-        dimensions: (usize, usize, usize),
-        block_pixel_size: usize,
-        texel_byte_pitch: usize,
+        level: &MipLevel,
+        format: &T,
+        endian: XenosEndian,
+        deswizzle: bool,
+    ) -> Result<(), SwizzleError> {
+        let (image_width, image_height, image_depth) = level.dimensions;
+        let (tiled_width, tiled_height, _) = level.padded_dimensions;
+        let (offset_x, offset_y, offset_z) = level.packed_offset;
+        let block_pixel_size = format.pixel_block_size();
+        let texel_byte_pitch = format.block_size()?;
+
+        let width_in_blocks = image_width.div_ceil(block_pixel_size);
+        let height_in_blocks = image_height.div_ceil(block_pixel_size);
+
+        let padded_width_in_blocks = (tiled_width.div_ceil(block_pixel_size) + 31) & !31;
+        let padded_height_in_blocks = (tiled_height.div_ceil(block_pixel_size) + 31) & !31;
+
+        for z in 0..image_depth {
+            for y in 0..height_in_blocks {
+                for x in 0..width_in_blocks {
+                    let tiled_byte_offset = xg_address_3d_tiled_offset(
+                        x + offset_x,
+                        y + offset_y,
+                        z + offset_z,
+                        padded_width_in_blocks,
+                        padded_height_in_blocks,
+                        texel_byte_pitch,
+                    ) * texel_byte_pitch;
+                    let linear_byte_offset =
+                        ((z * height_in_blocks + y) * width_in_blocks + x) * texel_byte_pitch;
+
+                    let (src, dst) = if deswizzle {
+                        (tiled_byte_offset, linear_byte_offset)
+                    } else {
+                        (linear_byte_offset, tiled_byte_offset)
+                    };
+
+                    copy_texel(
+                        image_data,
+                        dest,
+                        (src, dst),
+                        texel_byte_pitch,
+                        endian,
+                        deswizzle,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn untile_x360_image_data<T: Format>(
+        image_data: &[u8],
+        dest: &mut [u8],
+        level: &MipLevel,
+        format: &T,
+        endian: XenosEndian,
         deswizzle: bool,
     ) -> Result<(), SwizzleError> {
-        let (image_width, image_height, image_depth) = dimensions;
+        let (image_width, image_height, image_depth) = level.dimensions;
+        let (tiled_width, tiled_height, _) = level.padded_dimensions;
+        let (offset_x, offset_y, _) = level.packed_offset;
+        let block_pixel_size = format.pixel_block_size();
+        let texel_byte_pitch = format.block_size()?;
+
+        let width_in_blocks = image_width.div_ceil(block_pixel_size);
+        let height_in_blocks = image_height.div_ceil(block_pixel_size);
 
-        let width_in_blocks = image_width / block_pixel_size;
-        let height_in_blocks = image_height / block_pixel_size;
+        let padded_width_in_blocks = (tiled_width.div_ceil(block_pixel_size) + 31) & !31;
+        let padded_height_in_blocks = (tiled_height.div_ceil(block_pixel_size) + 31) & !31;
 
-        let padded_width_in_blocks = (width_in_blocks + 31) & !31;
-        let padded_height_in_blocks = (height_in_blocks + 31) & !31;
+        // Tiles of small texels are spread over 4KB, even when they hold less than that
+        let tiled_slice_size =
+            (padded_width_in_blocks * padded_height_in_blocks * texel_byte_pitch)
+                .next_multiple_of(4096);
+        let linear_slice_size = width_in_blocks * height_in_blocks * texel_byte_pitch;
 
-        let slice_size = padded_width_in_blocks * padded_height_in_blocks * texel_byte_pitch;
+        let (source_slice_size, dest_slice_size) = if deswizzle {
+            (tiled_slice_size, linear_slice_size)
+        } else {
+            (linear_slice_size, tiled_slice_size)
+        };
 
         for slice in 0..image_depth {
-            let Some(slice_src) = image_data.get(slice * slice_size..) else {
+            let Some(slice_src) = image_data.get(slice * source_slice_size..) else {
                 return Err(SwizzleError::OutOfBounds(TextureSlice::Source));
             };
 
-            let Some(slice_dest) = dest.get_mut(slice * slice_size..) else {
+            let Some(slice_dest) = dest.get_mut(slice * dest_slice_size..) else {
                 return Err(SwizzleError::OutOfBounds(TextureSlice::Dest));
             };
 
-            for j in 0..padded_height_in_blocks {
-                for i in 0..padded_width_in_blocks {
-                    let block_offset = j * padded_width_in_blocks + i;
+            for block_offset in 0..tiled_slice_size / texel_byte_pitch {
+                let x =
+                    xg_address_2d_tiled_x(block_offset, padded_width_in_blocks, texel_byte_pitch);
 
-                    let x = xg_address_2d_tiled_x(
-                        block_offset,
-                        padded_width_in_blocks,
-                        texel_byte_pitch,
-                    );
-
-                    let y = xg_address_2d_tiled_y(
-                        block_offset,
-                        padded_width_in_blocks,
-                        texel_byte_pitch,
-                    );
+                let y =
+                    xg_address_2d_tiled_y(block_offset, padded_width_in_blocks, texel_byte_pitch);
 
-                    let src_byte_offset = block_offset * texel_byte_pitch;
-
-                    let dest_byte_offset = (y * width_in_blocks + x) * texel_byte_pitch;
-
-                    if dest_byte_offset + texel_byte_pitch > slice_dest.len()
-                        || src_byte_offset + texel_byte_pitch > slice_src.len()
-                    {
-                        continue;
-                    }
-
-                    if deswizzle {
-                        match slice_src.get(src_byte_offset..src_byte_offset + texel_byte_pitch) {
-                            Some(source) => {
-                                if source.iter().all(|&b| b == 0) {
-                                    continue;
-                                }
-                                slice_dest[dest_byte_offset..dest_byte_offset + texel_byte_pitch]
-                                    .copy_from_slice(source);
-                            }
-                            None => {
-                                continue;
-                            }
-                        }
-                    } else {
-                        match slice_src.get(dest_byte_offset..dest_byte_offset + texel_byte_pitch) {
-                            Some(source) => {
-                                slice_dest[src_byte_offset..src_byte_offset + texel_byte_pitch]
-                                    .copy_from_slice(source);
-                            }
-                            None => {
-                                continue;
-                            }
-                        }
-                    }
+                // Padding blocks and other levels of a packed mip tail have no place in the
+                // linear image
+                let (Some(x), Some(y)) = (x.checked_sub(offset_x), y.checked_sub(offset_y)) else {
+                    continue;
+                };
+                if x >= width_in_blocks || y >= height_in_blocks {
+                    continue;
                 }
+
+                let tiled_byte_offset = block_offset * texel_byte_pitch;
+                let linear_byte_offset = (y * width_in_blocks + x) * texel_byte_pitch;
+
+                let (src, dst) = if deswizzle {
+                    (tiled_byte_offset, linear_byte_offset)
+                } else {
+                    (linear_byte_offset, tiled_byte_offset)
+                };
+
+                copy_texel(
+                    slice_src,
+                    slice_dest,
+                    (src, dst),
+                    texel_byte_pitch,
+                    endian,
+                    deswizzle,
+                );
             }
         }
         Ok(())
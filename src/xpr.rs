@@ -0,0 +1,346 @@
+//! Reading Xbox 360 texture fetch constants and XPR2 resource packages.
+//!
+//! Every Xenos texture is described by a `GPUTEXTURE_FETCH_CONSTANT`: six dwords holding its
+//! format, dimensions, pitch, tiling and mip addresses. [`FetchConstant`] decodes them into a
+//! [`SurfaceDesc`] and drives [`Xbox360`] with the matching settings.
+//!
+//! ```
+//! # use swizzleinator::{swizzle::{TextureLayout, xbox::Xbox360}, xpr::FetchConstant};
+//! // A tiled 64x64 DXT1 texture with 7 packed mip levels
+//! let fetch = FetchConstant::from_words([
+//!     0x8000_0002,
+//!     0x0000_0052,
+//!     0x0007_E03F,
+//!     0x0000_0D10,
+//!     0x0000_0180,
+//!     0x0000_2A00,
+//! ])
+//! .unwrap();
+//! let desc = fetch.surface_desc().unwrap();
+//!
+//! let data = vec![0u8; Xbox360::swizzled_size(&desc).unwrap()];
+//! let mut linear = vec![0u8; Xbox360::linear_size(&desc).unwrap()];
+//! fetch.deswizzle(&data, &mut linear).unwrap();
+//! ```
+//!
+//! [`Xbox360`]: crate::swizzle::xbox::Xbox360
+
+use crate::{
+    bytes::{read_u32_be, slice},
+    format::XenosSurfaceFormat,
+    swizzle::{
+        Format, MipChain, SurfaceDesc, SwizzleError, TextureKind, TextureLayout, TextureSlice,
+        xbox::{Xbox360, XenosEndian, XenosSwizzle},
+    },
+};
+
+const MAGIC: u32 = u32::from_be_bytes(*b"XPR2");
+/// The magic, header size and data size come before the part of the header that offsets are
+/// relative to
+const HEADER_BASE: usize = 12;
+const RESOURCE_SIZE: usize = 16;
+/// Size of the `D3DResource` fields of a `D3DBaseTexture` that come before its fetch constant
+const TEXTURE_RESOURCE_SIZE: usize = 28;
+
+/// `GPUDIMENSION`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TextureDimension {
+    Texture1D = 0,
+    Texture2D = 1,
+    Texture3D = 2,
+    Cube = 3,
+}
+
+/// A decoded `GPUTEXTURE_FETCH_CONSTANT`. Fields that only affect sampling are not kept.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub struct FetchConstant {
+    /// Whether the texture is tiled, rather than stored linearly
+    pub tiled: bool,
+    /// Pitch of the base level in texels
+    pub pitch: usize,
+    pub format: XenosSurfaceFormat,
+    pub endian: XenosEndian,
+    /// Whether a 2D texture is an array
+    pub stacked: bool,
+    /// Address of the base level in bytes
+    pub base_address: u32,
+    pub width: usize,
+    pub height: usize,
+    /// Depth of a volume, or the number of layers of an array
+    pub depth: usize,
+    /// Applied by [`FetchConstant::deswizzle`], set it to [`XenosSwizzle::IDENTITY`] to keep the
+    /// components as they are stored
+    pub swizzle: XenosSwizzle,
+    pub mip_min_level: usize,
+    pub mip_max_level: usize,
+    pub dimension: TextureDimension,
+    /// Whether the smallest mip levels share a single tile
+    pub packed_mips: bool,
+    /// Address of the first mip level in bytes, 0 if it directly follows the base level
+    pub mip_address: u32,
+}
+
+impl FetchConstant {
+    pub fn from_words(words: [u32; 6]) -> Result<Self, SwizzleError> {
+        let dimension = match (words[5] >> 9) & 3 {
+            0 => TextureDimension::Texture1D,
+            1 => TextureDimension::Texture2D,
+            2 => TextureDimension::Texture3D,
+            _ => TextureDimension::Cube,
+        };
+        let (width, height, depth) = match dimension {
+            TextureDimension::Texture1D => (words[2] & 0xFFFFFF, 0, 0),
+            TextureDimension::Texture2D | TextureDimension::Cube => (
+                words[2] & 0x1FFF,
+                (words[2] >> 13) & 0x1FFF,
+                (words[2] >> 26) & 0x3F,
+            ),
+            TextureDimension::Texture3D => (
+                words[2] & 0x7FF,
+                (words[2] >> 11) & 0x7FF,
+                (words[2] >> 22) & 0x3FF,
+            ),
+        };
+        Ok(FetchConstant {
+            tiled: words[0] >> 31 != 0,
+            pitch: (((words[0] >> 22) & 0x1FF) as usize) << 5,
+            format: XenosSurfaceFormat::try_from((words[1] & 0x3F) as u8)?,
+            endian: match (words[1] >> 6) & 3 {
+                0 => XenosEndian::None,
+                1 => XenosEndian::Swap8In16,
+                2 => XenosEndian::Swap8In32,
+                _ => XenosEndian::Swap16In32,
+            },
+            stacked: (words[1] >> 10) & 1 != 0,
+            base_address: words[1] & 0xFFFFF000,
+            width: width as usize + 1,
+            height: height as usize + 1,
+            depth: depth as usize + 1,
+            swizzle: XenosSwizzle::from_bits(words[3] >> 1),
+            mip_min_level: ((words[4] >> 2) & 0xF) as usize,
+            mip_max_level: ((words[4] >> 6) & 0xF) as usize,
+            dimension,
+            packed_mips: (words[5] >> 11) & 1 != 0,
+            mip_address: words[5] & 0xFFFFF000,
+        })
+    }
+
+    /// Describes the surface for [`Xbox360`]. The mip chain starts at level 0 and runs up to
+    /// `mip_max_level`.
+    pub fn surface_desc(&self) -> Result<SurfaceDesc<XenosSurfaceFormat>, SwizzleError> {
+        let (kind, depth) = match self.dimension {
+            TextureDimension::Texture1D => (TextureKind::Texture2D, 1),
+            TextureDimension::Texture2D if self.stacked => (TextureKind::Array(self.depth), 1),
+            TextureDimension::Texture2D => (TextureKind::Texture2D, 1),
+            TextureDimension::Texture3D => (TextureKind::Volume, self.depth),
+            TextureDimension::Cube => (TextureKind::Cube, 1),
+        };
+
+        let mut builder = SurfaceDesc::builder(self.format)
+            .width(self.width)
+            .height(self.height)
+            .kind(kind)
+            .depth(depth)
+            .mip_count(self.mip_max_level + 1);
+        if self.pitch >= self.width {
+            builder = builder.pitch(self.pitch);
+        }
+        builder.build()
+    }
+
+    /// Deswizzles the texture into `dest`, laid out like [`deswizzle_surface`] does.
+    /// * `data` - The texture data, starting at the base address.
+    ///
+    /// The mip levels must directly follow the base level. Byte order is swapped with `endian`,
+    /// then the components of uncompressed texels are reordered with `swizzle`.
+    ///
+    /// [`deswizzle_surface`]: crate::swizzle::Deswizzler::deswizzle_surface
+    pub fn deswizzle(&self, data: &[u8], dest: &mut [u8]) -> Result<MipChain, SwizzleError> {
+        let desc = self.surface_desc()?;
+
+        if !self.tiled {
+            return self.copy_linear(&desc, data, dest);
+        }
+
+        let chain = Xbox360::mip_chain(&desc)?;
+        if chain.len() > 1
+            && self.mip_address != 0
+            && self.mip_address.wrapping_sub(self.base_address) as usize != chain[1].swizzled_offset
+        {
+            return Err(SwizzleError::InvalidMipAddress(self.mip_address));
+        }
+
+        Xbox360::deswizzle_surface_with_endian(data, dest, &desc, self.packed_mips, self.endian)?;
+        self.swizzle
+            .apply(&mut dest[..chain.linear_size()], self.format);
+        Ok(chain)
+    }
+
+    /// Copies the rows of an untiled texture, which only has a base level
+    fn copy_linear(
+        &self,
+        desc: &SurfaceDesc<XenosSurfaceFormat>,
+        data: &[u8],
+        dest: &mut [u8],
+    ) -> Result<MipChain, SwizzleError> {
+        if desc.mip_count() > 1 {
+            return Err(SwizzleError::InvalidMipCount(desc.mip_count()));
+        }
+
+        let format = desc.format();
+        let chain = Xbox360::mip_chain(desc)?;
+        let dest = dest
+            .get_mut(..chain.linear_size())
+            .ok_or(SwizzleError::OutOfBounds(TextureSlice::Dest))?;
+
+        let block_size = format.block_size()?;
+        let row_size = desc.width().div_ceil(format.pixel_block_size()) * block_size;
+        let pitch = desc
+            .pitch()
+            .unwrap_or(desc.width())
+            .div_ceil(format.pixel_block_size())
+            * block_size;
+        let rows = desc.height().div_ceil(format.pixel_block_size());
+        let slices = desc.array_size() * desc.depth();
+        let slice_size = (pitch * rows).next_multiple_of(4096);
+
+        // Rows start on a word, so each one is swapped on its own rather than once packed
+        let word_size = self.endian.word_size();
+        for slice_index in 0..slices {
+            for row in 0..rows {
+                let source = slice(
+                    data,
+                    slice_index * slice_size + row * pitch,
+                    row_size.next_multiple_of(word_size),
+                )?;
+                let offset = (slice_index * rows + row) * row_size;
+                let words = source.chunks_exact(word_size);
+                for (dest, source) in dest[offset..offset + row_size]
+                    .chunks_mut(word_size)
+                    .zip(words)
+                {
+                    let mut word = [0u8; 4];
+                    let word = &mut word[..word_size];
+                    word.copy_from_slice(source);
+                    self.endian.swap_in_place(word);
+                    dest.copy_from_slice(&word[..dest.len()]);
+                }
+            }
+        }
+
+        self.swizzle.apply(dest, self.format);
+        Ok(chain)
+    }
+}
+
+/// An XPR2 resource package, borrowing its data from the file
+#[derive(Debug, Clone, Copy)]
+pub struct Xpr2<'a> {
+    /// The header from the resource count on, which resource offsets are relative to
+    header: &'a [u8],
+    data: &'a [u8],
+    resource_count: usize,
+}
+
+impl<'a> Xpr2<'a> {
+    /// Parses the header of an XPR2 package. Resources are only parsed by [`Xpr2::texture`].
+    pub fn read(file: &'a [u8]) -> Result<Self, SwizzleError> {
+        let magic = read_u32_be(file, 0)?;
+        if magic != MAGIC {
+            return Err(SwizzleError::InvalidMagic(magic));
+        }
+        let header_size = read_u32_be(file, 4)? as usize;
+        let data_size = read_u32_be(file, 8)? as usize;
+
+        let header = slice(file, HEADER_BASE, header_size)?;
+        let resource_count = read_u32_be(header, 0)? as usize;
+        slice(header, 4, resource_count * RESOURCE_SIZE)?;
+
+        Ok(Xpr2 {
+            header,
+            data: slice(file, HEADER_BASE + header_size, data_size)?,
+            resource_count,
+        })
+    }
+
+    /// Number of resources in the package, textures or not
+    pub fn len(&self) -> usize {
+        self.resource_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resource_count == 0
+    }
+
+    /// The four character type of a resource, such as `TX2D`
+    pub fn resource_type(&self, index: usize) -> Result<[u8; 4], SwizzleError> {
+        let resource = self.resource(index)?;
+        Ok(resource[..4].try_into().unwrap())
+    }
+
+    /// Parses a texture resource (`TX2D`, `TX3D`, `TXCM` or `TXAR`)
+    pub fn texture(&self, index: usize) -> Result<Xpr2Texture<'a>, SwizzleError> {
+        let resource = self.resource(index)?;
+        let resource_type = read_u32_be(resource, 0)?;
+        if !resource.starts_with(b"TX") {
+            return Err(SwizzleError::InvalidTextureType(resource_type));
+        }
+        let offset = read_u32_be(resource, 4)? as usize;
+        let name_offset = read_u32_be(resource, 12)? as usize;
+
+        let mut words = [0; 6];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = read_u32_be(self.header, offset + TEXTURE_RESOURCE_SIZE + i * 4)?;
+        }
+        let fetch = FetchConstant::from_words(words)?;
+
+        let name = self.header.get(name_offset..).unwrap_or_default();
+        let name = &name[..name.iter().position(|&c| c == 0).unwrap_or(name.len())];
+
+        Ok(Xpr2Texture {
+            name,
+            fetch,
+            data: self
+                .data
+                .get(fetch.base_address as usize..)
+                .ok_or(SwizzleError::OutOfBounds(TextureSlice::Source))?,
+        })
+    }
+
+    fn resource(&self, index: usize) -> Result<&'a [u8], SwizzleError> {
+        if index >= self.resource_count {
+            return Err(SwizzleError::OutOfBounds(TextureSlice::Source));
+        }
+        slice(self.header, 4 + index * RESOURCE_SIZE, RESOURCE_SIZE)
+    }
+}
+
+/// A texture of an XPR2 package
+#[derive(Debug, Clone, Copy)]
+pub struct Xpr2Texture<'a> {
+    name: &'a [u8],
+    fetch: FetchConstant,
+    data: &'a [u8],
+}
+
+impl<'a> Xpr2Texture<'a> {
+    /// Name of the resource, usually ASCII
+    pub fn name(&self) -> &'a [u8] {
+        self.name
+    }
+
+    pub fn fetch_constant(&self) -> &FetchConstant {
+        &self.fetch
+    }
+
+    /// The data section of the package from the base address of the texture on
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// See [`FetchConstant::deswizzle`]
+    pub fn deswizzle(&self, dest: &mut [u8]) -> Result<MipChain, SwizzleError> {
+        self.fetch.deswizzle(self.data, dest)
+    }
+}